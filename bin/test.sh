@@ -10,6 +10,7 @@ cargo test --features "cache-multiplication" --verbose --workspace
 cargo check --features "prime-two concurrent" --workspace
 cargo clippy --workspace --all-targets -- -D warnings
 cargo clippy --workspace --features "cache-multiplication concurrent" --all-targets -- -D warnings -A unused_imports
+cbindgen --config cbindgen.toml --crate ext --output include/ext.h --verify
 
 cd crates
 cargo test --workspace
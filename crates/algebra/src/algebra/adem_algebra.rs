@@ -6,7 +6,7 @@ use parking_lot::Mutex;
 
 use once::OnceVec;
 use crate::algebra::combinatorics::{self, MAX_XI_TAU};
-use crate::algebra::{Algebra, Bialgebra};
+use crate::algebra::{Algebra, Bialgebra, HopfAlgebra};
 use fp::prime::ValidPrime;
 use fp::vector::{FpVector, FpVectorT};
 use nom::{
@@ -1290,6 +1290,63 @@ impl Bialgebra for AdemAlgebra {
     }
 }
 
+impl AdemAlgebra {
+    /// Returns the list $\chi(P^0), \ldots, \chi(P^n)$ (or $\chi(Sq^i)$ when p = 2). These are
+    /// computed using the recursion $\sum_i P^i \chi(P^{n - i}) = 0$ for $n > 0$.
+    fn antipode_pn(&self, n : u32) -> Vec<FpVector> {
+        let p = self.prime();
+        let mut result : Vec<FpVector> = Vec::with_capacity(n as usize + 1);
+
+        let mut unit = FpVector::new(p, 1);
+        unit.set_entry(0, 1);
+        result.push(unit);
+
+        for k in 1 ..= n {
+            let degree = self.beps_pn(0, k).0;
+            let mut chi = FpVector::new(p, self.dimension(degree, -1));
+            for i in 1 ..= k {
+                let (op_deg, op_idx) = self.beps_pn(0, i);
+                self.multiply_basis_element_by_element(&mut chi, *p - 1, op_deg, op_idx, degree - op_deg, &result[(k - i) as usize], -1);
+            }
+            result.push(chi);
+        }
+        result
+    }
+}
+
+impl HopfAlgebra for AdemAlgebra {
+    fn antipode_on_basis(&self, result : &mut FpVector, coeff : u32, degree : i32, idx : usize) {
+        let p = self.prime();
+        let elt = &self.basis_table[degree as usize][idx];
+        let chi_pn = self.antipode_pn(elt.ps.iter().copied().max().unwrap_or(0));
+        let q = if self.generic { 2 * *p - 2 } else { 1 };
+
+        // χ is an anti-automorphism and `decompose` lists the factors in reverse order, so χ of
+        // the element is the product of the χ of the factors in the order they are listed, up to
+        // a sign coming from the Bocksteins.
+        let mut cur_degree = 0;
+        let mut cur = FpVector::new(p, 1);
+        cur.set_entry(0, 1);
+        let mut bocksteins = 0;
+        for (op_deg, op_idx) in self.decompose(degree, idx) {
+            let mut next = FpVector::new(p, self.dimension(cur_degree + op_deg, -1));
+            if self.generic && op_deg == 1 {
+                bocksteins += 1;
+                self.multiply_element_by_basis_element(&mut next, *p - 1, cur_degree, &cur, op_deg, op_idx, -1);
+            } else {
+                let chi = &chi_pn[op_deg as usize / q as usize];
+                self.multiply_element_by_element(&mut next, 1, cur_degree, &cur, op_deg, chi, -1);
+            }
+            cur = next;
+            cur_degree += op_deg;
+        }
+
+        // Reversing the order of the Bocksteins introduces a sign.
+        let coeff = if (bocksteins * (bocksteins - 1) / 2) % 2 == 1 { (*p - coeff) % *p } else { coeff };
+        result.add(&cur, coeff);
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
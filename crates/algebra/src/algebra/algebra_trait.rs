@@ -70,6 +70,11 @@ pub trait Algebra : Send + Sync + 'static {
     /// degree, index)` for whom we want to compute products with in the resolutions.
     fn default_filtration_one_products(&self) -> Vec<(String, i32, usize)> { Vec::new() }
 
+    /// The augmentation of the basis element of degree 0 with index `idx`. A connected algebra
+    /// has a single basis element in degree 0, namely the unit, so the default returns 1. This is
+    /// also correct for group algebras, whose basis elements are group elements.
+    fn augmentation(&self, _idx : usize) -> u32 { 1 }
+
     /// Converts a JSON object into a basis element. The way basis elements are represented by JSON
     /// objects is to be specified by the algebra itself, and will be used by module
     /// specifications.
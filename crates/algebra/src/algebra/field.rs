@@ -3,7 +3,7 @@ use serde_json::json;
 
 use fp::prime::ValidPrime;
 use fp::vector::{FpVector, FpVectorT};
-use crate::algebra::{Algebra, Bialgebra};
+use crate::algebra::{Algebra, Bialgebra, HopfAlgebra};
 
 pub struct Field {
     prime : ValidPrime
@@ -93,3 +93,9 @@ impl Bialgebra for Field {
         vec![(1, 0)]
     }
 }
+
+impl HopfAlgebra for Field {
+    fn antipode_on_basis(&self, result : &mut FpVector, coeff : u32, _degree : i32, idx : usize) {
+        result.add_basis_element(idx, coeff);
+    }
+}
@@ -0,0 +1,494 @@
+use std::collections::HashMap;
+
+use error::GenericError;
+use fp::prime::ValidPrime;
+use fp::vector::{FpVector, FpVectorT};
+use crate::algebra::{Algebra, Bialgebra, HopfAlgebra};
+use nom::{
+    IResult,
+    bytes::complete::tag,
+    character::complete::space1,
+    sequence::terminated,
+};
+use serde_json::value::Value;
+
+/// The group algebra $\mathbb{F}_p[G]$ of a finite group $G$, concentrated in degree 0. The basis
+/// elements are the elements of the group, and the identity always has index 0.
+///
+/// Resolving the trivial module $\mathbb{F}_p$ over this algebra computes the group cohomology
+/// $H^*(G; \mathbb{F}_p)$, where all classes live in internal degree 0. The resolution is only
+/// minimal when $G$ is a $p$-group, since otherwise $\mathbb{F}_p[G]$ is not local.
+pub struct GroupAlgebra {
+    p : ValidPrime,
+    name : String,
+    /// `multiplication_table[g][h]` is the index of the product $gh$.
+    multiplication_table : Vec<Vec<usize>>,
+    element_names : Vec<String>,
+    generators : Vec<usize>,
+    /// For each element $g$ that is neither the identity nor a generator, a pair $(x, h)$ with
+    /// $x$ a generator and $g = xh$, where $h$ is shorter than $g$ as a word in the generators.
+    decompositions : Vec<Option<(usize, usize)>>,
+}
+
+impl GroupAlgebra {
+    /// Constructs the group algebra from a multiplication table, where `table[g][h]` is the index
+    /// of $gh$. The element with index 0 must be the identity.
+    pub fn from_multiplication_table(p : ValidPrime, name : String, table : Vec<Vec<usize>>) -> error::Result<Self> {
+        check_multiplication_table(&table)?;
+
+        let element_names = (0 .. table.len())
+            .map(|g| if g == 0 { "1".to_string() } else { format!("g{}", g) })
+            .collect();
+
+        // Greedily pick generators, skipping elements of the subgroup generated so far.
+        let mut generators = Vec::new();
+        let mut subgroup = subgroup_generated_by(&table, &generators);
+        for g in 1 .. table.len() {
+            if !subgroup[g] {
+                generators.push(g);
+                subgroup = subgroup_generated_by(&table, &generators);
+            }
+        }
+
+        Ok(Self::new_unchecked(p, name, table, element_names, generators))
+    }
+
+    /// Constructs the group algebra of the subgroup of the symmetric group generated by
+    /// `permutations`. Each permutation is given by the list of images of $0, \ldots, n - 1$. The
+    /// generators are named `a`, `b`, `c`, ... in order, and the other elements are named after
+    /// the shortest word in the generators that represents them.
+    pub fn from_permutations(p : ValidPrime, name : String, permutations : Vec<Vec<usize>>) -> error::Result<Self> {
+        let n = permutations.first().map_or(0, Vec::len);
+        for perm in &permutations {
+            let mut seen = vec![false; n];
+            if perm.len() != n {
+                return Err(GenericError::new(format!("Permutation {:?} does not have length {}", perm, n)).into());
+            }
+            for &i in perm {
+                if i >= n || seen[i] {
+                    return Err(GenericError::new(format!("{:?} is not a permutation", perm)).into());
+                }
+                seen[i] = true;
+            }
+        }
+        if permutations.len() > 26 {
+            return Err(GenericError::new("At most 26 permutations are supported".to_string()).into());
+        }
+
+        // Enumerate the group by a breadth first search, which produces shortest words.
+        let identity : Vec<usize> = (0 .. n).collect();
+        let mut elements = vec![identity.clone()];
+        let mut element_names = vec!["1".to_string()];
+        let mut lookup = HashMap::new();
+        lookup.insert(identity, 0);
+
+        let mut generators = Vec::new();
+        let mut next = 0;
+        while next < elements.len() {
+            for (i, perm) in permutations.iter().enumerate() {
+                // The product x * g, where (x * g)(k) = x(g(k)).
+                let product : Vec<usize> = elements[next].iter().map(|&k| perm[k]).collect();
+                let idx = match lookup.get(&product) {
+                    Some(&idx) => idx,
+                    None => {
+                        let idx = elements.len();
+                        let letter = (b'a' + i as u8) as char;
+                        let name = if next == 0 { letter.to_string() } else { format!("{}{}", letter, element_names[next]) };
+                        lookup.insert(product.clone(), idx);
+                        elements.push(product);
+                        element_names.push(name);
+                        idx
+                    }
+                };
+                if next == 0 && idx != 0 && !generators.contains(&idx) {
+                    generators.push(idx);
+                }
+            }
+            next += 1;
+        }
+
+        let table = elements.iter()
+            .map(|g| elements.iter()
+                .map(|h| lookup[&h.iter().map(|&k| g[k]).collect::<Vec<_>>()])
+                .collect())
+            .collect();
+
+        Ok(Self::new_unchecked(p, name, table, element_names, generators))
+    }
+
+    /// The group algebra of the cyclic group of order `n`, with generator `a`.
+    pub fn cyclic(p : ValidPrime, n : usize) -> Self {
+        Self::abelian(p, &[n])
+    }
+
+    /// The group algebra of the elementary abelian group $(\mathbb{Z}/p)^{\mathrm{rank}}$, with
+    /// generators `a`, `b`, `c`, ....
+    pub fn elementary_abelian(p : ValidPrime, rank : usize) -> Self {
+        Self::abelian(p, &vec![*p as usize; rank])
+    }
+
+    /// The group algebra of the abelian group $\mathbb{Z}/n_1 \times \cdots \times \mathbb{Z}/n_k$,
+    /// where `orders` is $[n_1, \ldots, n_k]$. The generators are named `a`, `b`, `c`, ....
+    pub fn abelian(p : ValidPrime, orders : &[usize]) -> Self {
+        assert!(orders.len() <= 26, "At most 26 factors are supported");
+        assert!(orders.iter().all(|&n| n > 0), "Cyclic groups must have positive order");
+
+        let order : usize = orders.iter().product();
+        // Element i corresponds to the exponents of its mixed radix expansion.
+        let exponents = |mut i : usize| -> Vec<usize> {
+            orders.iter().map(|&n| { let e = i % n; i /= n; e }).collect()
+        };
+        let index = |exps : &[usize]| -> usize {
+            exps.iter().zip(orders).rev().fold(0, |acc, (&e, &n)| acc * n + e)
+        };
+
+        let table = (0 .. order).map(|g| {
+            let g = exponents(g);
+            (0 .. order).map(|h| {
+                let h = exponents(h);
+                let gh : Vec<usize> = g.iter().zip(&h).zip(orders).map(|((a, b), n)| (a + b) % n).collect();
+                index(&gh)
+            }).collect()
+        }).collect();
+
+        let element_names = (0 .. order).map(|g| {
+            let name : String = exponents(g).into_iter().enumerate()
+                .map(|(i, e)| power_name((b'a' + i as u8) as char, e))
+                .collect();
+            if name.is_empty() { "1".to_string() } else { name }
+        }).collect();
+
+        let generators = (0 .. orders.len())
+            .filter(|&i| orders[i] > 1)
+            .map(|i| {
+                let mut exps = vec![0; orders.len()];
+                exps[i] = 1;
+                index(&exps)
+            })
+            .collect();
+
+        let name = orders.iter().map(|n| format!("C{}", n)).collect::<Vec<_>>().join("x");
+        Self::new_unchecked(p, name, table, element_names, generators)
+    }
+
+    /// The group algebra of the dihedral group of order `2 * n`, i.e. the symmetries of a regular
+    /// `n`-gon. It is generated by a rotation `r` and a reflection `s`.
+    pub fn dihedral(p : ValidPrime, n : usize) -> Self {
+        assert!(n > 0, "Dihedral groups must have positive order");
+
+        // The element r^i s^j has index i + n * j.
+        let table = (0 .. 2 * n).map(|g| {
+            let (i, j) = (g % n, g / n);
+            (0 .. 2 * n).map(|h| {
+                let (k, l) = (h % n, h / n);
+                // r^i s^j r^k s^l = r^{i + (-1)^j k} s^{j + l}
+                let k = if j == 0 { k } else { (n - k) % n };
+                (i + k) % n + n * ((j + l) % 2)
+            }).collect()
+        }).collect();
+
+        let element_names = (0 .. 2 * n).map(|g| {
+            let name = power_name('r', g % n) + &power_name('s', g / n);
+            if name.is_empty() { "1".to_string() } else { name }
+        }).collect();
+
+        let generators = if n > 1 { vec![1, n] } else { vec![n] };
+
+        Self::new_unchecked(p, format!("D{}", 2 * n), table, element_names, generators)
+    }
+
+    fn new_unchecked(
+        p : ValidPrime,
+        group_name : String,
+        multiplication_table : Vec<Vec<usize>>,
+        element_names : Vec<String>,
+        generators : Vec<usize>
+    ) -> Self {
+        fp::vector::initialize_limb_bit_index_table(p);
+
+        let order = multiplication_table.len();
+        let mut decompositions = vec![None; order];
+        let mut reached = vec![false; order];
+        reached[0] = true;
+        for &x in &generators {
+            reached[x] = true;
+        }
+        // Breadth first search from the generators, so that h is always reached before xh.
+        let mut queue : Vec<usize> = generators.clone();
+        let mut next = 0;
+        while next < queue.len() {
+            let h = queue[next];
+            for &x in &generators {
+                let g = multiplication_table[x][h];
+                if !reached[g] {
+                    reached[g] = true;
+                    decompositions[g] = Some((x, h));
+                    queue.push(g);
+                }
+            }
+            next += 1;
+        }
+
+        Self {
+            p,
+            name : format!("F{}[{}]", p, group_name),
+            multiplication_table,
+            element_names,
+            generators,
+            decompositions,
+        }
+    }
+
+    /// The order of the group.
+    pub fn order(&self) -> usize {
+        self.multiplication_table.len()
+    }
+
+    /// The index of the product $gh$.
+    pub fn product(&self, g : usize, h : usize) -> usize {
+        self.multiplication_table[g][h]
+    }
+
+    /// The index of the inverse of $g$.
+    pub fn inverse(&self, g : usize) -> usize {
+        self.multiplication_table[g].iter().position(|&gh| gh == 0).unwrap()
+    }
+}
+
+fn power_name(letter : char, exponent : usize) -> String {
+    match exponent {
+        0 => String::new(),
+        1 => letter.to_string(),
+        e => format!("{}^{}", letter, e),
+    }
+}
+
+/// Returns a list whose gth entry is whether g lies in the subgroup generated by `generators`.
+fn subgroup_generated_by(table : &[Vec<usize>], generators : &[usize]) -> Vec<bool> {
+    let mut result = vec![false; table.len()];
+    result[0] = true;
+    let mut queue = vec![0];
+    while let Some(h) = queue.pop() {
+        for &x in generators {
+            let g = table[x][h];
+            if !result[g] {
+                result[g] = true;
+                queue.push(g);
+            }
+        }
+    }
+    result
+}
+
+fn check_multiplication_table(table : &[Vec<usize>]) -> error::Result<()> {
+    let n = table.len();
+    let invalid = |reason : String| Err(GenericError::new(format!("Invalid multiplication table: {}", reason)).into());
+
+    if n == 0 {
+        return invalid("the group is empty".to_string());
+    }
+    for (g, row) in table.iter().enumerate() {
+        if row.len() != n {
+            return invalid(format!("row {} has length {} instead of {}", g, row.len(), n));
+        }
+        if let Some(&gh) = row.iter().find(|&&gh| gh >= n) {
+            return invalid(format!("entry {} in row {} is out of range", gh, g));
+        }
+    }
+    if (0 .. n).any(|g| table[0][g] != g || table[g][0] != g) {
+        return invalid("element 0 is not the identity".to_string());
+    }
+    // Since the table is finite, having a left inverse for every element is enough.
+    if let Some(g) = (0 .. n).find(|&g| !table.iter().any(|row| row[g] == 0)) {
+        return invalid(format!("element {} has no inverse", g));
+    }
+    for g in 0 .. n {
+        for h in 0 .. n {
+            for k in 0 .. n {
+                if table[table[g][h]][k] != table[g][table[h][k]] {
+                    return invalid(format!("({} * {}) * {} != {} * ({} * {})", g, h, k, g, h, k));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Algebra for GroupAlgebra {
+    fn algebra_type(&self) -> &str {
+        "group"
+    }
+
+    fn prime(&self) -> ValidPrime {
+        self.p
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn max_degree(&self) -> i32 {
+        i32::MAX
+    }
+
+    fn compute_basis(&self, _degree : i32) {}
+
+    fn dimension(&self, degree : i32, _excess : i32) -> usize {
+        if degree == 0 { self.order() } else { 0 }
+    }
+
+    fn multiply_basis_elements(&self, result : &mut FpVector, coeff : u32, r_degree : i32, r_idx : usize, s_degree: i32, s_idx : usize, _excess : i32) {
+        debug_assert!(r_degree == 0 && s_degree == 0);
+        result.add_basis_element(self.multiplication_table[r_idx][s_idx], coeff);
+    }
+
+    fn json_to_basis(&self, json : Value) -> error::Result<(i32, usize)> {
+        let name = json.as_str()
+            .ok_or_else(|| GenericError::new(format!("Expected group element name, found {}", json)))?;
+        self.element_names.iter()
+            .position(|x| x == name)
+            .map(|idx| (0, idx))
+            .ok_or_else(|| GenericError::new(format!("Unknown group element: {}", name)).into())
+    }
+
+    fn json_from_basis(&self, degree : i32, idx : usize) -> Value {
+        assert!(degree == 0);
+        Value::String(self.element_names[idx].clone())
+    }
+
+    fn basis_element_to_string(&self, degree : i32, idx : usize) -> String {
+        assert!(degree == 0);
+        self.element_names[idx].clone()
+    }
+
+    fn generators(&self, degree : i32) -> Vec<usize> {
+        if degree == 0 {
+            self.generators.clone()
+        } else {
+            vec![]
+        }
+    }
+
+    fn string_to_generator<'a, 'b>(&'a self, input: &'b str) -> IResult<&'b str, (i32, usize)> {
+        // Try longer names first so that no name is shadowed by one of its prefixes.
+        let mut generators = self.generators.clone();
+        generators.sort_by_key(|&g| std::cmp::Reverse(self.element_names[g].len()));
+        for g in generators {
+            let result : IResult<&'b str, &'b str> = terminated(tag(&*self.element_names[g]), space1)(input);
+            if let Ok((rest, _)) = result {
+                return Ok((rest, (0, g)));
+            }
+        }
+        Err(nom::Err::Error((input, nom::error::ErrorKind::Tag)))
+    }
+
+    /// Since everything lies in degree 0, the factors do not have smaller degree. Instead, the
+    /// second factor is a shorter word in the generators.
+    fn decompose_basis_element(&self, _degree : i32, idx : usize) -> Vec<(u32, (i32, usize), (i32, usize))> {
+        match self.decompositions[idx] {
+            Some((x, h)) => vec![(1, (0, x), (0, h))],
+            None => vec![],
+        }
+    }
+
+    fn relations_to_check(&self, _degree : i32) -> Vec<Vec<(u32, (i32, usize), (i32, usize))>> {
+        vec![]
+    }
+}
+
+impl Bialgebra for GroupAlgebra {
+    /// Group elements are group-like, i.e. $\Delta g = g \otimes g$.
+    fn coproduct(&self, op_deg : i32, op_idx : usize) -> Vec<(i32, usize, i32, usize)> {
+        vec![(op_deg, op_idx, op_deg, op_idx)]
+    }
+
+    fn decompose(&self, op_deg : i32, op_idx : usize) -> Vec<(i32, usize)> {
+        vec![(op_deg, op_idx)]
+    }
+}
+
+impl HopfAlgebra for GroupAlgebra {
+    /// The antipode sends $g$ to $g^{-1}$.
+    fn antipode_on_basis(&self, result : &mut FpVector, coeff : u32, _degree : i32, idx : usize) {
+        result.add_basis_element(self.inverse(idx), coeff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_group_axioms(algebra : &GroupAlgebra) {
+        let table : Vec<Vec<usize>> = (0 .. algebra.order())
+            .map(|g| (0 .. algebra.order()).map(|h| algebra.product(g, h)).collect())
+            .collect();
+        check_multiplication_table(&table).unwrap();
+        let subgroup = subgroup_generated_by(&table, &algebra.generators(0));
+        assert!(subgroup.into_iter().all(|x| x));
+    }
+
+    #[test]
+    fn test_group_algebra_builtins() {
+        let p = ValidPrime::new(2);
+
+        let c4 = GroupAlgebra::cyclic(p, 4);
+        check_group_axioms(&c4);
+        assert_eq!(c4.dimension(0, 0), 4);
+        assert_eq!(c4.dimension(1, 0), 0);
+        assert_eq!(c4.basis_element_to_string(0, 3), "a^3");
+        assert_eq!(c4.inverse(1), 3);
+
+        let v4 = GroupAlgebra::elementary_abelian(p, 2);
+        check_group_axioms(&v4);
+        assert_eq!(v4.order(), 4);
+        assert_eq!(v4.generators(0).len(), 2);
+        assert_eq!(v4.basis_element_to_string(0, 3), "ab");
+
+        let d8 = GroupAlgebra::dihedral(p, 4);
+        check_group_axioms(&d8);
+        assert_eq!(d8.order(), 8);
+        // s r s = r^{-1}
+        let (r, s) = (1, 4);
+        assert_eq!(d8.product(d8.product(s, r), s), d8.inverse(r));
+        assert_eq!(d8.basis_element_to_string(0, 7), "r^3s");
+    }
+
+    #[test]
+    fn test_group_algebra_from_permutations() {
+        let p = ValidPrime::new(3);
+        let s3 = GroupAlgebra::from_permutations(p, "S3".to_string(), vec![vec![1, 0, 2], vec![1, 2, 0]]).unwrap();
+        check_group_axioms(&s3);
+        assert_eq!(s3.order(), 6);
+        assert_eq!(s3.generators(0), vec![1, 2]);
+        assert_eq!(s3.basis_element_to_string(0, 1), "a");
+        assert_eq!(s3.basis_element_to_string(0, 2), "b");
+
+        assert_eq!(s3.string_to_generator("b x0").unwrap(), ("x0", (0, 2)));
+
+        for g in 0 .. s3.order() {
+            for (c, (_, x), (_, h)) in s3.decompose_basis_element(0, g) {
+                let mut result = FpVector::new(p, s3.order());
+                s3.multiply_basis_elements(&mut result, c, 0, x, 0, h, 0);
+                assert_eq!(result.entry(g), 1);
+            }
+        }
+
+        assert!(GroupAlgebra::from_permutations(p, "".to_string(), vec![vec![0, 0]]).is_err());
+    }
+
+    #[test]
+    fn test_group_algebra_from_multiplication_table() {
+        let p = ValidPrime::new(2);
+        let c3 = GroupAlgebra::from_multiplication_table(p, "C3".to_string(),
+            vec![vec![0, 1, 2], vec![1, 2, 0], vec![2, 0, 1]]).unwrap();
+        assert_eq!(c3.generators(0), vec![1]);
+        assert_eq!(c3.decompose_basis_element(0, 2), vec![(1, (0, 1), (0, 1))]);
+
+        // Not associative
+        assert!(GroupAlgebra::from_multiplication_table(p, "".to_string(),
+            vec![vec![0, 1, 2], vec![1, 0, 0], vec![2, 2, 0]]).is_err());
+        // 0 is not the identity
+        assert!(GroupAlgebra::from_multiplication_table(p, "".to_string(),
+            vec![vec![1, 0], vec![0, 1]]).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+use crate::algebra::Bialgebra;
+use fp::vector::FpVector;
+
+/// A Hopf algebra is a bialgebra with an antipode (or conjugation) χ. This is the unique linear map
+/// satisfying $\sum x' \chi(x'') = \epsilon(x)$, where $\Delta x = \sum x' \otimes x''$. It is an
+/// anti-automorphism of the algebra, i.e. $\chi(xy) = (-1)^{|x||y|} \chi(y) \chi(x)$.
+///
+/// The antipode is needed to turn left modules into right modules, and hence to define the
+/// action on duals and Hom modules.
+pub trait HopfAlgebra : Bialgebra {
+    /// Computes χ of the basis element `(degree, idx)` and *adds* `coeff` times the result to
+    /// `result`.
+    fn antipode_on_basis(&self, result : &mut FpVector, coeff : u32, degree : i32, idx : usize);
+
+    /// Computes χ of the element `input` of degree `degree` and *adds* `coeff` times the result to
+    /// `result`.
+    fn antipode(&self, result : &mut FpVector, coeff : u32, degree : i32, input : &FpVector) {
+        let p = *self.prime();
+        for (i, v) in input.iter().enumerate() {
+            if v == 0 {
+                continue;
+            }
+            self.antipode_on_basis(result, (coeff * v) % p, degree, i);
+        }
+    }
+}
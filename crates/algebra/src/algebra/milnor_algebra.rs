@@ -6,7 +6,7 @@ use once::OnceVec;
 use fp::prime::ValidPrime;
 use fp::vector::{FpVector, FpVectorT};
 use crate::algebra::combinatorics;
-use crate::algebra::{Algebra, Bialgebra};
+use crate::algebra::{Algebra, Bialgebra, HopfAlgebra};
 
 use nom::{
     IResult,
@@ -111,8 +111,10 @@ pub struct MilnorAlgebra {
     qpart_table : Vec<OnceVec<QPart>>,
     basis_table : OnceVec<Vec<MilnorBasisElement>>,
     basis_element_to_index_map : OnceVec<HashMap<MilnorBasisElement, usize>>, // degree -> MilnorBasisElement -> index
-    #[cfg(feature = "cache-multiplication")]
-    multiplication_table : OnceVec<OnceVec<Vec<Vec<FpVector>>>> // source_deg -> target_deg -> source_op -> target_op
+    /// The products of basis elements. This is only populated if the algebra is finite dimensional
+    /// or the `cache-multiplication` feature is enabled; see `use_multiplication_table`.
+    multiplication_table : OnceVec<OnceVec<Vec<Vec<FpVector>>>>, // source_deg -> target_deg -> source_op -> target_op
+    antipode_table : Mutex<HashMap<PPart, FpVector>>, // P(R) -> χ(P(R)), populated on demand
 }
 
 impl MilnorAlgebra {
@@ -138,8 +140,8 @@ impl MilnorAlgebra {
             qpart_table,
             basis_table : OnceVec::new(),
             basis_element_to_index_map : OnceVec::new(),
-            #[cfg(feature = "cache-multiplication")]
-            multiplication_table : OnceVec::new()
+            multiplication_table : OnceVec::new(),
+            antipode_table : Mutex::new(HashMap::new()),
         }
     }
 
@@ -154,6 +156,38 @@ impl MilnorAlgebra {
     pub fn basis_element_to_index(&self, elt : &MilnorBasisElement) -> usize {
         self.try_basis_element_to_index(elt).unwrap_or_else(|| panic!("Didn't find element: {:?}", elt))
     }
+
+    /// Returns the degree of the top class if the algebra is finite dimensional, i.e. the profile
+    /// is truncated. For example, this is 23 for A(2). Returns `None` if the algebra is infinite
+    /// dimensional (or if the top degree does not fit in an `i32`).
+    pub fn top_degree(&self) -> Option<i32> {
+        if !self.profile.truncated {
+            return None;
+        }
+        let p = *self.prime();
+        let q = if self.generic { 2 * p as i32 - 2 } else { 1 };
+
+        let mut top : i32 = 0;
+        for (&e, &xi_degree) in self.profile.p_part.iter().zip(combinatorics::xi_degrees(self.prime())) {
+            let max = p.checked_pow(e)? - 1;
+            top = top.checked_add((max as i32).checked_mul(xi_degree)?.checked_mul(q)?)?;
+        }
+        if self.generic {
+            for (k, &tau_degree) in combinatorics::tau_degrees(self.prime()).iter().enumerate() {
+                if self.profile.q_part & (1 << k) != 0 {
+                    top = top.checked_add(tau_degree)?;
+                }
+            }
+        }
+        Some(top)
+    }
+
+    /// Whether products are read off from a precomputed multiplication table. This is the case
+    /// when the algebra is finite dimensional, e.g. A(n), where the table is small and the
+    /// general multiplication algorithm dominates the cost of module actions.
+    fn use_multiplication_table(&self) -> bool {
+        cfg!(feature = "cache-multiplication") || self.top_degree().is_some()
+    }
 }
 
 impl Algebra for MilnorAlgebra {
@@ -247,8 +281,7 @@ impl Algebra for MilnorAlgebra {
             self.basis_element_to_index_map.push(map);
         }
 
-        #[cfg(feature = "cache-multiplication")]
-        {
+        if self.use_multiplication_table() {
             for d in 0 ..= max_degree as usize {
                 if self.multiplication_table.len() == d {
                     self.multiplication_table.push(OnceVec::new());
@@ -276,14 +309,12 @@ impl Algebra for MilnorAlgebra {
         self.basis_table[degree as usize].len()
     }
 
-    #[cfg(not(feature = "cache-multiplication"))]
-    fn multiply_basis_elements(&self, result : &mut FpVector, coef : u32, r_degree : i32, r_idx : usize, s_degree: i32, s_idx : usize, _excess : i32) {
-        self.multiply(result, coef, &self.basis_table[r_degree as usize][r_idx], &self.basis_table[s_degree as usize][s_idx]);
-    }
-
-    #[cfg(feature = "cache-multiplication")]
     fn multiply_basis_elements(&self, result : &mut FpVector, coef : u32, r_degree : i32, r_idx : usize, s_degree: i32, s_idx : usize, _excess : i32) {
-        result.shift_add(&self.multiplication_table[r_degree as usize][s_degree as usize][r_idx][s_idx], coef);
+        if let Some(products) = self.multiplication_table.get(r_degree as usize).and_then(|row| row.get(s_degree as usize)) {
+            result.shift_add(&products[r_idx][s_idx], coef);
+        } else {
+            self.multiply(result, coef, &self.basis_table[r_degree as usize][r_idx], &self.basis_table[s_degree as usize][s_idx]);
+        }
     }
 
     fn json_to_basis(&self, json : Value) -> error::Result<(i32, usize)> {
@@ -980,6 +1011,76 @@ impl MilnorAlgebra {
 }
 
 
+impl MilnorAlgebra {
+    fn ppart_degree(&self, p_part : &[u32]) -> i32 {
+        let q = if self.generic { 2 * *self.prime() as i32 - 2 } else { 1 };
+        let xi_degrees = combinatorics::xi_degrees(self.prime());
+        p_part.iter().zip(xi_degrees).map(|(&r, &d)| r as i32 * d * q).sum()
+    }
+
+    /// Computes $\chi(P(R))$ using the recursion $\sum_{R' + R'' = R} P(R') \chi(P(R'')) = 0$ for
+    /// $R \neq 0$. The results are memoized in `memo`, which is `antipode_table`, since the same
+    /// $R''$ appear repeatedly.
+    fn antipode_ppart(&self, p_part : &[u32], memo : &mut HashMap<PPart, FpVector>) -> FpVector {
+        let p = self.prime();
+        if p_part.iter().all(|&r| r == 0) {
+            let mut unit = FpVector::new(p, 1);
+            unit.set_entry(0, 1);
+            return unit;
+        }
+        if let Some(v) = memo.get(p_part) {
+            return v.clone();
+        }
+
+        let degree = self.ppart_degree(p_part);
+        let mut result = FpVector::new(p, self.dimension(degree, -1));
+
+        let mut left = vec![0; p_part.len()];
+        while !Self::increment_p_part(&mut left, p_part) {
+            let mut right = p_part.iter().zip(&left).map(|(r, l)| r - l).collect::<Vec<_>>();
+            while let Some(0) = right.last() {
+                right.pop();
+            }
+            let mut left_ppart = left.clone();
+            while let Some(0) = left_ppart.last() {
+                left_ppart.pop();
+            }
+            let left_degree = self.ppart_degree(&left_ppart);
+            let left_idx = self.basis_element_to_index(&from_p(left_ppart, left_degree));
+
+            let chi_right = self.antipode_ppart(&right, memo);
+            self.multiply_basis_element_by_element(&mut result, *p - 1, left_degree, left_idx, degree - left_degree, &chi_right, -1);
+        }
+        memo.insert(p_part.to_vec(), result.clone());
+        result
+    }
+}
+
+impl HopfAlgebra for MilnorAlgebra {
+    /// We use $\chi(Q(E) P(R)) = \chi(P(R)) \chi(Q(E))$, which holds since $P(R)$ has even degree.
+    /// Here $\chi(Q(E)) = (-1)^{|E|} Q(E)$ because the $Q_i$ are primitive and anticommute.
+    fn antipode_on_basis(&self, result : &mut FpVector, coeff : u32, degree : i32, idx : usize) {
+        let p = *self.prime();
+        let elt = &self.basis_table[degree as usize][idx];
+        let chi_ppart = self.antipode_ppart(&elt.p_part, &mut self.antipode_table.lock());
+
+        if elt.q_part == 0 {
+            result.add(&chi_ppart, coeff);
+            return;
+        }
+
+        let coeff = if elt.q_part.count_ones() % 2 == 1 { (p - coeff) % p } else { coeff };
+        let ppart_degree = self.ppart_degree(&elt.p_part);
+        let qpart_degree = degree - ppart_degree;
+        let qpart_idx = self.basis_element_to_index(&MilnorBasisElement {
+            q_part : elt.q_part,
+            p_part : vec![],
+            degree : qpart_degree,
+        });
+        self.multiply_element_by_basis_element(result, coeff, ppart_degree, &chi_ppart, qpart_degree, qpart_idx, -1);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1037,6 +1138,44 @@ mod tests {
         }
     }
 
+    #[rstest(p, q_part, p_part, top_degree, total_dimension,
+        case(2, 0, vec![2, 1], 6, 8), // A(1)
+        case(2, 0, vec![3, 2, 1], 23, 64), // A(2)
+        case(3, 0b11, vec![1], 14, 12)
+    )]
+    fn test_milnor_finite_profile(p : u32, q_part : u32, p_part : Vec<u32>, top_degree : i32, total_dimension : usize) {
+        let p = ValidPrime::new(p);
+        let mut algebra = MilnorAlgebra::new(p);
+        algebra.profile = MilnorProfile { truncated : true, q_part, p_part };
+        assert_eq!(algebra.top_degree(), Some(top_degree));
+
+        let max_degree = top_degree + 5;
+        algebra.compute_basis(max_degree);
+        assert!(!algebra.multiplication_table.is_empty());
+        assert_eq!((0 ..= max_degree).map(|i| algebra.dimension(i, -1)).sum::<usize>(), total_dimension);
+        assert_eq!(algebra.dimension(top_degree, -1), 1);
+
+        // Compare the multiplication table against the general algorithm
+        for r_degree in 0 ..= max_degree {
+            for s_degree in 0 ..= max_degree - r_degree {
+                let dim = algebra.dimension(r_degree + s_degree, -1);
+                let mut table_result = FpVector::new(p, dim);
+                let mut result = FpVector::new(p, dim);
+                for r_idx in 0 .. algebra.dimension(r_degree, -1) {
+                    for s_idx in 0 .. algebra.dimension(s_degree, -1) {
+                        table_result.set_to_zero();
+                        result.set_to_zero();
+                        algebra.multiply_basis_elements(&mut table_result, 1, r_degree, r_idx, s_degree, s_idx, -1);
+                        algebra.multiply(&mut result, 1,
+                            algebra.basis_element_from_index(r_degree, r_idx),
+                            algebra.basis_element_from_index(s_degree, s_idx));
+                        assert_eq!(table_result, result);
+                    }
+                }
+            }
+        }
+    }
+
     use crate::module::ModuleFailedRelationError;
     #[rstest(p, max_degree,
         case(2, 32),
@@ -1077,6 +1216,34 @@ mod tests {
             }
         }
     }    
+    #[rstest(p, max_degree,
+        case(2, 32),
+        case(3, 80),
+        case(5, 120)
+    )]
+    fn test_milnor_bialgebra_decompose(p : u32, max_degree : i32) {
+        let p = ValidPrime::new(p);
+        let algebra = MilnorAlgebra::new(p);
+        algebra.compute_basis(max_degree);
+        for degree in 1 ..= max_degree {
+            for idx in 0 .. algebra.dimension(degree, -1) {
+                let decomposition = algebra.decompose(degree, idx);
+                let (mut working_degree, first_idx) = decomposition[0];
+                let mut product = FpVector::new(p, algebra.dimension(working_degree, -1));
+                product.set_entry(first_idx, 1);
+                for &(op_degree, op_idx) in &decomposition[1..] {
+                    let mut result = FpVector::new(p, algebra.dimension(working_degree + op_degree, -1));
+                    algebra.multiply_basis_element_by_element(&mut result, 1, op_degree, op_idx, working_degree, &product, -1);
+                    product = result;
+                    working_degree += op_degree;
+                }
+                let mut expected = FpVector::new(p, algebra.dimension(degree, -1));
+                expected.set_entry(idx, 1);
+                assert_eq!(working_degree, degree);
+                assert_eq!(product, expected, "Decomposition of {} is incorrect", algebra.basis_element_to_string(degree, idx));
+            }
+        }
+    }
 }
 
 impl MilnorAlgebra {
@@ -1095,14 +1262,22 @@ impl MilnorAlgebra {
 
 impl Bialgebra for MilnorAlgebra {
     fn coproduct(&self, op_deg : i32, op_idx : usize) -> Vec<(i32, usize, i32, usize)> {
-        assert_eq!(*self.prime(), 2, "Coproduct at odd primes not supported");
         if op_deg == 0 {
             return vec![(0, 0, 0, 0)];
         }
+        let elt = self.basis_element_from_index(op_deg, op_idx);
+        if elt.q_part != 0 {
+            // `decompose` only returns the Q_i as elements with a non-trivial q part, and these
+            // are primitive.
+            assert!(elt.q_part.count_ones() == 1 && elt.p_part.is_empty());
+            return vec![(op_deg, op_idx, 0, 0), (0, 0, op_deg, op_idx)];
+        }
+
+        let q = if self.generic { 2 * *self.prime() as i32 - 2 } else { 1 };
         let xi_degrees = combinatorics::xi_degrees(self.prime());
 
         let mut len = 1;
-        let p_part = &self.basis_element_from_index(op_deg, op_idx).p_part;
+        let p_part = &elt.p_part;
 
         for i in p_part.iter() {
             len *= i + 1;
@@ -1114,7 +1289,7 @@ impl Bialgebra for MilnorAlgebra {
         loop {
             let mut left_degree : i32 = 0;
             for i in 0 .. cur_ppart.len() {
-                left_degree += cur_ppart[i] as i32 * xi_degrees[i];
+                left_degree += cur_ppart[i] as i32 * xi_degrees[i] * q;
             }
             let right_degree : i32 = op_deg - left_degree;
 
@@ -1146,7 +1321,37 @@ impl Bialgebra for MilnorAlgebra {
         }
         result
     }
+    /// At odd primes, Q(E) P(R) = Q_{e_1} \cdots Q_{e_k} P(R) with e_1 < \cdots < e_k is
+    /// decomposed into its factors. The Q_i are primitive, and the coproduct of P(R) is given by
+    /// the same formula as at p = 2.
     fn decompose(&self, op_deg : i32, op_idx : usize) -> Vec<(i32, usize)> {
-        vec![(op_deg, op_idx)]
+        let elt = self.basis_element_from_index(op_deg, op_idx);
+        if elt.q_part == 0 || (elt.q_part.count_ones() == 1 && elt.p_part.is_empty()) {
+            return vec![(op_deg, op_idx)];
+        }
+        let tau_degrees = combinatorics::tau_degrees(self.prime());
+
+        let mut result = Vec::with_capacity(elt.q_part.count_ones() as usize + 1);
+        let mut p_degree = op_deg;
+        let mut qs = Vec::new();
+        for (i, &tau_degree) in tau_degrees.iter().enumerate() {
+            if elt.q_part & (1 << i) != 0 {
+                p_degree -= tau_degree;
+                qs.push((tau_degree, self.basis_element_to_index(&MilnorBasisElement {
+                    q_part : 1 << i,
+                    p_part : Vec::new(),
+                    degree : tau_degree,
+                })));
+            }
+        }
+        if p_degree > 0 {
+            result.push((p_degree, self.basis_element_to_index(&MilnorBasisElement {
+                q_part : 0,
+                p_part : elt.p_part.clone(),
+                degree : p_degree,
+            })));
+        }
+        result.extend(qs.into_iter().rev());
+        result
     }
 }
@@ -3,13 +3,17 @@ pub mod combinatorics;
 mod algebra_trait;
 mod steenrod_algebra;
 mod bialgebra_trait;
+mod hopf_algebra_trait;
 pub mod adem_algebra;
 pub mod milnor_algebra;
 pub mod field;
+pub mod group_algebra;
 
 pub use algebra_trait::Algebra;
 pub use steenrod_algebra::{SteenrodAlgebra, SteenrodAlgebraT, SteenrodAlgebraBorrow};
 pub use bialgebra_trait::Bialgebra;
+pub use hopf_algebra_trait::HopfAlgebra;
 pub use adem_algebra::AdemAlgebra;
 pub use milnor_algebra::MilnorAlgebra;
 pub use field::Field;
+pub use group_algebra::GroupAlgebra;
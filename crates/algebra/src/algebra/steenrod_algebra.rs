@@ -2,6 +2,7 @@ use fp::prime::ValidPrime;
 use fp::vector::FpVector;
 use crate::algebra::Algebra;
 use crate::algebra::Bialgebra;
+use crate::algebra::HopfAlgebra;
 use crate::algebra::AdemAlgebra;
 use crate::algebra::MilnorAlgebra;
 
@@ -55,6 +56,15 @@ impl Bialgebra for SteenrodAlgebra {
     }
 }
 
+impl HopfAlgebra for SteenrodAlgebra {
+    fn antipode_on_basis(&self, result : &mut FpVector, coeff : u32, degree : i32, idx : usize) {
+        match self {
+            SteenrodAlgebra::AdemAlgebra(a) => a.antipode_on_basis(result, coeff, degree, idx),
+            SteenrodAlgebra::MilnorAlgebra(a) => a.antipode_on_basis(result, coeff, degree, idx),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 struct MilnorProfileOption {
     truncated : Option<bool>,
@@ -0,0 +1,326 @@
+//! Import and export of data in the formats used by Bruner's `ext` programs. These only make
+//! sense at the prime 2.
+//!
+//! A module file in Bruner's format consists of
+//!  * a line containing the dimension $n$ of the module;
+//!  * a line containing the degrees of the basis elements $x_0, \ldots, x_{n - 1}$, separated by
+//!    spaces;
+//!  * for each non-zero action, a line `g r k j_1 ... j_k`, which says that
+//!    $$ \mathrm{Sq}^r x_g = x_{j_1} + \cdots + x_{j_k}. $$
+//!
+//! Actions that are not listed are zero. The actions of $\mathrm{Sq}^r$ for $r$ not a power of
+//! two are redundant, and are checked against the ones computed from the algebra generators.
+//!
+//! Elements of the Steenrod algebra are written in the Milnor basis, with $\mathrm{Sq}(r_1,
+//! \ldots, r_n)$ written as `i(r1,...,rn)`.
+
+use crate::algebra::milnor_algebra::MilnorBasisElement;
+use crate::algebra::{Algebra, MilnorAlgebra, SteenrodAlgebraBorrow, SteenrodAlgebraT};
+use crate::change_of_basis::adem_to_milnor_on_basis;
+use crate::margolis::milnor_to_algebra;
+use crate::module::{BoundedModule, FDModule, Module};
+use bivec::BiVec;
+use error::GenericError;
+use fp::prime::ValidPrime;
+use fp::vector::{FpVector, FpVectorT};
+
+use std::sync::Arc;
+
+fn check_prime(p: ValidPrime) -> error::Result<()> {
+    if *p != 2 {
+        return Err(GenericError::new(format!(
+            "Bruner's format is only supported at the prime 2, not {}",
+            p
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Returns $\mathrm{Sq}^r$ as an element of `algebra`, whose basis must be computed up to degree
+/// `r`.
+fn sq<A: SteenrodAlgebraT>(algebra: &A, r: u32) -> error::Result<FpVector> {
+    let elt = MilnorBasisElement {
+        q_part: 0,
+        p_part: vec![r],
+        degree: r as i32,
+    };
+    let mut result = FpVector::new(algebra.prime(), algebra.dimension(r as i32, -1));
+    milnor_to_algebra(algebra, &MilnorAlgebra::new(algebra.prime()), &mut result, &elt)?;
+    Ok(result)
+}
+
+fn parse_line(line: &str) -> error::Result<Vec<i64>> {
+    line.split_whitespace()
+        .map(|x| {
+            x.parse().map_err(|_| {
+                GenericError::new(format!(
+                    "Invalid line in Bruner module file: {}. This must be a list of integers",
+                    line
+                ))
+                .into()
+            })
+        })
+        .collect()
+}
+
+/// Parses a module in Bruner's format. The basis element $x_i$ is named `x{i}`. The actions are
+/// checked to define a valid module, and the resulting module is validated with
+/// `FDModule::check_validity`.
+pub fn fd_module_from_bruner<A: SteenrodAlgebraT>(
+    algebra: Arc<A>,
+    name: String,
+    input: &str,
+) -> error::Result<FDModule<A>> {
+    check_prime(algebra.prime())?;
+    let mut lines = input.lines().map(str::trim).filter(|x| !x.is_empty());
+
+    let dim = match lines.next().map(parse_line).transpose()?.as_deref() {
+        Some(&[n]) if n >= 0 => n as usize,
+        _ => {
+            return Err(GenericError::new(
+                "The first line of a Bruner module file must be the dimension of the module"
+                    .to_string(),
+            )
+            .into())
+        }
+    };
+    let degrees: Vec<i32> = lines
+        .next()
+        .map(parse_line)
+        .transpose()?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|x| x as i32)
+        .collect();
+    if degrees.len() != dim {
+        return Err(GenericError::new(format!(
+            "Expected {} basis elements but found {} degrees",
+            dim,
+            degrees.len()
+        ))
+        .into());
+    }
+
+    let min_degree = degrees.iter().copied().min().unwrap_or(0);
+    let max_degree = degrees.iter().copied().max().unwrap_or(0);
+    let mut graded_dimension = BiVec::from_vec(
+        min_degree,
+        vec![0; (max_degree - min_degree + 1) as usize],
+    );
+    // The (degree, index) of each basis element
+    let mut basis = Vec::with_capacity(dim);
+    for &d in &degrees {
+        basis.push((d, graded_dimension[d]));
+        graded_dimension[d] += 1;
+    }
+
+    let mut result = FDModule::new(Arc::clone(&algebra), name, graded_dimension);
+    for (i, &(d, idx)) in basis.iter().enumerate() {
+        result.set_basis_element_name(d, idx, format!("x{}", i));
+    }
+
+    // Actions of operations that are not algebra generators, to be checked at the end.
+    let mut redundant_actions = Vec::new();
+    for line in lines {
+        let data = parse_line(line)?;
+        if data.len() < 3 || data.len() != data[2] as usize + 3 {
+            return Err(GenericError::new(format!(
+                "Invalid action: {}. The third entry must be the number of terms in the sum",
+                line
+            ))
+            .into());
+        }
+        let get_basis = |i: i64| {
+            basis.get(i as usize).copied().ok_or_else(|| {
+                GenericError::new(format!("Invalid basis element x{} in action: {}", i, line))
+            })
+        };
+        let (input_degree, input_idx) = get_basis(data[0])?;
+        let r = data[1] as u32;
+        if data.len() == 3 && result.dimension(input_degree + r as i32) == 0 {
+            continue;
+        }
+        let mut output = FpVector::new(algebra.prime(), result.dimension(input_degree + r as i32));
+        for &j in &data[3..] {
+            let (output_degree, output_idx) = get_basis(j)?;
+            if output_degree != input_degree + r as i32 {
+                return Err(GenericError::new(format!("Action has wrong degree: {}", line)).into());
+            }
+            output.add_basis_element(output_idx, 1);
+        }
+        if r.is_power_of_two() {
+            let op = sq(&*algebra, r)?;
+            let op_idx = op.iter().position(|x| x != 0).unwrap();
+            result
+                .action_mut(r as i32, op_idx, input_degree, input_idx)
+                .add(&output, 1);
+        } else if r > 0 {
+            redundant_actions.push((r, data[0], input_degree, input_idx, output));
+        }
+    }
+
+    for input_degree in (result.min_degree()..=result.max_degree()).rev() {
+        for output_degree in input_degree + 1..=result.max_degree() {
+            result.extend_actions(input_degree, output_degree);
+            result.check_validity(input_degree, output_degree)?;
+        }
+    }
+
+    for (r, g, input_degree, input_idx, output) in redundant_actions {
+        let op = sq(&*algebra, r)?;
+        let mut computed = FpVector::new(algebra.prime(), output.dimension());
+        let mut input = FpVector::new(algebra.prime(), result.dimension(input_degree));
+        input.set_entry(input_idx, 1);
+        result.act_by_element(&mut computed, 1, r as i32, &op, input_degree, &input);
+        if computed != output {
+            return Err(GenericError::new(format!(
+                "Sq{} x{} is {} but the module file says it is {}",
+                r,
+                g,
+                result.element_to_string(input_degree + r as i32, &computed),
+                result.element_to_string(input_degree + r as i32, &output)
+            ))
+            .into());
+        }
+    }
+    Ok(result)
+}
+
+/// Writes a bounded module in Bruner's format. The basis elements are numbered in order of
+/// degree. All non-zero actions of $\mathrm{Sq}^r$ are listed, including those for which $r$ is not
+/// a power of two.
+pub fn fd_module_to_bruner<A: SteenrodAlgebraT>(module: &FDModule<A>) -> error::Result<String> {
+    let algebra = module.algebra();
+    let p = algebra.prime();
+    check_prime(p)?;
+
+    let min_degree = module.min_degree();
+    let max_degree = module.max_degree();
+    let offset = |d: i32| -> usize { (min_degree..d).map(|i| module.dimension(i)).sum() };
+
+    let mut degrees = Vec::new();
+    for t in min_degree..=max_degree {
+        for _ in 0..module.dimension(t) {
+            degrees.push(t.to_string());
+        }
+    }
+    let mut result = format!("{}\n{}\n", degrees.len(), degrees.join(" "));
+
+    for t in min_degree..=max_degree {
+        for idx in 0..module.dimension(t) {
+            let mut input = FpVector::new(p, module.dimension(t));
+            input.set_entry(idx, 1);
+            for r in 1..=max_degree - t {
+                if module.dimension(t + r) == 0 {
+                    continue;
+                }
+                let op = sq(&*algebra, r as u32)?;
+                let mut output = FpVector::new(p, module.dimension(t + r));
+                module.act_by_element(&mut output, 1, r, &op, t, &input);
+                let targets: Vec<String> = output
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, v)| v != 0)
+                    .map(|(j, _)| (offset(t + r) + j).to_string())
+                    .collect();
+                if targets.is_empty() {
+                    continue;
+                }
+                result.push_str(&format!(
+                    "{} {} {} {}\n",
+                    offset(t) + idx,
+                    r,
+                    targets.len(),
+                    targets.join(" ")
+                ));
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Writes an element of `algebra` of degree `degree` as a space separated list of Milnor basis
+/// elements `i(r1,...,rn)`. The unit is written as `i(0)`. The `milnor` algebra is used for the
+/// change of basis if `algebra` uses the Adem basis.
+pub fn element_to_bruner<A: SteenrodAlgebraT>(
+    algebra: &A,
+    milnor: &MilnorAlgebra,
+    degree: i32,
+    elt: &FpVector,
+) -> String {
+    let mut terms = Vec::new();
+    let mut push = |elt: &MilnorBasisElement| {
+        let mut p_part: Vec<String> = elt.p_part.iter().map(u32::to_string).collect();
+        if p_part.is_empty() {
+            p_part.push("0".to_string());
+        }
+        terms.push(format!("i({})", p_part.join(",")));
+    };
+    match algebra.to_steenrod_algebra() {
+        SteenrodAlgebraBorrow::BorrowMilnor(a) => {
+            for (i, v) in elt.iter().enumerate() {
+                if v != 0 {
+                    push(a.basis_element_from_index(degree, i));
+                }
+            }
+        }
+        SteenrodAlgebraBorrow::BorrowAdem(a) => {
+            milnor.compute_basis(degree);
+            let mut result = FpVector::new(algebra.prime(), milnor.dimension(degree, -1));
+            for (i, v) in elt.iter().enumerate() {
+                if v != 0 {
+                    adem_to_milnor_on_basis(a, milnor, &mut result, v, degree, i);
+                }
+            }
+            for (i, v) in result.iter().enumerate() {
+                if v != 0 {
+                    push(milnor.basis_element_from_index(degree, i));
+                }
+            }
+        }
+    }
+    terms.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::{AdemAlgebra, SteenrodAlgebra};
+
+    const JOKER: &str = "5\n0 1 2 3 4\n0 1 1 1\n0 2 1 2\n1 2 1 3\n2 2 1 4\n3 1 1 4\n2 1 0\n1 3 1 4\n";
+
+    fn check_round_trip(algebra: SteenrodAlgebra) {
+        let algebra = Arc::new(algebra);
+        let module =
+            fd_module_from_bruner(Arc::clone(&algebra), "Joker".to_string(), JOKER).unwrap();
+        let output = fd_module_to_bruner(&module).unwrap();
+        let module2 = fd_module_from_bruner(algebra, "Joker".to_string(), &output).unwrap();
+        assert_eq!(module2.test_equal(&module), Ok(()));
+
+        let mut json = serde_json::Value::Null;
+        module.to_json(&mut json);
+        assert_eq!(json["gens"]["x4"], 4);
+    }
+
+    #[test]
+    fn test_bruner_round_trip() {
+        let p = ValidPrime::new(2);
+        check_round_trip(SteenrodAlgebra::from(AdemAlgebra::new(p, false, false)));
+        check_round_trip(SteenrodAlgebra::from(MilnorAlgebra::new(p)));
+    }
+
+    #[test]
+    fn test_bruner_invalid() {
+        let algebra = Arc::new(SteenrodAlgebra::from(MilnorAlgebra::new(ValidPrime::new(2))));
+        // Sq1 Sq1 is non-zero
+        let input = "3\n0 1 2\n0 1 1 1\n1 1 1 2\n";
+        assert!(fd_module_from_bruner(Arc::clone(&algebra), String::new(), input).is_err());
+        // Sq3 = Sq1 Sq2 is zero
+        let input = "2\n0 3\n0 3 1 1\n";
+        assert!(fd_module_from_bruner(Arc::clone(&algebra), String::new(), input).is_err());
+        // Wrong number of terms
+        let input = "2\n0 1\n0 1 2 1\n";
+        assert!(fd_module_from_bruner(algebra, String::new(), input).is_err());
+    }
+}
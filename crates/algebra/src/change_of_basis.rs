@@ -1,4 +1,8 @@
+use fp::prime::ValidPrime;
 use fp::vector::{FpVector, FpVectorT};
+use fp::matrix::Matrix;
+use once::OnceVec;
+use parking_lot::Mutex;
 use crate::algebra::{Algebra, AdemAlgebra, MilnorAlgebra};
 use crate::algebra::adem_algebra::AdemBasisElement;
 use crate::algebra::milnor_algebra::MilnorBasisElement;
@@ -197,11 +201,389 @@ pub fn adem_plist(
     milnor_to_adem_on_basis(adem_algebra, milnor_algebra, result, coeff, degree, idx);
 }
 
+/// The bases of the mod 2 Steenrod algebra (and the P^s_t basis at odd primes) consisting of
+/// monomials in certain Milnor basis elements. These are not made into `Algebra`s. Instead,
+/// elements are converted to and from the Milnor basis with `milnor_to_monomial` and
+/// `monomial_to_milnor`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MonomialBasisType {
+    /// Monomials $Q_0^{e_0} Q_1^{e_1} \cdots \prod P^s_t$, where $P^s_t = P(0, \ldots, 0, p^s)$ with
+    /// $p^s$ in the $t$th position. Each $P^s_t$ appears fewer than $p$ times, and the factors are
+    /// ordered by increasing $s + t$, and then by increasing $s$.
+    PST,
+    /// Arnon's A basis. Monomials $X^{m_1}_{k_1} \cdots X^{m_r}_{k_r}$, where $X^m_k = Sq^{2^m}
+    /// Sq^{2^{m - 1}} \cdots Sq^{2^k}$, and the pairs $(m_i, k_i)$ are distinct and in increasing
+    /// lexicographic order. This is only defined at p = 2.
+    ArnonA,
+    /// Arnon's C basis. Monomials $Sq^{s_1} \cdots Sq^{s_r}$ with $s_i \leq 2 s_{i + 1}$ and
+    /// $2^{r - i} \mid s_i$. This is only defined at p = 2.
+    ArnonC,
+    /// Wall's basis. Monomials $Q^{m_1}_{k_1} \cdots Q^{m_r}_{k_r}$, where $Q^m_k = Sq^{2^k}
+    /// Sq^{2^{k + 1}} \cdots Sq^{2^m}$, and the pairs $(m_i, k_i)$ are distinct and in decreasing
+    /// lexicographic order. This is only defined at p = 2.
+    Wall,
+}
+
+impl MonomialBasisType {
+    pub fn name(self) -> &'static str {
+        match self {
+            MonomialBasisType::PST => "pst",
+            MonomialBasisType::ArnonA => "arnona",
+            MonomialBasisType::ArnonC => "arnonc",
+            MonomialBasisType::Wall => "wall",
+        }
+    }
+}
+
+impl std::str::FromStr for MonomialBasisType {
+    type Err = error::GenericError;
+
+    fn from_str(name : &str) -> Result<Self, Self::Err> {
+        match name {
+            "pst" => Ok(MonomialBasisType::PST),
+            "arnona" => Ok(MonomialBasisType::ArnonA),
+            "arnonc" => Ok(MonomialBasisType::ArnonC),
+            "wall" => Ok(MonomialBasisType::Wall),
+            _ => Err(error::GenericError::new(format!("Unknown basis: {}", name)))
+        }
+    }
+}
+
+/// A factor of a basis element of a `MonomialBasis`. The `Display` implementation gives the string
+/// form accepted by `steenrod_parser`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MonomialFactor {
+    Q(u32),
+    Pst(u32, u32),
+    Sq(u32),
+    ArnonX(u32, u32),
+    WallQ(u32, u32),
+}
+
+impl MonomialFactor {
+    /// The factor as a product of Milnor basis elements.
+    fn milnor_factors(self, p : ValidPrime) -> Vec<MilnorBasisElement> {
+        let generic = *p != 2;
+        let q = if generic { 2 * (*p) - 2 } else { 1 };
+        let xi_degrees = crate::algebra::combinatorics::xi_degrees(p);
+        let tau_degrees = crate::algebra::combinatorics::tau_degrees(p);
+        let sq = |n : u32| MilnorBasisElement {
+            degree : (n * q) as i32,
+            q_part : 0,
+            p_part : vec![n]
+        };
+        match self {
+            MonomialFactor::Q(i) => vec![MilnorBasisElement {
+                degree : tau_degrees[i as usize],
+                q_part : 1 << i,
+                p_part : vec![]
+            }],
+            MonomialFactor::Pst(s, t) => {
+                let mut p_part = vec![0; t as usize];
+                p_part[t as usize - 1] = fp::prime::integer_power(*p, s);
+                vec![MilnorBasisElement {
+                    degree : (p_part[t as usize - 1] * q) as i32 * xi_degrees[t as usize - 1],
+                    q_part : 0,
+                    p_part
+                }]
+            }
+            MonomialFactor::Sq(n) => vec![sq(n)],
+            MonomialFactor::ArnonX(m, k) => (k ..= m).rev().map(|i| sq(1 << i)).collect(),
+            MonomialFactor::WallQ(m, k) => (k ..= m).map(|i| sq(1 << i)).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for MonomialFactor {
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MonomialFactor::Q(i) => write!(f, "Q{}", i),
+            MonomialFactor::Pst(s, t) => write!(f, "P^{}_{}", s, t),
+            MonomialFactor::Sq(n) => write!(f, "Sq{}", n),
+            MonomialFactor::ArnonX(m, k) => write!(f, "X^{}_{}", m, k),
+            MonomialFactor::WallQ(m, k) => write!(f, "Q^{}_{}", m, k),
+        }
+    }
+}
+
+/// A basis of the Steenrod algebra consisting of monomials, of the type specified by
+/// `MonomialBasisType`. Like an `Algebra`, `compute_basis` has to be called before using a degree.
+/// The change of basis matrices to and from the Milnor basis are computed at the same time.
+pub struct MonomialBasis {
+    p : ValidPrime,
+    basis_type : MonomialBasisType,
+    next_degree : Mutex<i32>,
+    basis_table : OnceVec<Vec<Vec<MonomialFactor>>>, // degree -> index -> factors
+    to_milnor_table : OnceVec<Vec<FpVector>>, // degree -> index -> Milnor element
+    from_milnor_table : OnceVec<Vec<FpVector>>, // degree -> Milnor index -> element
+}
+
+impl MonomialBasis {
+    pub fn new(p : ValidPrime, basis_type : MonomialBasisType) -> error::Result<Self> {
+        if *p != 2 && basis_type != MonomialBasisType::PST {
+            return Err(error::GenericError::new(
+                format!("The {} basis is only defined at p = 2", basis_type.name())).into());
+        }
+        Ok(Self {
+            p,
+            basis_type,
+            next_degree : Mutex::new(0),
+            basis_table : OnceVec::new(),
+            to_milnor_table : OnceVec::new(),
+            from_milnor_table : OnceVec::new(),
+        })
+    }
+
+    pub fn prime(&self) -> ValidPrime {
+        self.p
+    }
+
+    pub fn basis_type(&self) -> MonomialBasisType {
+        self.basis_type
+    }
+
+    /// Computes the basis and the change of basis matrices up to and including degree `degree`.
+    /// `milnor_algebra` must be the full Milnor algebra at the same prime.
+    pub fn compute_basis(&self, milnor_algebra : &MilnorAlgebra, degree : i32) {
+        let mut next_degree = self.next_degree.lock();
+        if degree < *next_degree {
+            return;
+        }
+        milnor_algebra.compute_basis(degree);
+
+        for d in *next_degree ..= degree {
+            let basis = match self.basis_type {
+                MonomialBasisType::PST => Self::pst_basis(milnor_algebra, d),
+                MonomialBasisType::ArnonA => Self::pair_basis(d, MonomialFactor::ArnonX, |(m, k)| (m, k)),
+                MonomialBasisType::ArnonC => Self::arnon_c_basis(d),
+                MonomialBasisType::Wall => Self::pair_basis(d, MonomialFactor::WallQ,
+                    |(m, k)| (-(m as i32), -(k as i32))),
+            };
+            let dim = milnor_algebra.dimension(d, -1);
+            assert_eq!(basis.len(), dim);
+
+            let to_milnor = basis.iter().map(|monomial| {
+                let mut v = FpVector::new(self.p, dim);
+                self.monomial_to_milnor(milnor_algebra, &mut v, monomial);
+                v
+            }).collect::<Vec<_>>();
+
+            // Invert the change of basis matrix by row reducing [M | I].
+            let mut matrix = Matrix::new(self.p, dim, 2 * dim);
+            for (row, v) in matrix.iter_mut().zip(&to_milnor) {
+                row.set_slice(0, dim);
+                row.assign(v);
+                row.clear_slice();
+            }
+            matrix.add_identity(dim, 0, dim);
+            matrix.initialize_pivots();
+            matrix.row_reduce();
+            assert!(matrix.pivots()[0 .. dim].iter().all(|&x| x >= 0),
+                "The {} monomials in degree {} are not a basis", self.basis_type.name(), d);
+            let from_milnor = matrix.iter().map(|row| {
+                let mut v = FpVector::new(self.p, dim);
+                for j in 0 .. dim {
+                    v.set_entry(j, row.entry(dim + j));
+                }
+                v
+            }).collect::<Vec<_>>();
+
+            self.basis_table.push(basis);
+            self.to_milnor_table.push(to_milnor);
+            self.from_milnor_table.push(from_milnor);
+        }
+        *next_degree = degree + 1;
+    }
+
+    fn monomial_to_milnor(&self, milnor_algebra : &MilnorAlgebra, result : &mut FpVector, monomial : &[MonomialFactor]) {
+        let mut cur_degree = 0;
+        let mut cur = FpVector::new(self.p, 1);
+        cur.set_entry(0, 1);
+        for factor in monomial {
+            for elt in factor.milnor_factors(self.p) {
+                let idx = milnor_algebra.basis_element_to_index(&elt);
+                let mut next = FpVector::new(self.p, milnor_algebra.dimension(cur_degree + elt.degree, -1));
+                milnor_algebra.multiply_element_by_basis_element(&mut next, 1, cur_degree, &cur, elt.degree, idx, -1);
+                cur = next;
+                cur_degree += elt.degree;
+            }
+        }
+        result.add(&cur, 1);
+    }
+
+    fn pst_basis(milnor_algebra : &MilnorAlgebra, degree : i32) -> Vec<Vec<MonomialFactor>> {
+        let p = *milnor_algebra.prime();
+        (0 .. milnor_algebra.dimension(degree, -1)).map(|i| {
+            let elt = milnor_algebra.basis_element_from_index(degree, i);
+            let mut result = Vec::new();
+            let mut q_part = elt.q_part;
+            while q_part != 0 {
+                let i = q_part.trailing_zeros();
+                q_part ^= 1 << i;
+                result.push(MonomialFactor::Q(i));
+            }
+
+            let mut pst = Vec::new();
+            for (t, &r) in elt.p_part.iter().enumerate() {
+                let mut r = r;
+                let mut s = 0;
+                while r != 0 {
+                    for _ in 0 .. r % p {
+                        pst.push((s, t as u32 + 1));
+                    }
+                    r /= p;
+                    s += 1;
+                }
+            }
+            pst.sort_by_key(|&(s, t)| (s + t, s));
+            result.extend(pst.into_iter().map(|(s, t)| MonomialFactor::Pst(s, t)));
+            result
+        }).collect()
+    }
+
+    /// Lists the monomials in distinct factors `factor(m, k)`, where `factor(m, k)` has degree
+    /// $2^{m + 1} - 2^k$. The factors in each monomial are sorted by `key`.
+    fn pair_basis<K : Ord>(
+        degree : i32,
+        factor : fn(u32, u32) -> MonomialFactor,
+        key : fn((u32, u32)) -> K
+    ) -> Vec<Vec<MonomialFactor>> {
+        let pair_degree = |(m, k) : (u32, u32)| (1 << (m + 1)) - (1 << k);
+
+        let mut pairs = Vec::new();
+        let mut m = 0;
+        while pair_degree((m, m)) <= degree {
+            for k in 0 ..= m {
+                if pair_degree((m, k)) <= degree {
+                    pairs.push((m, k));
+                }
+            }
+            m += 1;
+        }
+        pairs.sort_by_key(|&x| key(x));
+
+        fn helper(pairs : &[(u32, u32)], degree : i32, pair_degree : &dyn Fn((u32, u32)) -> i32,
+            current : &mut Vec<(u32, u32)>, result : &mut Vec<Vec<(u32, u32)>>) {
+            if degree == 0 {
+                result.push(current.clone());
+                return;
+            }
+            for (i, &pair) in pairs.iter().enumerate() {
+                if pair_degree(pair) <= degree {
+                    current.push(pair);
+                    helper(&pairs[i + 1 ..], degree - pair_degree(pair), pair_degree, current, result);
+                    current.pop();
+                }
+            }
+        }
+        let mut result = Vec::new();
+        helper(&pairs, degree, &pair_degree, &mut Vec::new(), &mut result);
+        result.into_iter()
+            .map(|monomial| monomial.into_iter().map(|(m, k)| factor(m, k)).collect())
+            .collect()
+    }
+
+    fn arnon_c_basis(degree : i32) -> Vec<Vec<MonomialFactor>> {
+        // Fills in a sequence of length `len` from the end. The ith entry from the end must be
+        // divisible by 2^i.
+        fn helper(len : usize, degree : u32, current : &mut Vec<u32>, result : &mut Vec<Vec<MonomialFactor>>) {
+            if current.len() == len {
+                if degree == 0 {
+                    result.push(current.iter().rev().map(|&s| MonomialFactor::Sq(s)).collect());
+                }
+                return;
+            }
+            let step = 1 << current.len();
+            let max = current.last().map_or(degree, |&n| std::cmp::min(degree, 2 * n));
+            let mut s = step;
+            while s <= max {
+                current.push(s);
+                helper(len, degree - s, current, result);
+                current.pop();
+                s += step;
+            }
+        }
+
+        let mut result = Vec::new();
+        if degree == 0 {
+            result.push(vec![]);
+        }
+        let mut len = 1;
+        // A sequence of length len has degree at least 2^len - 1.
+        while (1 << len) - 1 <= degree {
+            helper(len, degree as u32, &mut Vec::new(), &mut result);
+            len += 1;
+        }
+        result
+    }
+
+    pub fn dimension(&self, degree : i32) -> usize {
+        if degree < 0 {
+            return 0;
+        }
+        self.basis_table[degree as usize].len()
+    }
+
+    pub fn basis_element(&self, degree : i32, idx : usize) -> &[MonomialFactor] {
+        &self.basis_table[degree as usize][idx]
+    }
+
+    pub fn basis_element_to_string(&self, degree : i32, idx : usize) -> String {
+        let monomial = self.basis_element(degree, idx);
+        if monomial.is_empty() {
+            return "1".to_string();
+        }
+        monomial.iter().map(MonomialFactor::to_string).collect::<Vec<_>>().join(" ")
+    }
+
+    pub fn element_to_string(&self, degree : i32, element : &FpVector) -> String {
+        let terms = element.iter().enumerate()
+            .filter(|&(_, v)| v != 0)
+            .map(|(i, v)| if v == 1 {
+                self.basis_element_to_string(degree, i)
+            } else {
+                format!("{} * {}", v, self.basis_element_to_string(degree, i))
+            })
+            .collect::<Vec<_>>();
+        if terms.is_empty() {
+            "0".to_string()
+        } else {
+            terms.join(" + ")
+        }
+    }
+}
+
+pub fn monomial_to_milnor(
+    basis : &MonomialBasis,
+    result : &mut FpVector, coeff : u32, degree : i32, input : &FpVector
+){
+    let p = basis.prime();
+    for (i, v) in input.iter().enumerate() {
+        if v == 0 {
+            continue;
+        }
+        result.add(&basis.to_milnor_table[degree as usize][i], (coeff * v) % *p);
+    }
+}
+
+pub fn milnor_to_monomial(
+    basis : &MonomialBasis,
+    result : &mut FpVector, coeff : u32, degree : i32, input : &FpVector
+){
+    let p = basis.prime();
+    for (i, v) in input.iter().enumerate() {
+        if v == 0 {
+            continue;
+        }
+        result.add(&basis.from_milnor_table[degree as usize][i], (coeff * v) % *p);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rstest::rstest;
-    use fp::prime::ValidPrime;
+    use crate::algebra::HopfAlgebra;
     
     #[test]
     fn test_cob_milnor_qs_to_adem(){
@@ -266,4 +648,126 @@ mod tests {
 
     }
 
+    #[rstest(basis_type, p, max_degree,
+        case(MonomialBasisType::PST, 2, 32),
+        case(MonomialBasisType::PST, 3, 60),
+        case(MonomialBasisType::ArnonA, 2, 32),
+        case(MonomialBasisType::ArnonC, 2, 32),
+        case(MonomialBasisType::Wall, 2, 32)
+    )]
+    fn test_cob_monomial_basis(basis_type : MonomialBasisType, p : u32, max_degree : i32) {
+        let p = ValidPrime::new(p);
+        let adem = AdemAlgebra::new(p, *p != 2, false);
+        let milnor = MilnorAlgebra::new(p);
+        adem.compute_basis(max_degree);
+        milnor.compute_basis(max_degree);
+        let basis = MonomialBasis::new(p, basis_type).unwrap();
+        basis.compute_basis(&milnor, max_degree);
+
+        for degree in 0 .. max_degree {
+            let dim = basis.dimension(degree);
+            let mut input = FpVector::new(p, dim);
+            let mut milnor_result = FpVector::new(p, dim);
+            let mut result = FpVector::new(p, dim);
+            for i in 0 .. dim {
+                input.set_entry(i, 1);
+                monomial_to_milnor(&basis, &mut milnor_result, 1, degree, &input);
+                milnor_to_monomial(&basis, &mut result, 1, degree, &milnor_result);
+                assert_eq!(result, input);
+
+                // The string form evaluates to the same element
+                let string = basis.basis_element_to_string(degree, i);
+                let (parsed_degree, parsed) = crate::steenrod_evaluator::evaluate_algebra_milnor(&adem, &milnor, &string).unwrap();
+                assert_eq!(parsed_degree, degree, "{}", string);
+                assert_eq!(parsed, milnor_result, "{}", string);
+
+                input.set_to_zero();
+                milnor_result.set_to_zero();
+                result.set_to_zero();
+            }
+        }
+    }
+
+    #[test]
+    fn test_monomial_basis_strings() {
+        let p = ValidPrime::new(2);
+        let milnor = MilnorAlgebra::new(p);
+        for (basis_type, expected) in &[
+            (MonomialBasisType::PST, vec!["P^1_1 P^2_1", "P^0_1 P^0_2 P^1_1", "P^1_2"]),
+            (MonomialBasisType::ArnonA, vec!["X^0_0 X^1_0 X^1_1", "X^1_1 X^2_2", "X^2_1"]),
+            (MonomialBasisType::ArnonC, vec!["Sq6", "Sq4 Sq2", "Sq2 Sq4"]),
+            (MonomialBasisType::Wall, vec!["Q^2_2 Q^1_1", "Q^2_1", "Q^1_1 Q^1_0 Q^0_0"]),
+        ] {
+            let basis = MonomialBasis::new(p, *basis_type).unwrap();
+            basis.compute_basis(&milnor, 6);
+            let strings = (0 .. basis.dimension(6)).map(|i| basis.basis_element_to_string(6, i)).collect::<Vec<_>>();
+            assert_eq!(&strings, expected);
+        }
+        assert!(MonomialBasis::new(ValidPrime::new(3), MonomialBasisType::Wall).is_err());
+    }
+
+    #[test]
+    fn test_milnor_antipode_sq() {
+        // χ(Sq^n) is the sum of all Milnor basis elements of degree n.
+        let p = ValidPrime::new(2);
+        let max_degree = 32;
+        let adem = AdemAlgebra::new(p, false, false);
+        let milnor = MilnorAlgebra::new(p);
+        adem.compute_basis(max_degree);
+        milnor.compute_basis(max_degree);
+
+        for degree in 1 .. max_degree {
+            let dim = milnor.dimension(degree, -1);
+            let mut result = FpVector::new(p, dim);
+            let (_, idx) = adem.beps_pn(0, degree as u32);
+            let mut chi = FpVector::new(p, dim);
+            adem.antipode_on_basis(&mut chi, 1, degree, idx);
+            adem_to_milnor(&adem, &milnor, &mut result, 1, degree, &chi);
+            assert!(result.iter().all(|v| v == 1), "χ(Sq{}) = {}", degree, milnor.element_to_string(degree, &result));
+        }
+    }
+
+    #[rstest(p, max_degree,
+        case(2, 32),
+        case(3, 60)
+    )]
+    fn test_cob_antipode(p : u32, max_degree : i32) {
+        let p = ValidPrime::new(p);
+        let adem = AdemAlgebra::new(p, *p != 2, false);
+        let milnor = MilnorAlgebra::new(p);
+        adem.compute_basis(max_degree);
+        milnor.compute_basis(max_degree);
+
+        for degree in 0 .. max_degree {
+            let dim = adem.dimension(degree, -1);
+            let mut adem_chi = FpVector::new(p, dim);
+            let mut milnor_elt = FpVector::new(p, dim);
+            let mut milnor_chi = FpVector::new(p, dim);
+            let mut milnor_chi_chi = FpVector::new(p, dim);
+            let mut expected = FpVector::new(p, dim);
+            for i in 0 .. dim {
+                adem.antipode_on_basis(&mut adem_chi, 1, degree, i);
+                adem_to_milnor(&adem, &milnor, &mut expected, 1, degree, &adem_chi);
+
+                adem_to_milnor_on_basis(&adem, &milnor, &mut milnor_elt, 1, degree, i);
+                milnor.antipode(&mut milnor_chi, 1, degree, &milnor_elt);
+                assert_eq!(milnor_chi, expected, "χ({}) = {} but χ({}) = {}",
+                    adem.basis_element_to_string(degree, i),
+                    adem.element_to_string(degree, &adem_chi),
+                    milnor.element_to_string(degree, &milnor_elt),
+                    milnor.element_to_string(degree, &milnor_chi));
+
+                // χ is an involution
+                milnor.antipode(&mut milnor_chi_chi, 1, degree, &milnor_chi);
+                assert_eq!(milnor_chi_chi, milnor_elt);
+
+                adem_chi.set_to_zero();
+                milnor_elt.set_to_zero();
+                milnor_chi.set_to_zero();
+                milnor_chi_chi.set_to_zero();
+                expected.set_to_zero();
+            }
+        }
+    }
+
 }
@@ -12,7 +12,9 @@
 #![warn(clippy::explicit_into_iter_loop)]
 
 mod algebra;
+pub mod bruner;
 pub mod change_of_basis;
+pub mod margolis;
 pub mod steenrod_parser;
 pub mod steenrod_evaluator;
 pub mod module;
@@ -0,0 +1,561 @@
+//! Margolis homology of bounded modules over the Steenrod algebra.
+//!
+//! If $x$ is an element of the Steenrod algebra with $x^n = 0$, then multiplication by $x$ and
+//! $x^{n - 1}$ give a chain complex, and the Margolis homology of a module $M$ with respect to $x$
+//! is
+//! $$ H(M; x) = \ker(x) / \operatorname{im}(x^{n - 1}). $$
+//! The standard choices of $x$ are the Milnor primitives $Q_i$ and the elements $P^s_t$ with
+//! $s < t$, which are listed by `MargolisOperator`. A bounded module is free over a
+//! finite sub-Hopf algebra if and only if its Margolis homologies with respect to the
+//! operators in the sub-Hopf algebra all vanish.
+
+use crate::algebra::combinatorics::{tau_degrees, xi_degrees};
+use crate::algebra::milnor_algebra::MilnorBasisElement;
+use crate::algebra::{Algebra, MilnorAlgebra, SteenrodAlgebraBorrow, SteenrodAlgebraT};
+use crate::change_of_basis::milnor_to_adem_on_basis;
+use crate::module::{BoundedModule, Module};
+use bivec::BiVec;
+use fp::matrix::{AugmentedMatrix2, Subspace};
+use fp::prime::ValidPrime;
+use fp::vector::{FpVector, FpVectorT};
+
+/// An operator whose Margolis homology is of interest.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MargolisOperator {
+    /// The Milnor primitive $Q_i$.
+    Q(u32),
+    /// The element $P^s_t = P(0, \ldots, 0, p^s)$, with $p^s$ in the $t$th position. This
+    /// requires $s < t$. At the prime 2, $P^0_t = Q_{t - 1}$, so we also require $s > 0$ there.
+    P(u32, u32),
+}
+
+impl MargolisOperator {
+    /// The degree of the operator.
+    pub fn degree(self, p: ValidPrime) -> i32 {
+        match self {
+            MargolisOperator::Q(i) => tau_degrees(p)[i as usize],
+            MargolisOperator::P(s, t) => {
+                let q = if *p == 2 { 1 } else { 2 * *p as i32 - 2 };
+                (*p as i32).pow(s) * xi_degrees(p)[t as usize - 1] * q
+            }
+        }
+    }
+
+    /// The operator as an element of the Milnor basis.
+    pub fn milnor_basis_element(self, p: ValidPrime) -> MilnorBasisElement {
+        let degree = self.degree(p);
+        match self {
+            MargolisOperator::Q(i) if *p != 2 => MilnorBasisElement {
+                q_part: 1 << i,
+                p_part: Vec::new(),
+                degree,
+            },
+            MargolisOperator::Q(i) => {
+                let mut p_part = vec![0; i as usize + 1];
+                p_part[i as usize] = 1;
+                MilnorBasisElement {
+                    q_part: 0,
+                    p_part,
+                    degree,
+                }
+            }
+            MargolisOperator::P(s, t) => {
+                let mut p_part = vec![0; t as usize];
+                p_part[t as usize - 1] = (*p).pow(s);
+                MilnorBasisElement {
+                    q_part: 0,
+                    p_part,
+                    degree,
+                }
+            }
+        }
+    }
+
+    /// Lists the operators of degree at most `max_degree`, sorted by degree. This is all the
+    /// operators whose homology can possibly be non-trivial for a module whose degrees span a
+    /// range of length `max_degree`.
+    pub fn operators(p: ValidPrime, max_degree: i32) -> Vec<Self> {
+        let mut result = Vec::new();
+        for i in 0.. {
+            let op = MargolisOperator::Q(i);
+            if op.degree(p) > max_degree {
+                break;
+            }
+            result.push(op);
+        }
+        let min_s = if *p == 2 { 1 } else { 0 };
+        for t in 1.. {
+            if MargolisOperator::P(0, t).degree(p) > max_degree {
+                break;
+            }
+            for s in min_s..t {
+                let op = MargolisOperator::P(s, t);
+                if op.degree(p) <= max_degree {
+                    result.push(op);
+                }
+            }
+        }
+        result.sort_by_key(|op| op.degree(p));
+        result
+    }
+
+    /// Expresses the operator as an element of `algebra`. Returns the degree of the element
+    /// together with the element. This fails if the operator is not in the algebra, e.g. if the
+    /// algebra is a Milnor algebra with a profile that excludes the operator.
+    pub fn to_element<A: SteenrodAlgebraT>(self, algebra: &A) -> error::Result<(i32, FpVector)> {
+        let p = algebra.prime();
+        let degree = self.degree(p);
+        algebra.compute_basis(degree);
+        let mut result = FpVector::new(p, algebra.dimension(degree, -1));
+        milnor_to_algebra(
+            algebra,
+            &MilnorAlgebra::new(p),
+            &mut result,
+            &self.milnor_basis_element(p),
+        )?;
+        Ok((degree, result))
+    }
+}
+
+/// Expresses a Milnor basis element in the basis of `algebra` and adds it to `result`. The basis
+/// of `algebra` must be computed up to the degree of `elt`. If `algebra` uses the Adem basis,
+/// `milnor` is used for the change of basis. This fails if `elt` is not in `algebra`.
+pub(crate) fn milnor_to_algebra<A: SteenrodAlgebraT>(
+    algebra: &A,
+    milnor: &MilnorAlgebra,
+    result: &mut FpVector,
+    elt: &MilnorBasisElement,
+) -> error::Result<()> {
+    match algebra.to_steenrod_algebra() {
+        SteenrodAlgebraBorrow::BorrowMilnor(a) => {
+            let idx = a.try_basis_element_to_index(elt).ok_or_else(|| {
+                error::GenericError::new(format!("{} is not in the algebra", elt))
+            })?;
+            result.add_basis_element(idx, 1);
+        }
+        SteenrodAlgebraBorrow::BorrowAdem(a) => {
+            milnor.compute_basis(elt.degree);
+            let idx = milnor.basis_element_to_index(elt);
+            milnor_to_adem_on_basis(a, milnor, result, 1, elt.degree, idx);
+        }
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for MargolisOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MargolisOperator::Q(i) => write!(f, "Q_{}", i),
+            MargolisOperator::P(s, t) => write!(f, "P^{}_{}", s, t),
+        }
+    }
+}
+
+/// Computes the Margolis homology of `module` with respect to the element `op` of degree
+/// `op_degree`. If $n$ is the smallest number with $\mathrm{op}^n = 0$, this is the homology of
+/// $$ M \xrightarrow{\mathrm{op}^{n - 1}} M \xrightarrow{\mathrm{op}} M, $$
+/// and we require $1 < n \leq p$. The result lists, for each degree of the module, elements of
+/// $M$ that project to a basis of the homology in that degree.
+pub fn margolis_homology<M: BoundedModule>(
+    module: &M,
+    op_degree: i32,
+    op: &FpVector,
+) -> error::Result<BiVec<Vec<FpVector>>> {
+    let p = module.prime();
+    let algebra = module.algebra();
+    if op_degree <= 0 {
+        return Err(error::GenericError::new(format!(
+            "Margolis homology requires an operator of positive degree, but got degree {}",
+            op_degree
+        ))
+        .into());
+    }
+
+    // Find the smallest n with op^n = 0. The power op^{n - 1} is `last_power`.
+    algebra.compute_basis(op_degree * (*p as i32));
+    let mut last_power = op.clone();
+    let mut n = 1;
+    loop {
+        let degree = op_degree * n;
+        let mut next_power = FpVector::new(p, algebra.dimension(degree + op_degree, -1));
+        algebra.multiply_element_by_element(
+            &mut next_power,
+            1,
+            op_degree,
+            op,
+            degree,
+            &last_power,
+            -1,
+        );
+        n += 1;
+        if next_power.is_zero() {
+            break;
+        }
+        if n as u32 >= *p {
+            return Err(error::GenericError::new(format!(
+                "Margolis homology requires x^p = 0, but x = {} does not satisfy this",
+                algebra.element_to_string(op_degree, op)
+            ))
+            .into());
+        }
+        last_power = next_power;
+    }
+    let power_degree = op_degree * (n - 1);
+
+    let module = module.to_fd_module();
+    let min_degree = module.min_degree();
+    let max_degree = module.max_degree();
+
+    let mut result = BiVec::with_capacity(min_degree, max_degree + 1);
+    for t in min_degree..=max_degree {
+        let dimension = module.dimension(t);
+        let target_dimension = if t + op_degree > max_degree {
+            0
+        } else {
+            module.dimension(t + op_degree)
+        };
+
+        let mut matrix = AugmentedMatrix2::new(p, dimension, &[target_dimension, dimension]);
+        for i in 0..dimension {
+            let mut output = FpVector::new(p, target_dimension);
+            if target_dimension > 0 {
+                let mut input = FpVector::new(p, dimension);
+                input.set_entry(i, 1);
+                module.act_by_element(&mut output, 1, op_degree, op, t, &input);
+            }
+            for (j, c) in output.iter().enumerate() {
+                matrix[i].set_entry(j, c);
+            }
+        }
+        matrix.segment(1, 1).add_identity(dimension, 0, 0);
+        matrix.initialize_pivots();
+        matrix.row_reduce();
+        let kernel = matrix.compute_kernel();
+
+        let source_degree = t - power_degree;
+        let source_dimension = if source_degree < min_degree {
+            0
+        } else {
+            module.dimension(source_degree)
+        };
+        let mut image = Subspace::new(p, dimension + 1, dimension);
+        image.add_vectors((0..source_dimension).map(|i| {
+            let mut input = FpVector::new(p, source_dimension);
+            input.set_entry(i, 1);
+            let mut output = FpVector::new(p, dimension);
+            module.act_by_element(&mut output, 1, power_degree, &last_power, source_degree, &input);
+            output
+        }));
+
+        let mut homology = Vec::new();
+        for v in kernel.basis() {
+            if !image.contains(v) {
+                image.add_vector(v);
+                homology.push(v.clone());
+            }
+        }
+        result.push(homology);
+    }
+    Ok(result)
+}
+
+/// Decides whether `module` is free over `subalgebra`, which must be a Milnor algebra with a
+/// finite profile, e.g. $A(n)$. By a theorem of Adams and Margolis, a bounded module is free over
+/// such a subalgebra if and only if its Margolis homologies with respect to the operators
+/// $Q_i$ and $P^s_t$ in the subalgebra all vanish.
+///
+/// The action of `subalgebra` on `module` is the restriction of the action of the algebra of
+/// `module`, so `subalgebra` must be contained in the algebra of `module`.
+pub fn is_free<M>(module: &M, subalgebra: &MilnorAlgebra) -> error::Result<bool>
+where
+    M: BoundedModule,
+    M::Algebra: SteenrodAlgebraT,
+{
+    let p = module.prime();
+    let top_degree = subalgebra.top_degree().ok_or_else(|| {
+        error::GenericError::new("The subalgebra must have a finite profile".to_string())
+    })?;
+    subalgebra.compute_basis(top_degree);
+
+    let module = module.to_fd_module();
+    let algebra = module.algebra();
+    let milnor = MilnorAlgebra::new(p);
+    for op in MargolisOperator::operators(p, top_degree) {
+        let elt = op.milnor_basis_element(p);
+        if subalgebra.try_basis_element_to_index(&elt).is_none() {
+            continue;
+        }
+        algebra.compute_basis(elt.degree);
+        let mut x = FpVector::new(p, algebra.dimension(elt.degree, -1));
+        milnor_to_algebra(&*algebra, &milnor, &mut x, &elt)?;
+        if margolis_homology(&module, elt.degree, &x)?
+            .iter()
+            .any(|basis| !basis.is_empty())
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// If `module` is free over `subalgebra`, returns a free basis, listed by degree. Otherwise,
+/// returns `None`. See `is_free` for the requirements on `subalgebra`.
+///
+/// The free basis is a lift of a basis of the indecomposables $M / \bar{B} M$, where $\bar{B}$
+/// is the augmentation ideal of the subalgebra $B$.
+pub fn free_basis<M>(
+    module: &M,
+    subalgebra: &MilnorAlgebra,
+) -> error::Result<Option<BiVec<Vec<FpVector>>>>
+where
+    M: BoundedModule,
+    M::Algebra: SteenrodAlgebraT,
+{
+    if !is_free(module, subalgebra)? {
+        return Ok(None);
+    }
+    let p = module.prime();
+    let module = module.to_fd_module();
+    let algebra = module.algebra();
+    let min_degree = module.min_degree();
+    let max_degree = module.max_degree();
+    let range = max_degree - min_degree;
+
+    // The basis elements of the subalgebra of positive degree, as elements of `algebra`.
+    let milnor = MilnorAlgebra::new(p);
+    let top_degree = std::cmp::min(range, subalgebra.top_degree().unwrap());
+    algebra.compute_basis(top_degree);
+    let mut ops = vec![Vec::new()];
+    for degree in 1..=top_degree {
+        let mut ops_in_degree = Vec::with_capacity(subalgebra.dimension(degree, -1));
+        for idx in 0..subalgebra.dimension(degree, -1) {
+            let mut x = FpVector::new(p, algebra.dimension(degree, -1));
+            milnor_to_algebra(
+                &*algebra,
+                &milnor,
+                &mut x,
+                subalgebra.basis_element_from_index(degree, idx),
+            )?;
+            ops_in_degree.push(x);
+        }
+        ops.push(ops_in_degree);
+    }
+
+    let mut result = BiVec::with_capacity(min_degree, max_degree + 1);
+    let mut rank = 0;
+    for t in min_degree..=max_degree {
+        let dimension = module.dimension(t);
+        let mut decomposables = Subspace::new(p, dimension + 1, dimension);
+        for s in std::cmp::max(min_degree, t - top_degree)..t {
+            for x in &ops[(t - s) as usize] {
+                decomposables.add_vectors((0..module.dimension(s)).map(|i| {
+                    let mut input = FpVector::new(p, module.dimension(s));
+                    input.set_entry(i, 1);
+                    let mut output = FpVector::new(p, dimension);
+                    module.act_by_element(&mut output, 1, t - s, x, s, &input);
+                    output
+                }));
+            }
+        }
+
+        let mut generators = Vec::new();
+        for i in 0..dimension {
+            let mut v = FpVector::new(p, dimension);
+            v.set_entry(i, 1);
+            if !decomposables.contains(&v) {
+                decomposables.add_vector(&v);
+                generators.push(v);
+            }
+        }
+        rank += generators.len();
+        result.push(generators);
+    }
+    let subalgebra_dimension: usize = (0..=subalgebra.top_degree().unwrap())
+        .map(|degree| subalgebra.dimension(degree, -1))
+        .sum();
+    let module_dimension: usize = (min_degree..=max_degree).map(|t| module.dimension(t)).sum();
+    assert_eq!(
+        rank * subalgebra_dimension,
+        module_dimension,
+        "The number of free generators is inconsistent with the dimension of the module"
+    );
+    Ok(Some(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::milnor_algebra::MilnorProfile;
+    use crate::algebra::{AdemAlgebra, SteenrodAlgebra};
+    use crate::module::{FDModule, FreeModule, TruncatedModule};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn homology_dimensions(
+        algebra_name: &str,
+        mut json: serde_json::Value,
+        op: MargolisOperator,
+    ) -> Vec<usize> {
+        let p = ValidPrime::new(json["p"].as_u64().unwrap() as u32);
+        let algebra = Arc::new(match algebra_name {
+            "adem" => SteenrodAlgebra::from(AdemAlgebra::new(p, *p != 2, false)),
+            _ => SteenrodAlgebra::from(MilnorAlgebra::new(p)),
+        });
+        algebra.compute_basis(20);
+        let module = FDModule::from_json(Arc::clone(&algebra), &mut json).unwrap();
+        let (degree, elt) = op.to_element(&*algebra).unwrap();
+        margolis_homology(&module, degree, &elt)
+            .unwrap()
+            .iter()
+            .map(Vec::len)
+            .collect()
+    }
+
+    #[test]
+    fn test_margolis_homology() {
+        for &algebra in &["adem", "milnor"] {
+            let c2 = json!({"p": 2, "gens": {"x0": 0, "x1": 1}, "actions": ["Sq1 x0 = x1"]});
+            assert_eq!(
+                homology_dimensions(algebra, c2.clone(), MargolisOperator::Q(0)),
+                vec![0, 0]
+            );
+            assert_eq!(
+                homology_dimensions(algebra, c2, MargolisOperator::Q(1)),
+                vec![1, 1]
+            );
+
+            let joker = json!({
+                "p": 2,
+                "gens": {"x0": 0, "x1": 1, "x2": 2, "x3": 3, "x4": 4},
+                "actions": ["Sq1 x0 = x1", "Sq2 x0 = x2", "Sq2 x1 = x3", "Sq2 x2 = x4", "Sq1 x3 = x4"]
+            });
+            assert_eq!(
+                homology_dimensions(algebra, joker.clone(), MargolisOperator::Q(0)),
+                vec![0, 0, 1, 0, 0]
+            );
+            assert_eq!(
+                homology_dimensions(algebra, joker, MargolisOperator::Q(1)),
+                vec![0, 0, 1, 0, 0]
+            );
+
+            // P^0_1 = P^1 at p = 3 satisfies (P^1)^3 = 0 but (P^1)^2 = 2 P^2 is non-zero.
+            let free = json!({
+                "p": 3,
+                "gens": {"x0": 0, "x4": 4, "x8": 8},
+                "actions": ["P1 x0 = x4", "P1 x4 = x8"]
+            });
+            assert_eq!(
+                homology_dimensions(algebra, free, MargolisOperator::P(0, 1)),
+                vec![0, 0, 0, 0, 0, 0, 0, 0, 0]
+            );
+            let truncated = json!({"p": 3, "gens": {"x0": 0, "x4": 4}, "actions": ["P1 x0 = x4"]});
+            assert_eq!(
+                homology_dimensions(algebra, truncated, MargolisOperator::P(0, 1)),
+                vec![0, 0, 0, 0, 1]
+            );
+        }
+    }
+
+    #[test]
+    fn test_margolis_requires_nilpotent() {
+        let p = ValidPrime::new(2);
+        let algebra = Arc::new(SteenrodAlgebra::from(MilnorAlgebra::new(p)));
+        algebra.compute_basis(10);
+        let mut json = json!({"p": 2, "gens": {"x0": 0}, "actions": []});
+        let module = FDModule::from_json(Arc::clone(&algebra), &mut json).unwrap();
+        // Sq^2 Sq^2 = Sq^3 Sq^1 is non-zero.
+        let sq2 = FpVector::from_vec(p, &[1]);
+        assert!(margolis_homology(&module, 2, &sq2).is_err());
+    }
+
+    #[test]
+    fn test_margolis_operators() {
+        let ops = MargolisOperator::operators(ValidPrime::new(2), 7);
+        assert_eq!(
+            ops.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["Q_0", "Q_1", "P^1_2", "Q_2"]
+        );
+        let ops = MargolisOperator::operators(ValidPrime::new(3), 16);
+        assert_eq!(
+            ops.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["Q_0", "P^0_1", "Q_1", "P^0_2"]
+        );
+    }
+
+    fn finite_milnor_algebra(p: u32, q_part: u32, p_part: Vec<u32>) -> MilnorAlgebra {
+        let mut algebra = MilnorAlgebra::new(ValidPrime::new(p));
+        algebra.profile = MilnorProfile {
+            truncated: true,
+            q_part,
+            p_part,
+        };
+        algebra
+    }
+
+    fn free_basis_dimensions(
+        algebra_name: &str,
+        mut json: serde_json::Value,
+        subalgebra: &MilnorAlgebra,
+    ) -> Option<Vec<usize>> {
+        let p = ValidPrime::new(json["p"].as_u64().unwrap() as u32);
+        let algebra = Arc::new(match algebra_name {
+            "adem" => SteenrodAlgebra::from(AdemAlgebra::new(p, *p != 2, false)),
+            _ => SteenrodAlgebra::from(MilnorAlgebra::new(p)),
+        });
+        algebra.compute_basis(20);
+        let module = FDModule::from_json(Arc::clone(&algebra), &mut json).unwrap();
+        free_basis(&module, subalgebra)
+            .unwrap()
+            .map(|basis| basis.iter().map(Vec::len).collect())
+    }
+
+    #[test]
+    fn test_free_basis() {
+        let e0 = finite_milnor_algebra(2, 0, vec![1]);
+        let a1 = finite_milnor_algebra(2, 0, vec![2, 1]);
+        for &algebra in &["adem", "milnor"] {
+            let c2 = json!({"p": 2, "gens": {"x0": 0, "x1": 1}, "actions": ["Sq1 x0 = x1"]});
+            assert_eq!(
+                free_basis_dimensions(algebra, c2.clone(), &e0),
+                Some(vec![1, 0])
+            );
+            assert_eq!(free_basis_dimensions(algebra, c2, &a1), None);
+
+            let joker = json!({
+                "p": 2,
+                "gens": {"x0": 0, "x1": 1, "x2": 2, "x3": 3, "x4": 4},
+                "actions": ["Sq1 x0 = x1", "Sq2 x0 = x2", "Sq2 x1 = x3", "Sq2 x2 = x4", "Sq1 x3 = x4"]
+            });
+            assert_eq!(free_basis_dimensions(algebra, joker, &a1), None);
+
+            let c3 = json!({"p": 3, "gens": {"x0": 0, "x1": 1}, "actions": ["b x0 = x1"]});
+            assert_eq!(
+                free_basis_dimensions(algebra, c3, &finite_milnor_algebra(3, 1, vec![])),
+                Some(vec![1, 0])
+            );
+        }
+    }
+
+    #[test]
+    fn test_free_basis_free_module() {
+        let a1 = Arc::new(SteenrodAlgebra::from(finite_milnor_algebra(2, 0, vec![2, 1])));
+        a1.compute_basis(10);
+        let module = Arc::new(FreeModule::new(Arc::clone(&a1), "F".to_string(), 0));
+        module.extend_table_entries(10);
+        for t in 0..=10 {
+            module.add_generators(t, if t == 0 || t == 2 { 1 } else { 0 }, None);
+        }
+        let module = TruncatedModule::new(module, 10);
+
+        let subalgebra = finite_milnor_algebra(2, 0, vec![2, 1]);
+        let basis = free_basis(&module, &subalgebra).unwrap().unwrap();
+        assert_eq!(
+            basis.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+
+        // The truncation is not free.
+        let module = TruncatedModule::new(Arc::clone(&module.module), 7);
+        assert!(!is_free(&module, &subalgebra).unwrap());
+    }
+}
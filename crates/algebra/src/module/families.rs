@@ -0,0 +1,218 @@
+//! Generators for infinite families of cyclic modules over the Steenrod algebra.
+//!
+//! These are the Brown–Gitler modules $B(k)$, which are finite dimensional, and the modules
+//! $A/\!/A(n)$, which are finitely presented. Both are constructed over an arbitrary
+//! `SteenrodAlgebraT`, with the computations done in the Milnor basis.
+
+use crate::algebra::milnor_algebra::MilnorBasisElement;
+use crate::algebra::{
+    Algebra, HopfAlgebra, MilnorAlgebra, SteenrodAlgebraBorrow, SteenrodAlgebraT,
+};
+use crate::change_of_basis::adem_to_milnor_on_basis;
+use crate::margolis::milnor_to_algebra;
+use crate::module::{FDModule, FPModule, Module};
+use bivec::BiVec;
+use error::GenericError;
+use fp::matrix::Matrix;
+use fp::vector::{FpVector, FpVectorT};
+
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+/// The weight of a Milnor basis element, which is the weight of the dual monomial in the dual
+/// Steenrod algebra. At the prime 2, $\xi_i$ has weight $2^{i - 1}$. At odd primes, $\tau_i$ and
+/// $\xi_i$ both have weight $p^i$.
+fn weight(p: u32, elt: &MilnorBasisElement) -> u32 {
+    if p == 2 {
+        elt.p_part.iter().enumerate().map(|(i, &r)| r << i).sum()
+    } else {
+        let q_weight: u32 = (0..32)
+            .filter(|&i| elt.q_part & (1 << i) != 0)
+            .map(|i| p.pow(i))
+            .sum();
+        let p_weight: u32 = elt
+            .p_part
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| r * p.pow(i as u32 + 1))
+            .sum();
+        q_weight + p_weight
+    }
+}
+
+/// Adds the basis element `idx` of `algebra` in degree `degree`, expressed in the Milnor basis
+/// of `milnor`, to `result`.
+fn algebra_to_milnor<A: SteenrodAlgebraT>(
+    algebra: &A,
+    milnor: &MilnorAlgebra,
+    result: &mut FpVector,
+    degree: i32,
+    idx: usize,
+) {
+    match algebra.to_steenrod_algebra() {
+        SteenrodAlgebraBorrow::BorrowMilnor(a) => {
+            let elt = a.basis_element_from_index(degree, idx);
+            result.add_basis_element(milnor.basis_element_to_index(elt), 1);
+        }
+        SteenrodAlgebraBorrow::BorrowAdem(a) => {
+            adem_to_milnor_on_basis(a, milnor, result, 1, degree, idx);
+        }
+    }
+}
+
+/// The Brown–Gitler module $B(k)$, i.e. the cohomology of the Brown–Gitler spectrum. The
+/// homology of $B(k)$ is the subcomodule of the dual Steenrod algebra spanned by the monomials
+/// in the conjugates $\chi(\xi_i)$ and $\chi(\tau_i)$ of weight at most $k$ (see `weight`). Dually,
+/// $B(k)$ is the quotient of the Steenrod algebra by the span of the $\chi(Q(E) P(R))$ of weight
+/// greater than $k$. At the prime 2, this is $A/A\{\chi(\mathrm{Sq}^i) : i > k\}$.
+///
+/// The basis of $B(k)$ in each degree is given by the images of the $\chi(Q(E) P(R))$ of weight
+/// at most $k$, in the order of the Milnor basis.
+pub fn brown_gitler_module<A: SteenrodAlgebraT>(algebra: Arc<A>, k: u32) -> FDModule<A> {
+    let p = algebra.prime();
+    let milnor = MilnorAlgebra::new(p);
+
+    // An element of weight w has degree less than 2w.
+    let max_degree = k
+        .checked_mul(2)
+        .and_then(|d| i32::try_from(d).ok())
+        .expect("B(k) has degrees that do not fit in an i32");
+    milnor.compute_basis(max_degree);
+    algebra.compute_basis(max_degree);
+
+    // For each degree, the Milnor basis elements of weight at most k, and the inverse map.
+    let mut basis = Vec::new();
+    let mut milnor_to_module = Vec::new();
+    for t in 0..=max_degree {
+        let mut elements = Vec::new();
+        let mut indices = Vec::new();
+        for i in 0..milnor.dimension(t, -1) {
+            if weight(*p, milnor.basis_element_from_index(t, i)) <= k {
+                indices.push(Some(elements.len()));
+                elements.push(i);
+            } else {
+                indices.push(None);
+            }
+        }
+        basis.push(elements);
+        milnor_to_module.push(indices);
+    }
+    while basis.last().map(Vec::is_empty) == Some(true) {
+        basis.pop();
+    }
+    let basis = BiVec::from_vec(0, basis);
+    let milnor_to_module = BiVec::from_vec(0, milnor_to_module);
+    let graded_dimension = BiVec::from_vec(0, basis.iter().map(Vec::len).collect());
+
+    let mut result = FDModule::new(Arc::clone(&algebra), format!("B({})", k), graded_dimension);
+    let top_degree = basis.max_degree();
+    for input_degree in 0..=top_degree {
+        for output_degree in input_degree + 1..=top_degree {
+            if basis[output_degree].is_empty() {
+                continue;
+            }
+            let op_degree = output_degree - input_degree;
+            let mut op = FpVector::new(p, milnor.dimension(op_degree, -1));
+            let mut input = FpVector::new(p, milnor.dimension(input_degree, -1));
+            let mut product = FpVector::new(p, milnor.dimension(output_degree, -1));
+            let mut output = FpVector::new(p, milnor.dimension(output_degree, -1));
+            for op_idx in 0..algebra.dimension(op_degree, -1) {
+                op.set_to_zero();
+                algebra_to_milnor(&*algebra, &milnor, &mut op, op_degree, op_idx);
+                for (input_idx, &i) in basis[input_degree].iter().enumerate() {
+                    // The coordinates of op * χ(x) in the basis χ(Q(E) P(R)) are the coordinates
+                    // of χ(op * χ(x)) in the Milnor basis.
+                    input.set_to_zero();
+                    milnor.antipode_on_basis(&mut input, 1, input_degree, i);
+                    product.set_to_zero();
+                    milnor.multiply_element_by_element(
+                        &mut product,
+                        1,
+                        op_degree,
+                        &op,
+                        input_degree,
+                        &input,
+                        -1,
+                    );
+                    output.set_to_zero();
+                    milnor.antipode(&mut output, 1, output_degree, &product);
+
+                    let action = result.action_mut(op_degree, op_idx, input_degree, input_idx);
+                    for (l, c) in output.iter().enumerate() {
+                        if let Some(idx) = milnor_to_module[output_degree][l] {
+                            action.add_basis_element(idx, c);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/// The module $A/\!/A(n) = A \otimes_{A(n)} \mathbb{F}_p$. This is the quotient of $A$ by the
+/// left ideal generated by $\mathrm{Sq}^{2^i}$ for $i \leq n$ at the prime 2, and by $\beta$ and
+/// $P^{p^i}$ for $i < n$ at odd primes. The generator is called `x0`.
+///
+/// This fails if the generators of $A(n)$ are not in `algebra`, or if their degrees do not fit in
+/// an `i32`.
+pub fn a_mod_a_n_module<A: SteenrodAlgebraT>(
+    algebra: Arc<A>,
+    n: u32,
+) -> error::Result<FPModule<A>> {
+    let p = algebra.prime();
+    let milnor = MilnorAlgebra::new(p);
+
+    let too_large = || GenericError::new(format!("The generators of A({}) are too large", n));
+    let mut relations = Vec::new();
+    if *p == 2 {
+        for i in 0..=n {
+            let r = 2u32
+                .checked_pow(i)
+                .filter(|&r| r <= i32::MAX as u32)
+                .ok_or_else(too_large)?;
+            relations.push(MilnorBasisElement {
+                q_part: 0,
+                p_part: vec![r],
+                degree: r as i32,
+            });
+        }
+    } else {
+        relations.push(MilnorBasisElement {
+            q_part: 1,
+            p_part: vec![],
+            degree: 1,
+        });
+        for i in 0..n {
+            let r = p.checked_pow(i).ok_or_else(too_large)?;
+            let degree = r
+                .checked_mul(2 * *p - 2)
+                .and_then(|d| i32::try_from(d).ok())
+                .ok_or_else(too_large)?;
+            relations.push(MilnorBasisElement {
+                q_part: 0,
+                p_part: vec![r],
+                degree,
+            });
+        }
+    }
+
+    let max_degree = relations.last().unwrap().degree;
+    algebra.compute_basis(max_degree);
+
+    let result = FPModule::new(Arc::clone(&algebra), format!("A//A({})", n), 0);
+    result.add_generators(0, vec!["x0".to_string()]);
+    result.generators.extend_by_zero(max_degree);
+    for t in 0..=max_degree {
+        let dimension = result.generators.dimension(t);
+        let mut rows = Vec::new();
+        for elt in relations.iter().filter(|elt| elt.degree == t) {
+            let mut row = FpVector::new(p, dimension);
+            milnor_to_algebra(&*algebra, &milnor, &mut row, elt)?;
+            rows.push(row);
+        }
+        let mut matrix = Matrix::from_rows(p, rows, dimension);
+        result.add_relations(t, &mut matrix);
+    }
+    Ok(result)
+}
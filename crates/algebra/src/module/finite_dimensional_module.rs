@@ -1,14 +1,16 @@
 use bivec::BiVec;
 
-use crate::algebra::Algebra;
-use crate::module::{BoundedModule, Module, ModuleFailedRelationError, ZeroModule};
+use crate::algebra::{Algebra, HopfAlgebra};
+use crate::module::{BoundedModule, Module, ModuleFailedRelationError, ModuleSpecError, ZeroModule};
 use error::GenericError;
+use fp::matrix::{AugmentedMatrix2, Subspace};
 use fp::vector::{FpVector, FpVectorT};
 
 use serde::Deserialize;
 use serde_json::json;
 use serde_json::value::Value;
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -17,7 +19,7 @@ use nom::{
     branch::alt,
     bytes::complete::{is_not, take},
     character::complete::{char, digit1, space0, space1},
-    combinator::map,
+    combinator::{map, map_res},
     multi::separated_list,
     sequence::delimited,
     sequence::tuple,
@@ -246,19 +248,28 @@ impl<A: Algebra> FiniteDimensionalModule<A> {
         self.gen_names[degree][idx] = name;
     }
 
+    #[allow(clippy::type_complexity)]
     fn module_gens_from_json(
         gens: Value,
-    ) -> (
-        BiVec<usize>,
-        BiVec<Vec<String>>,
-        HashMap<String, (i32, usize)>,
-    ) {
-        let gens = gens.as_object().unwrap();
+    ) -> Result<
+        (
+            BiVec<usize>,
+            BiVec<Vec<String>>,
+            HashMap<String, (i32, usize)>,
+        ),
+        ModuleSpecError,
+    > {
+        let gens = gens.as_object().ok_or_else(|| ModuleSpecError::InvalidField {
+            path: "gens".to_string(),
+            expected: "an object",
+        })?;
 
         let degrees = gens
             .iter()
-            .map(|(_, x)| x.as_i64().unwrap() as i32)
-            .collect::<Vec<_>>();
+            .map(|(name, x)| {
+                ModuleSpecError::expect_i64(x, &format!("gens.{}", name)).map(|x| x as i32)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         let min_degree = degrees.iter().copied().min().unwrap_or(0);
         let max_degree = degrees.iter().copied().max().unwrap_or(-1) + 1;
@@ -272,13 +283,12 @@ impl<A: Algebra> FiniteDimensionalModule<A> {
             gen_names.push(vec![]);
         }
 
-        for (name, degree) in gens {
-            let degree = degree.as_i64().unwrap() as i32;
+        for (name, &degree) in gens.keys().zip(&degrees) {
             gen_names[degree].push(name.clone());
             gen_to_idx.insert(name.clone(), (degree, graded_dimension[degree]));
             graded_dimension[degree] += 1;
         }
-        (graded_dimension, gen_names, gen_to_idx)
+        Ok((graded_dimension, gen_names, gen_to_idx))
     }
 
     fn allocate_actions(
@@ -480,7 +490,7 @@ impl<A: Algebra> FiniteDimensionalModule<A> {
 
     pub fn from_json(algebra: Arc<A>, json: &mut Value) -> error::Result<Self> {
         let gens = json["gens"].take();
-        let (graded_dimension, gen_names, gen_to_idx) = Self::module_gens_from_json(gens);
+        let (graded_dimension, gen_names, gen_to_idx) = Self::module_gens_from_json(gens)?;
         let name = json["name"].as_str().unwrap_or("").to_string();
 
         let mut result = Self::new(Arc::clone(&algebra), name, graded_dimension.clone());
@@ -491,8 +501,10 @@ impl<A: Algebra> FiniteDimensionalModule<A> {
         }
 
         if let Ok(actions) = serde_json::from_value::<Vec<String>>(json["actions"].take()) {
-            for action in actions {
-                result.parse_action(&gen_to_idx, &action, false)?;
+            for (i, action) in actions.iter().enumerate() {
+                result
+                    .parse_action(&gen_to_idx, action, false)
+                    .map_err(|err| err.at(format!("actions[{}]", i)))?;
             }
             for input_degree in (result.min_degree()..=result.max_degree()).rev() {
                 for output_degree in input_degree + 1..=result.max_degree() {
@@ -513,21 +525,67 @@ impl<A: Algebra> FiniteDimensionalModule<A> {
                 output: Vec<OutputStruct>,
             };
 
-            let actions_value = json[format!("{}_actions", algebra.algebra_type())].take();
-            let actions: Vec<ActionStruct> = serde_json::from_value(actions_value)?;
-            for action in actions {
-                let (degree, idx) = algebra.json_to_basis(action.op)?;
+            let key = format!("{}_actions", algebra.algebra_type());
+            let actions_value = json[&key].take();
+            // If neither format is present, complain about the usual one.
+            let path = if actions_value.is_null() {
+                "actions".to_string()
+            } else {
+                key.clone()
+            };
+            let actions: Vec<ActionStruct> = serde_json::from_value(actions_value)
+                .map_err(|_| ModuleSpecError::InvalidField {
+                    path,
+                    expected: "an array of actions",
+                })?;
+            for (i, action) in actions.into_iter().enumerate() {
+                let op = action.op.to_string();
+                let (degree, idx) =
+                    algebra
+                        .json_to_basis(action.op)
+                        .map_err(|err| ModuleSpecError::Invalid {
+                            path: format!("{}[{}].op", key, i),
+                            input: op,
+                            info: err.to_string(),
+                        })?;
+                let unknown_generator = |path: String, gen: &str| ModuleSpecError::UnknownGenerator {
+                    path,
+                    input: gen.to_string(),
+                    generator: gen.to_string(),
+                };
                 let input = action.input;
                 let (input_degree, input_idx) = *gen_to_idx
                     .get(&input)
-                    .ok_or_else(|| GenericError::new(format!("Invalid generator: {}", input)))?;
-                let output_vec = result.action_mut(degree, idx, input_degree, input_idx);
-                for basis_elt in action.output {
+                    .ok_or_else(|| unknown_generator(format!("{}[{}].input", key, i), &input))?;
+                let output_degree = input_degree + degree;
+                // Validate the outputs before writing them, since action_mut panics if there is
+                // nothing in the output degree.
+                let mut outputs = Vec::with_capacity(action.output.len());
+                for (j, basis_elt) in action.output.into_iter().enumerate() {
+                    let path = format!("{}[{}].output[{}].gen", key, i, j);
                     let gen = basis_elt.gen;
-                    let (_, output_idx) = *gen_to_idx.get(&gen).ok_or_else(move || {
-                        GenericError::new(format!("Invalid generator: {}", gen))
-                    })?;
-                    output_vec.add_basis_element(output_idx, basis_elt.coeff);
+                    let (gen_degree, output_idx) = *gen_to_idx
+                        .get(&gen)
+                        .ok_or_else(|| unknown_generator(path.clone(), &gen))?;
+                    if gen_degree != output_degree {
+                        return Err(ModuleSpecError::Invalid {
+                            path,
+                            info: format!(
+                                "{} is in degree {} but the action has degree {}",
+                                gen, gen_degree, output_degree
+                            ),
+                            input: gen,
+                        }
+                        .into());
+                    }
+                    outputs.push((output_idx, basis_elt.coeff));
+                }
+                if outputs.is_empty() {
+                    continue;
+                }
+                let output_vec = result.action_mut(degree, idx, input_degree, input_idx);
+                for (output_idx, coeff) in outputs {
+                    output_vec.add_basis_element(output_idx, coeff);
                 }
             }
         }
@@ -552,7 +610,7 @@ impl<A: Algebra> FiniteDimensionalModule<A> {
         gen_to_idx: &HashMap<String, (i32, usize)>,
         entry_: &str,
         overwrite: bool,
-    ) -> error::Result<()> {
+    ) -> Result<(), ModuleSpecError> {
         let algebra = self.algebra();
         let lhs = tuple((
             |e| algebra.string_to_generator(e),
@@ -561,11 +619,31 @@ impl<A: Algebra> FiniteDimensionalModule<A> {
         ));
 
         let (entry, ((op_deg, op_idx), gen, _)) =
-            lhs(entry_).map_err(|_err| GenericError::new(format!("Invalid action: {}", entry_)))?;
+            lhs(entry_).map_err(|err| ModuleSpecError::from_nom(entry_, err))?;
 
+        let unknown_generator = |gen: &str| ModuleSpecError::UnknownGenerator {
+            path: String::new(),
+            input: entry_.to_string(),
+            generator: gen.to_string(),
+        };
         let (input_deg, input_idx) = *gen_to_idx
             .get(gen.trim())
-            .ok_or_else(|| GenericError::new(format!("Invalid generator: {}", gen.trim())))?;
+            .ok_or_else(|| unknown_generator(gen.trim()))?;
+
+        let is_zero = matches!(
+            delimited(space0, char('0'), space0)(entry),
+            IResult::<_, _>::Ok(("", _))
+        );
+        if input_deg + op_deg > self.max_degree() {
+            if is_zero {
+                return Ok(());
+            }
+            return Err(ModuleSpecError::Invalid {
+                path: String::new(),
+                input: entry_.to_string(),
+                info: format!("The module is zero in degree {}", input_deg + op_deg),
+            });
+        }
 
         let row = self.action_mut(op_deg, op_idx, input_deg, input_idx);
 
@@ -573,22 +651,32 @@ impl<A: Algebra> FiniteDimensionalModule<A> {
             row.set_to_zero_pure();
         }
 
-        if let IResult::<_, _>::Ok(("", _)) = delimited(space0, char('0'), space0)(entry) {
+        if is_zero {
             return Ok(());
         }
 
         // Need explicit type here
-        let (_, values) = <IResult<_, _>>::unwrap(separated_list(take(1usize), is_not("+"))(entry));
+        let (_, values) = <IResult<_, _>>::map_err(
+            separated_list(take(1usize), is_not("+"))(entry),
+            |err| ModuleSpecError::from_nom(entry_, err),
+        )?;
 
         for value in values {
-            let (_, (coef, gen)) = Self::take_element(value)
-                .map_err(|_| GenericError::new(format!("Invalid action: {}", entry_)))?;
+            let (_, (coef, gen)) =
+                Self::take_element(value).map_err(|err| ModuleSpecError::from_nom(entry_, err))?;
 
-            let (deg, idx) = *gen_to_idx
-                .get(gen)
-                .ok_or_else(|| GenericError::new(format!("Invalid generator: {}", gen)))?;
+            let (deg, idx) = *gen_to_idx.get(gen).ok_or_else(|| unknown_generator(gen))?;
             if deg != input_deg + op_deg {
-                return Err(GenericError::new(format!("Invalid action: {}", entry_)).into());
+                return Err(ModuleSpecError::Invalid {
+                    path: String::new(),
+                    input: entry_.to_string(),
+                    info: format!(
+                        "{} is in degree {} but the action has degree {}",
+                        gen,
+                        deg,
+                        input_deg + op_deg
+                    ),
+                });
             }
 
             row.add_basis_element(idx, coef);
@@ -601,20 +689,30 @@ impl<A: Algebra> FiniteDimensionalModule<A> {
         entry: &str,
         degree: i32,
         result: &mut FpVector,
-    ) -> error::Result<()> {
+    ) -> Result<(), ModuleSpecError> {
         if let IResult::<_, _>::Ok(("", _)) = delimited(space0, char('0'), space0)(entry) {
             return Ok(());
         }
         for elt in entry.split('+') {
-            if let Ok(("", (coef, gen))) = Self::take_element(elt.trim_end()) {
-                if let Some(idx) = self.gen_names[degree].iter().position(|x| x == gen) {
-                    result.add_basis_element(idx, coef);
-                } else {
-                    return Err(GenericError::new(format!("Invalid generator: {}", elt)).into());
-                }
-            } else {
-                return Err(GenericError::new(format!("Invalid term: {}", elt)).into());
+            let (rest, (coef, gen)) = Self::take_element(elt.trim_end())
+                .map_err(|err| ModuleSpecError::from_nom(entry, err))?;
+            if !rest.is_empty() {
+                return Err(ModuleSpecError::parse_error_at(
+                    entry,
+                    rest,
+                    "Unexpected input after term",
+                ));
             }
+            let idx = self
+                .gen_names
+                .get(degree)
+                .and_then(|names| names.iter().position(|x| x == gen))
+                .ok_or_else(|| ModuleSpecError::UnknownGenerator {
+                    path: String::new(),
+                    input: entry.to_string(),
+                    generator: gen.to_string(),
+                })?;
+            result.add_basis_element(idx, coef);
         }
         Ok(())
     }
@@ -622,8 +720,8 @@ impl<A: Algebra> FiniteDimensionalModule<A> {
     fn take_element(i: &str) -> IResult<&str, (u32, &str)> {
         // coefficient, name
         let coef_gen = map(
-            tuple((space0, digit1, space1, is_not(" "))),
-            |(_, coef, _, gen)| (FromStr::from_str(coef).unwrap(), gen),
+            tuple((space0, map_res(digit1, u32::from_str), space1, is_not(" "))),
+            |(_, coef, _, gen)| (coef, gen),
         );
         let o_gen = map(tuple((space0, is_not(" "))), |(_, gen)| (1, gen));
         alt((coef_gen, o_gen))(i)
@@ -806,12 +904,389 @@ impl<A: Algebra> FiniteDimensionalModule<A> {
         }
         gens
     }
+
+    /// Returns the suspension $\Sigma^{\mathrm{shift}} M$, i.e. the module whose degrees are
+    /// shifted up by `shift`. The action is the same as that of the original module.
+    pub fn shift(&self, shift: i32) -> Self {
+        let min_degree = self.min_degree();
+        let max_degree = self.max_degree();
+
+        let mut graded_dimension =
+            BiVec::with_capacity(min_degree + shift, max_degree + shift + 1);
+        for t in min_degree..=max_degree {
+            graded_dimension.push(self.dimension(t));
+        }
+        let mut result = Self::new(
+            self.algebra(),
+            format!("Σ^{} {}", shift, self.name),
+            graded_dimension,
+        );
+        for t in min_degree..=max_degree {
+            result.gen_names[t + shift] = self.gen_names[t].clone();
+        }
+
+        let algebra = self.algebra();
+        for input_degree in min_degree..=max_degree {
+            for output_degree in input_degree + 1..=max_degree {
+                if self.dimension(output_degree) == 0 {
+                    continue;
+                }
+                let op_degree = output_degree - input_degree;
+                for op_idx in 0..algebra.dimension(op_degree, -1) {
+                    for input_idx in 0..self.dimension(input_degree) {
+                        result
+                            .action_mut(op_degree, op_idx, input_degree + shift, input_idx)
+                            .assign(self.action(op_degree, op_idx, input_degree, input_idx));
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// The maximum number of choices of generators that are compared when computing a canonical form.
+const MAX_CANONICAL_FORM_CANDIDATES: usize = 100_000;
+
+/// A choice of generators of a module, together with the basis of the module it induces. See
+/// `FiniteDimensionalModule::canonical_form`.
+#[derive(Clone)]
+struct GeneratorChoice {
+    generators: Vec<(i32, FpVector)>,
+    basis: BiVec<Vec<FpVector>>,
+}
+
+impl<A: Algebra> FiniteDimensionalModule<A> {
+    /// Returns the subspace of decomposable elements of degree `degree`, i.e. those of the form
+    /// $\sum a_i m_i$ with $|a_i| > 0$.
+    fn decomposables(&self, degree: i32) -> Subspace {
+        let p = self.prime();
+        let algebra = self.algebra();
+        let dimension = self.dimension(degree);
+        let mut result = Subspace::new(p, dimension + 1, dimension);
+        for input_degree in self.min_degree()..degree {
+            let op_degree = degree - input_degree;
+            for op_idx in 0..algebra.dimension(op_degree, input_degree) {
+                for input_idx in 0..self.dimension(input_degree) {
+                    let mut output = FpVector::new(p, dimension);
+                    self.act_on_basis(&mut output, 1, op_degree, op_idx, input_degree, input_idx);
+                    result.add_vector(&output);
+                }
+            }
+        }
+        result
+    }
+
+    /// Lists all tuples of elements of degree `degree` whose images form an ordered basis of the
+    /// indecomposables in that degree, i.e. all possible choices of generators in this degree.
+    fn generator_tuples(&self, degree: i32) -> error::Result<Vec<Vec<FpVector>>> {
+        let p = self.prime();
+        let dimension = self.dimension(degree);
+        let decomposables = self.decomposables(degree);
+        let num_gens = dimension - decomposables.dimension();
+        if num_gens == 0 {
+            return Ok(vec![Vec::new()]);
+        }
+
+        let too_many_choices = || {
+            GenericError::new(format!(
+                "Too many choices of generators in degree {}",
+                degree
+            ))
+        };
+        // Every element of this degree is a candidate generator, so there are at least as many
+        // choices as elements outside the decomposables.
+        let num_elements = (*p as usize)
+            .checked_pow(dimension as u32)
+            .filter(|&n| n <= MAX_CANONICAL_FORM_CANDIDATES)
+            .ok_or_else(too_many_choices)?;
+
+        let mut elements = Vec::new();
+        let mut element = FpVector::new(p, dimension);
+        for _ in 0..num_elements {
+            if !decomposables.contains(&element) {
+                elements.push(element.clone());
+            }
+            // Increment the element, viewed as a number in base p.
+            for i in 0..dimension {
+                let c = element.entry(i) + 1;
+                element.set_entry(i, c % *p);
+                if c < *p {
+                    break;
+                }
+            }
+        }
+
+        let mut result = vec![(Vec::new(), decomposables)];
+        for _ in 0..num_gens {
+            let mut next = Vec::new();
+            for (tuple, span) in &result {
+                for element in &elements {
+                    if span.contains(element) {
+                        continue;
+                    }
+                    let mut tuple = tuple.clone();
+                    tuple.push(element.clone());
+                    let mut span = span.clone();
+                    span.add_vector(element);
+                    next.push((tuple, span));
+                    if next.len() > MAX_CANONICAL_FORM_CANDIDATES {
+                        return Err(too_many_choices().into());
+                    }
+                }
+            }
+            result = next;
+        }
+        Ok(result.into_iter().map(|(tuple, _)| tuple).collect())
+    }
+
+    /// Returns the basis of the module in degree `degree` obtained by acting on `generators` with
+    /// the basis elements of the algebra, in order, and keeping the products that are linearly
+    /// independent from the previous ones. The generators must be sorted by degree.
+    fn basis_from_generators(&self, degree: i32, generators: &[(i32, FpVector)]) -> Vec<FpVector> {
+        let p = self.prime();
+        let algebra = self.algebra();
+        let dimension = self.dimension(degree);
+        let mut span = Subspace::new(p, dimension + 1, dimension);
+        let mut result = Vec::with_capacity(dimension);
+        for (gen_degree, gen) in generators {
+            let op_degree = degree - gen_degree;
+            if op_degree < 0 {
+                break;
+            }
+            for op_idx in 0..algebra.dimension(op_degree, *gen_degree) {
+                let mut output = FpVector::new(p, dimension);
+                self.act(&mut output, 1, op_degree, op_idx, *gen_degree, gen);
+                if !span.contains(&output) {
+                    span.add_vector(&output);
+                    result.push(output);
+                }
+            }
+        }
+        assert_eq!(result.len(), dimension);
+        result
+    }
+
+    /// Returns the actions with target degree `degree` on `basis`, written in terms of `basis`.
+    /// If `generators_only` is true, this only includes the actions of the algebra generators.
+    fn actions_in_basis(
+        &self,
+        degree: i32,
+        basis: &BiVec<Vec<FpVector>>,
+        generators_only: bool,
+    ) -> Vec<FpVector> {
+        let p = self.prime();
+        let algebra = self.algebra();
+        let dimension = self.dimension(degree);
+        if dimension == 0 {
+            return Vec::new();
+        }
+
+        let mut matrix = AugmentedMatrix2::new(p, dimension, &[dimension, dimension]);
+        for (i, v) in basis[degree].iter().enumerate() {
+            for (j, c) in v.iter().enumerate() {
+                matrix[i].set_entry(j, c);
+            }
+        }
+        matrix.segment(1, 1).add_identity(dimension, 0, 0);
+        matrix.initialize_pivots();
+        matrix.row_reduce();
+        let quasi_inverse = matrix.compute_quasi_inverse();
+
+        let mut result = Vec::new();
+        for input_degree in self.min_degree()..degree {
+            let op_degree = degree - input_degree;
+            let ops = if generators_only {
+                algebra.generators(op_degree)
+            } else {
+                (0..algebra.dimension(op_degree, -1)).collect()
+            };
+            for op_idx in ops {
+                for input in &basis[input_degree] {
+                    let mut output = FpVector::new(p, dimension);
+                    self.act(&mut output, 1, op_degree, op_idx, input_degree, input);
+                    let mut coordinates = FpVector::new(p, dimension);
+                    quasi_inverse.apply(&mut coordinates, 1, &output);
+                    result.push(coordinates);
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns a canonical form of the module, so that two modules are isomorphic if and only if
+    /// their canonical forms are equal. The algebra must be connected.
+    ///
+    /// Every choice of minimal generators gives a basis of the module by acting on the generators
+    /// with the basis elements of the algebra. The canonical form is the module written in the
+    /// basis for which the actions of the algebra generators are lexicographically smallest. We
+    /// find this degree by degree, keeping only the choices that are minimal so far. This returns
+    /// an error if there are too many choices to compare.
+    pub fn canonical_form(&self) -> error::Result<Self> {
+        let algebra = self.algebra();
+        if algebra.dimension(0, -1) != 1 {
+            return Err(GenericError::new(
+                "Canonical forms are only defined over connected algebras".to_string(),
+            )
+            .into());
+        }
+        let min_degree = self.min_degree();
+        let max_degree = self.max_degree();
+
+        let mut choices = vec![GeneratorChoice {
+            generators: Vec::new(),
+            basis: BiVec::new(min_degree),
+        }];
+        for degree in min_degree..=max_degree {
+            let tuples = self.generator_tuples(degree)?;
+            let mut best: Option<Vec<FpVector>> = None;
+            let mut next = Vec::new();
+            for choice in &choices {
+                for tuple in &tuples {
+                    let mut choice = choice.clone();
+                    choice
+                        .generators
+                        .extend(tuple.iter().map(|v| (degree, v.clone())));
+                    let basis = self.basis_from_generators(degree, &choice.generators);
+                    choice.basis.push(basis);
+                    let actions = self.actions_in_basis(degree, &choice.basis, true);
+
+                    let ordering = match &best {
+                        None => Ordering::Less,
+                        Some(best) => actions
+                            .iter()
+                            .flat_map(FpVector::iter)
+                            .cmp(best.iter().flat_map(FpVector::iter)),
+                    };
+                    match ordering {
+                        Ordering::Less => {
+                            best = Some(actions);
+                            next.clear();
+                            next.push(choice);
+                        }
+                        Ordering::Equal => next.push(choice),
+                        Ordering::Greater => (),
+                    }
+                    if next.len() > MAX_CANONICAL_FORM_CANDIDATES {
+                        return Err(GenericError::new(
+                            "Too many choices of generators to compute canonical form".to_string(),
+                        )
+                        .into());
+                    }
+                }
+            }
+            choices = next;
+        }
+
+        let basis = &choices[0].basis;
+        let mut result = Self::new(
+            Arc::clone(&algebra),
+            self.name.clone(),
+            self.graded_dimension.clone(),
+        );
+        for output_degree in min_degree..=max_degree {
+            if self.dimension(output_degree) == 0 {
+                continue;
+            }
+            // The actions are listed in the order of this iteration.
+            let indices = (min_degree..output_degree).flat_map(|input_degree| {
+                let op_degree = output_degree - input_degree;
+                (0..algebra.dimension(op_degree, -1)).flat_map(move |op_idx| {
+                    (0..self.dimension(input_degree))
+                        .map(move |input_idx| (op_degree, op_idx, input_degree, input_idx))
+                })
+            });
+            let actions = self.actions_in_basis(output_degree, basis, false);
+            for ((op_degree, op_idx, input_degree, input_idx), output) in indices.zip(&actions) {
+                result.set_action_vector(op_degree, op_idx, input_degree, input_idx, output);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Decides whether two modules are isomorphic by comparing their canonical forms. See
+    /// `canonical_form`.
+    pub fn is_isomorphic(&self, other: &Self) -> error::Result<bool> {
+        if self.prime() != other.prime() || self.graded_dimension != other.graded_dimension {
+            return Ok(false);
+        }
+        Ok(self.canonical_form()? == other.canonical_form()?)
+    }
+}
+
+impl<A: HopfAlgebra> FiniteDimensionalModule<A> {
+    /// Returns the dual module $DM$, with $(DM)_{-t} = \mathrm{Hom}(M_t, \mathbb{F}_p)$. The
+    /// basis of $DM$ is the dual basis, and an element $a$ of the algebra acts on $f \in DM$ by
+    /// $$ (af)(m) = (-1)^{|a| |f|} f(\chi(a) m). $$
+    /// If $M$ is the cohomology of a finite spectrum $X$, then $DM$ is the cohomology of the
+    /// Spanier-Whitehead dual of $X$.
+    pub fn dual(&self) -> Self {
+        let p = self.prime();
+        let algebra = self.algebra();
+        let min_degree = self.min_degree();
+        let max_degree = self.max_degree();
+        algebra.compute_basis(max_degree - min_degree);
+
+        let mut graded_dimension = BiVec::with_capacity(-max_degree, -min_degree + 1);
+        for t in -max_degree..=-min_degree {
+            graded_dimension.push(self.dimension(-t));
+        }
+        let mut result = Self::new(
+            Arc::clone(&algebra),
+            format!("D{}", self.name),
+            graded_dimension,
+        );
+        for t in min_degree..=max_degree {
+            for (idx, name) in self.gen_names[t].iter().enumerate() {
+                result.set_basis_element_name(-t, idx, format!("{}*", name));
+            }
+        }
+
+        // The dual basis element f of M_{-input_degree} is sent to an element of degree
+        // output_degree, which is determined by its values on M_{-output_degree}.
+        for input_degree in -max_degree..=-min_degree {
+            let input_dimension = self.dimension(-input_degree);
+            for output_degree in input_degree + 1..=-min_degree {
+                let output_dimension = self.dimension(-output_degree);
+                if input_dimension == 0 || output_dimension == 0 {
+                    continue;
+                }
+                let op_degree = output_degree - input_degree;
+                let coeff = if op_degree * input_degree % 2 == 0 {
+                    1
+                } else {
+                    *p - 1
+                };
+
+                for op_idx in 0..algebra.dimension(op_degree, -1) {
+                    let mut chi = FpVector::new(p, algebra.dimension(op_degree, -1));
+                    algebra.antipode_on_basis(&mut chi, 1, op_degree, op_idx);
+
+                    for m_idx in 0..output_dimension {
+                        let mut m = FpVector::new(p, output_dimension);
+                        m.set_entry(m_idx, 1);
+                        let mut image = FpVector::new(p, input_dimension);
+                        self.act_by_element(&mut image, coeff, op_degree, &chi, -output_degree, &m);
+
+                        for (f_idx, c) in image.iter().enumerate() {
+                            if c != 0 {
+                                result
+                                    .action_mut(op_degree, op_idx, input_degree, f_idx)
+                                    .add_basis_element(m_idx, c);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::algebra::{AdemAlgebra, SteenrodAlgebra};
+    use crate::algebra::{AdemAlgebra, MilnorAlgebra, SteenrodAlgebra};
     use bivec::BiVec;
 
     #[test]
@@ -834,4 +1309,135 @@ mod tests {
         adem_module.set_action_vector(2, 0, 0, 0, &FpVector::from_vec(p, &[1]));
         adem_module.check_validity(0, 2).unwrap();
     }
+
+    #[test]
+    fn test_module_dual_shift() {
+        let modules = [
+            json!({"p": 2, "gens": {"x0": 0, "x1": 1}, "actions": ["Sq1 x0 = x1"]}),
+            json!({
+                "p": 2,
+                "gens": {"x0": 0, "x1": 1, "x2": 2, "x3": 3, "x4": 4},
+                "actions": ["Sq1 x0 = x1", "Sq2 x0 = x2", "Sq2 x1 = x3", "Sq2 x2 = x4", "Sq1 x3 = x4"]
+            }),
+            json!({
+                "p": 3,
+                "gens": {"x0": 0, "x1": 1, "x4": 4, "x5": 5},
+                "actions": ["b x0 = x1", "P1 x0 = x4", "P1 x1 = x5", "b x4 = x5"]
+            }),
+        ];
+        for json in &modules {
+            let p = fp::prime::ValidPrime::new(json["p"].as_u64().unwrap() as u32);
+            for &algebra_name in &["adem", "milnor"] {
+                let algebra = Arc::new(match algebra_name {
+                    "adem" => SteenrodAlgebra::from(AdemAlgebra::new(p, *p != 2, false)),
+                    _ => SteenrodAlgebra::from(MilnorAlgebra::new(p)),
+                });
+                algebra.compute_basis(10);
+                let module =
+                    FiniteDimensionalModule::from_json(Arc::clone(&algebra), &mut json.clone())
+                        .unwrap();
+
+                let dual = module.dual();
+                assert_eq!(dual.min_degree(), -module.max_degree());
+                assert_eq!(dual.max_degree(), -module.min_degree());
+                for input_degree in dual.min_degree()..=dual.max_degree() {
+                    for output_degree in input_degree + 1..=dual.max_degree() {
+                        dual.check_validity(input_degree, output_degree).unwrap();
+                    }
+                }
+                // The isomorphism M -> DDM sends m to (-1)^{|m|} m**, so a m** = (-1)^{|a|} (am)**.
+                let double_dual = module.dual().dual();
+                for input_degree in module.min_degree()..=module.max_degree() {
+                    for output_degree in input_degree + 1..=module.max_degree() {
+                        if module.dimension(output_degree) == 0 {
+                            continue;
+                        }
+                        let op_degree = output_degree - input_degree;
+                        for op_idx in 0..algebra.dimension(op_degree, -1) {
+                            for idx in 0..module.dimension(input_degree) {
+                                let mut expected =
+                                    module.action(op_degree, op_idx, input_degree, idx).clone();
+                                if op_degree % 2 == 1 {
+                                    expected.scale(*p - 1);
+                                }
+                                assert_eq!(
+                                    double_dual.action(op_degree, op_idx, input_degree, idx),
+                                    &expected
+                                );
+                            }
+                        }
+                    }
+                }
+                module.shift(3).shift(-3).test_equal(&module).unwrap();
+                assert_eq!(module.shift(3).min_degree(), module.min_degree() + 3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        let isomorphic = [
+            // Changing the basis by y0 -> x0 + y0
+            (
+                json!({"p": 2, "gens": {"x0": 0, "y0": 0, "x1": 1}, "actions": ["Sq1 x0 = x1"]}),
+                json!({"p": 2, "gens": {"a": 0, "b": 0, "c": 1}, "actions": ["Sq1 a = c", "Sq1 b = c"]}),
+            ),
+            // Changing the generator y1 by the decomposable x1
+            (
+                json!({"p": 2, "gens": {"x0": 0, "x1": 1, "y1": 1, "x3": 3}, "actions": ["Sq1 x0 = x1", "Sq2 x1 = x3", "Sq2 y1 = x3"]}),
+                json!({"p": 2, "gens": {"x0": 0, "x1": 1, "y1": 1, "x3": 3}, "actions": ["Sq1 x0 = x1", "Sq2 x1 = x3"]}),
+            ),
+            (
+                json!({"p": 3, "gens": {"x0": 0, "x1": 1}, "actions": ["b x0 = x1"]}),
+                json!({"p": 3, "gens": {"x0": 0, "x1": 1}, "actions": ["b x0 = 2 x1"]}),
+            ),
+        ];
+        let non_isomorphic = [
+            (
+                json!({"p": 2, "gens": {"x0": 0, "x1": 1, "x2": 2}, "actions": ["Sq1 x0 = x1"]}),
+                json!({"p": 2, "gens": {"x0": 0, "x1": 1, "x2": 2}, "actions": ["Sq2 x0 = x2"]}),
+            ),
+            (
+                json!({"p": 2, "gens": {"x0": 0, "y0": 0, "x1": 1}, "actions": ["Sq1 x0 = x1"]}),
+                json!({"p": 2, "gens": {"x0": 0, "y0": 0, "x1": 1}, "actions": []}),
+            ),
+        ];
+
+        let load = |json: &Value| {
+            let p = fp::prime::ValidPrime::new(json["p"].as_u64().unwrap() as u32);
+            let algebra = Arc::new(SteenrodAlgebra::from(MilnorAlgebra::new(p)));
+            FiniteDimensionalModule::from_json(algebra, &mut json.clone()).unwrap()
+        };
+        for (a, b) in &isomorphic {
+            let (a, b) = (load(a), load(b));
+            assert!(a.is_isomorphic(&b).unwrap());
+            let canonical = a.canonical_form().unwrap();
+            assert!(canonical == b.canonical_form().unwrap());
+            assert!(canonical == canonical.canonical_form().unwrap());
+            for input_degree in canonical.min_degree()..=canonical.max_degree() {
+                for output_degree in input_degree + 1..=canonical.max_degree() {
+                    canonical.check_validity(input_degree, output_degree).unwrap();
+                }
+            }
+        }
+        for (a, b) in &non_isomorphic {
+            assert!(!load(a).is_isomorphic(&load(b)).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_canonical_form_too_many_choices() {
+        let p = fp::prime::ValidPrime::new(2);
+        let algebra = Arc::new(SteenrodAlgebra::from(MilnorAlgebra::new(p)));
+        // 2^20 elements is more than the limit, and 2^70 does not fit in a usize.
+        for &num_gens in &[20, 70] {
+            let gens: serde_json::Map<String, Value> = (0..num_gens)
+                .map(|i| (format!("x{}", i), json!(0)))
+                .collect();
+            let mut json = json!({"p": 2, "gens": gens, "actions": []});
+            let module =
+                FiniteDimensionalModule::from_json(Arc::clone(&algebra), &mut json).unwrap();
+            assert!(module.canonical_form().is_err());
+        }
+    }
 }
@@ -1,8 +1,15 @@
 use crate::algebra::{Algebra, SteenrodAlgebra};
-use crate::module::{FDModule, FPModule, Module, RealProjectiveSpace};
+use crate::module::{
+    a_mod_a_n_module, brown_gitler_module, BoundedModule, CupProducts, FDModule, FPModule,
+    LensSpace, Module, ModuleSpecError, ProjectiveSpace, ProjectiveSpaceKind, RealProjectiveSpace,
+    SumModule, TensorModule, TruncatedModule,
+};
+use error::GenericError;
 use fp::prime::ValidPrime;
 use fp::vector::FpVector;
 use serde_json::Value;
+use std::collections::HashSet;
+use std::convert::TryFrom;
 use std::sync::Arc;
 
 #[derive(PartialEq, Eq)]
@@ -10,6 +17,8 @@ pub enum FiniteModule {
     FDModule(FDModule<SteenrodAlgebra>),
     FPModule(FPModule<SteenrodAlgebra>),
     RealProjectiveSpace(RealProjectiveSpace<SteenrodAlgebra>),
+    ProjectiveSpace(ProjectiveSpace<SteenrodAlgebra>),
+    LensSpace(LensSpace<SteenrodAlgebra>),
 }
 
 impl Module for FiniteModule {
@@ -20,6 +29,8 @@ impl Module for FiniteModule {
             FiniteModule::FDModule(m) => m.algebra(),
             FiniteModule::FPModule(m) => m.algebra(),
             FiniteModule::RealProjectiveSpace(m) => m.algebra(),
+            FiniteModule::ProjectiveSpace(m) => m.algebra(),
+            FiniteModule::LensSpace(m) => m.algebra(),
         }
     }
 
@@ -28,6 +39,8 @@ impl Module for FiniteModule {
             FiniteModule::FDModule(m) => m.name(),
             FiniteModule::FPModule(m) => m.name(),
             FiniteModule::RealProjectiveSpace(m) => m.name(),
+            FiniteModule::ProjectiveSpace(m) => m.name(),
+            FiniteModule::LensSpace(m) => m.name(),
         }
     }
 
@@ -36,6 +49,8 @@ impl Module for FiniteModule {
             FiniteModule::FDModule(m) => m.min_degree(),
             FiniteModule::FPModule(m) => m.min_degree(),
             FiniteModule::RealProjectiveSpace(m) => m.min_degree(),
+            FiniteModule::ProjectiveSpace(m) => m.min_degree(),
+            FiniteModule::LensSpace(m) => m.min_degree(),
         }
     }
 
@@ -44,6 +59,8 @@ impl Module for FiniteModule {
             FiniteModule::FDModule(m) => m.compute_basis(degree),
             FiniteModule::FPModule(m) => m.compute_basis(degree),
             FiniteModule::RealProjectiveSpace(m) => m.compute_basis(degree),
+            FiniteModule::ProjectiveSpace(m) => m.compute_basis(degree),
+            FiniteModule::LensSpace(m) => m.compute_basis(degree),
         }
     }
 
@@ -52,6 +69,8 @@ impl Module for FiniteModule {
             FiniteModule::FDModule(m) => m.max_computed_degree(),
             FiniteModule::FPModule(m) => m.max_computed_degree(),
             FiniteModule::RealProjectiveSpace(m) => m.max_computed_degree(),
+            FiniteModule::ProjectiveSpace(m) => m.max_computed_degree(),
+            FiniteModule::LensSpace(m) => m.max_computed_degree(),
         }
     }
 
@@ -60,6 +79,8 @@ impl Module for FiniteModule {
             FiniteModule::FDModule(m) => m.dimension(degree),
             FiniteModule::FPModule(m) => m.dimension(degree),
             FiniteModule::RealProjectiveSpace(m) => m.dimension(degree),
+            FiniteModule::ProjectiveSpace(m) => m.dimension(degree),
+            FiniteModule::LensSpace(m) => m.dimension(degree),
         }
     }
 
@@ -82,6 +103,12 @@ impl Module for FiniteModule {
             FiniteModule::RealProjectiveSpace(m) => {
                 m.act_on_basis(result, coeff, op_degree, op_index, mod_degree, mod_index)
             }
+            FiniteModule::ProjectiveSpace(m) => {
+                m.act_on_basis(result, coeff, op_degree, op_index, mod_degree, mod_index)
+            }
+            FiniteModule::LensSpace(m) => {
+                m.act_on_basis(result, coeff, op_degree, op_index, mod_degree, mod_index)
+            }
         }
     }
 
@@ -107,6 +134,12 @@ impl Module for FiniteModule {
             FiniteModule::RealProjectiveSpace(m) => {
                 m.act(result, coeff, op_degree, op_index, input_degree, input)
             }
+            FiniteModule::ProjectiveSpace(m) => {
+                m.act(result, coeff, op_degree, op_index, input_degree, input)
+            }
+            FiniteModule::LensSpace(m) => {
+                m.act(result, coeff, op_degree, op_index, input_degree, input)
+            }
         }
     }
 
@@ -129,6 +162,12 @@ impl Module for FiniteModule {
             FiniteModule::RealProjectiveSpace(m) => {
                 m.act_by_element(result, coeff, op_degree, op, input_degree, input)
             }
+            FiniteModule::ProjectiveSpace(m) => {
+                m.act_by_element(result, coeff, op_degree, op, input_degree, input)
+            }
+            FiniteModule::LensSpace(m) => {
+                m.act_by_element(result, coeff, op_degree, op, input_degree, input)
+            }
         }
     }
 
@@ -137,6 +176,8 @@ impl Module for FiniteModule {
             FiniteModule::FDModule(m) => m.basis_element_to_string(degree, idx),
             FiniteModule::FPModule(m) => m.basis_element_to_string(degree, idx),
             FiniteModule::RealProjectiveSpace(m) => m.basis_element_to_string(degree, idx),
+            FiniteModule::ProjectiveSpace(m) => m.basis_element_to_string(degree, idx),
+            FiniteModule::LensSpace(m) => m.basis_element_to_string(degree, idx),
         }
     }
 
@@ -190,26 +231,248 @@ impl From<RealProjectiveSpace<SteenrodAlgebra>> for FiniteModule {
         Self::RealProjectiveSpace(m)
     }
 }
+impl From<ProjectiveSpace<SteenrodAlgebra>> for FiniteModule {
+    fn from(m: ProjectiveSpace<SteenrodAlgebra>) -> Self {
+        Self::ProjectiveSpace(m)
+    }
+}
+impl From<LensSpace<SteenrodAlgebra>> for FiniteModule {
+    fn from(m: LensSpace<SteenrodAlgebra>) -> Self {
+        Self::LensSpace(m)
+    }
+}
 
 impl FiniteModule {
     pub fn from_json(
         algebra: Arc<SteenrodAlgebra>,
         json: &mut serde_json::Value,
     ) -> error::Result<Self> {
-        match json["type"].as_str() {
-            Some("real projective space") => Ok(FiniteModule::from(RealProjectiveSpace::from_json(
-                algebra, json,
-            )?)),
+        let module_type = json["type"].as_str().map(str::to_string);
+        let mut result = match module_type.as_deref() {
+            Some("real projective space") => {
+                return Ok(FiniteModule::from(RealProjectiveSpace::from_json(
+                    algebra, json,
+                )?))
+            }
+            Some("complex projective space") => {
+                return Ok(FiniteModule::from(ProjectiveSpace::from_json(
+                    algebra,
+                    ProjectiveSpaceKind::Complex,
+                    json,
+                )?))
+            }
+            Some("quaternionic projective space") => {
+                return Ok(FiniteModule::from(ProjectiveSpace::from_json(
+                    algebra,
+                    ProjectiveSpaceKind::Quaternionic,
+                    json,
+                )?))
+            }
+            Some("lens space") => {
+                return Ok(FiniteModule::from(LensSpace::from_json(algebra, json)?))
+            }
+            Some("brown gitler module") => {
+                let k = ModuleSpecError::expect_u64(&json["k"], "k")?;
+                // B(k) lives in degrees less than 2k, which have to fit in an i32.
+                let k = u32::try_from(k)
+                    .ok()
+                    .filter(|&k| k <= i32::MAX as u32 / 2)
+                    .ok_or_else(|| ModuleSpecError::Invalid {
+                        path: "k".to_string(),
+                        input: k.to_string(),
+                        info: "The parameter is too large".to_string(),
+                    })?;
+                return Ok(FiniteModule::from(brown_gitler_module(algebra, k)));
+            }
+            Some("A mod A(n)") => {
+                let n = ModuleSpecError::expect_u64(&json["n"], "n")?;
+                let n = u32::try_from(n).map_err(|_| ModuleSpecError::Invalid {
+                    path: "n".to_string(),
+                    input: n.to_string(),
+                    info: "The parameter is too large".to_string(),
+                })?;
+                return Ok(FiniteModule::from(a_mod_a_n_module(algebra, n)?));
+            }
             Some("finite dimensional module") => {
-                Ok(FiniteModule::from(FDModule::from_json(algebra, json)?))
+                let module = FDModule::from_json(algebra, json)?;
+                if json["unstable"].as_bool() == Some(true) {
+                    module.check_unstable()?;
+                }
+                if !json["cup_products"].is_null() {
+                    let products = CupProducts::from_json(&module, &json["cup_products"])?;
+                    module.check_cartan_formula(&products)?;
+                }
+                return Ok(FiniteModule::from(module));
             }
             Some("finitely presented module") => {
-                Ok(FiniteModule::from(FPModule::from_json(algebra, json)?))
+                let module = FiniteModule::from(FPModule::from_json(algebra, json)?);
+                if json["to_fd_module"].as_bool() == Some(true) {
+                    return Ok(module.fp_to_fd_module());
+                }
+                return Ok(module);
+            }
+            Some("dual") => Self::inner_fd_module(algebra, &mut json["module"], "dual")?.dual(),
+            Some("shift") => {
+                let shift = json["shift"].as_i64().ok_or_else(|| {
+                    GenericError::new("Missing or invalid shift in module spec".to_string())
+                })?;
+                Self::inner_fd_module(algebra, &mut json["module"], "shift")?.shift(shift as i32)
+            }
+            Some("tensor") => {
+                let left = Self::inner_fd_module(Arc::clone(&algebra), &mut json["left"], "tensor")?;
+                let right = Self::inner_fd_module(algebra, &mut json["right"], "tensor")?;
+                TensorModule::new(Arc::new(left), Arc::new(right)).to_fd_module()
+            }
+            Some("sum") => {
+                let specs = json["modules"].as_array_mut().ok_or_else(|| {
+                    GenericError::new("Missing list of modules in module spec".to_string())
+                })?;
+                let modules = specs
+                    .iter_mut()
+                    .map(|spec| Ok(Arc::new(Self::inner_fd_module(Arc::clone(&algebra), spec, "sum")?)))
+                    .collect::<error::Result<Vec<_>>>()?;
+                Self::sum_fd_module(algebra, modules)
+            }
+            x => {
+                return Err(UnknownModuleTypeError {
+                    module_type: x.map(str::to_string),
+                }
+                .into())
+            }
+        };
+        if let Some(name) = json["name"].as_str() {
+            result.name = name.to_string();
+        }
+        Ok(FiniteModule::from(result))
+    }
+
+    /// Builds the module specified by `spec`, which must be bounded, as an `FDModule`. This is
+    /// used by the module types that are constructed out of other modules, and `module_type` is
+    /// the type of the module being constructed.
+    fn inner_fd_module(
+        algebra: Arc<SteenrodAlgebra>,
+        spec: &mut Value,
+        module_type: &str,
+    ) -> error::Result<FDModule<SteenrodAlgebra>> {
+        if let Some(name) = spec.as_str() {
+            return Err(GenericError::new(format!("Unresolved module reference: {}", name)).into());
+        }
+        let module = FiniteModule::from_json(algebra, spec)?;
+        module.into_bounded_fd_module().ok_or_else(|| {
+            GenericError::new(format!(
+                "The modules in a {} module spec must be bounded",
+                module_type
+            ))
+            .into()
+        })
+    }
+
+    /// The direct sum of a list of modules. If the summands have basis elements with the same
+    /// name, the basis elements of the ith summand are renamed to `{name}_{i}`.
+    fn sum_fd_module(
+        algebra: Arc<SteenrodAlgebra>,
+        modules: Vec<Arc<FDModule<SteenrodAlgebra>>>,
+    ) -> FDModule<SteenrodAlgebra> {
+        let min_degree = modules.iter().map(|m| m.min_degree()).min().unwrap_or(0);
+        let sum = SumModule::new(algebra, modules, min_degree);
+        let mut result = sum.to_fd_module();
+
+        let mut names = HashSet::new();
+        let unique = (min_degree..=result.max_degree()).all(|t| {
+            (0..result.dimension(t)).all(|idx| names.insert(result.basis_element_to_string(t, idx)))
+        });
+        if !unique {
+            for t in min_degree..=result.max_degree() {
+                for idx in 0..result.dimension(t) {
+                    let name = format!(
+                        "{}_{}",
+                        result.basis_element_to_string(t, idx),
+                        sum.get_module_num(t, idx)
+                    );
+                    result.set_basis_element_name(t, idx, name);
+                }
             }
-            x => Err(UnknownModuleTypeError {
-                module_type: x.map(str::to_string),
-            }.into()),
         }
+        result
+    }
+
+    /// Whether the module is bounded, i.e. whether `BoundedModule::max_degree` can be called.
+    /// This only checks the obvious bound, so that a finitely presented module is always
+    /// considered unbounded. Use `fp_to_fd_module` to convert finite finitely presented modules.
+    pub fn is_bounded(&self) -> bool {
+        match self {
+            Self::FDModule(_) => true,
+            Self::FPModule(_) => false,
+            Self::RealProjectiveSpace(m) => m.max_degree().is_some(),
+            Self::ProjectiveSpace(m) => m.max_degree().is_some(),
+            Self::LensSpace(m) => m.max_degree().is_some(),
+        }
+    }
+
+    /// Converts a bounded module into an `FDModule`. Finitely presented modules of finite total
+    /// dimension are converted as in `fp_to_fd_module`. Returns `None` if the module is not
+    /// bounded.
+    pub fn into_bounded_fd_module(self) -> Option<FDModule<SteenrodAlgebra>> {
+        match self.fp_to_fd_module() {
+            Self::FDModule(m) => Some(m),
+            m if m.is_bounded() => Some(m.to_fd_module()),
+            _ => None,
+        }
+    }
+
+    /// Returns the top degree of a finitely presented module if it has finite total dimension,
+    /// and `None` otherwise. The zero module has top degree `min_degree`.
+    ///
+    /// If the algebra is finite dimensional, the module vanishes above the top degree of the
+    /// algebra plus the top degree of the generators. Otherwise, the algebra has indecomposables
+    /// in arbitrarily high degrees, and these act non-trivially on any minimal generator because
+    /// there are only finitely many relations. So the module is finite dimensional only if it is
+    /// zero, which is the case if and only if it vanishes in the degrees of the generators. For
+    /// example, `A-mod-Sq1-Sq2` is infinite dimensional over the whole Steenrod algebra.
+    fn fp_module_top_degree(module: &FPModule<SteenrodAlgebra>) -> Option<i32> {
+        let min_degree = module.min_degree();
+        let max_gen_degree = module.generators.get_max_generator_degree();
+        let algebra_top_degree = match &*module.algebra() {
+            SteenrodAlgebra::MilnorAlgebra(a) => a.top_degree(),
+            _ => None,
+        };
+        let max_degree = max_gen_degree + algebra_top_degree.unwrap_or(0);
+        module.compute_basis(max_degree);
+
+        match (min_degree..=max_degree).rev().find(|&t| module.dimension(t) > 0) {
+            None => Some(min_degree),
+            Some(t) if algebra_top_degree.is_some() => Some(t),
+            Some(_) => None,
+        }
+    }
+
+    /// Converts a finitely presented module of finite total dimension into an `FDModule`. The
+    /// basis elements are named after the corresponding elements of the free module on the
+    /// generators, such as `Sq2 x0`. Other modules are returned unchanged.
+    pub fn fp_to_fd_module(self) -> Self {
+        match self {
+            Self::FPModule(m) => match Self::fp_module_top_degree(&m) {
+                Some(top_degree) => {
+                    let name = m.name();
+                    let mut result = TruncatedModule::new(Arc::new(m), top_degree).to_fd_module();
+                    result.name = name;
+                    Self::FDModule(result)
+                }
+                None => Self::FPModule(m),
+            },
+            m => m,
+        }
+    }
+
+    /// Decides whether two bounded modules are isomorphic. See `FDModule::canonical_form`.
+    pub fn is_isomorphic(&self, other: &Self) -> error::Result<bool> {
+        if !self.is_bounded() || !other.is_bounded() {
+            return Err(GenericError::new(
+                "Isomorphisms can only be detected between bounded modules".to_string(),
+            )
+            .into());
+        }
+        self.to_fd_module().is_isomorphic(&other.to_fd_module())
     }
 
     pub fn to_json(&self, json: &mut Value) {
@@ -217,6 +480,8 @@ impl FiniteModule {
             Self::FDModule(m) => m.to_json(json),
             Self::FPModule(m) => m.to_json(json),
             Self::RealProjectiveSpace(m) => m.to_json(json),
+            Self::ProjectiveSpace(m) => m.to_json(json),
+            Self::LensSpace(m) => m.to_json(json),
         }
     }
 
@@ -225,6 +490,8 @@ impl FiniteModule {
             Self::FDModule(_) => "finite dimensional module",
             Self::FPModule(_) => "finitely presented module",
             Self::RealProjectiveSpace(_) => "real projective space",
+            Self::ProjectiveSpace(m) => m.kind.module_type(),
+            Self::LensSpace(_) => "lens space",
         }
     }
 
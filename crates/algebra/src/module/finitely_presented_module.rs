@@ -24,9 +24,26 @@ pub struct FinitelyPresentedModule<A: Algebra> {
     index_table: OnceVec<FPMIndexTable>,
 }
 
+/// Two finitely presented modules are equal if they have the same generators and the same
+/// relations. Isomorphic modules with different presentations are not equal.
 impl<A: Algebra> PartialEq for FinitelyPresentedModule<A> {
-    fn eq(&self, _other: &Self) -> bool {
-        todo!()
+    fn eq(&self, other: &Self) -> bool {
+        let max_gen_degree = self.generators.get_max_generator_degree();
+        let max_relation_degree = self.relations.get_max_generator_degree();
+        if self.min_degree != other.min_degree
+            || max_gen_degree != other.generators.get_max_generator_degree()
+            || max_relation_degree != other.relations.get_max_generator_degree()
+        {
+            return false;
+        }
+        (self.min_degree..=max_gen_degree).all(|t| {
+            self.generators.number_of_gens_in_degree(t)
+                == other.generators.number_of_gens_in_degree(t)
+        }) && (self.min_degree..=max_relation_degree).all(|t| {
+            let num_relations = self.relations.number_of_gens_in_degree(t);
+            num_relations == other.relations.number_of_gens_in_degree(t)
+                && (0..num_relations).all(|i| self.map.output(t, i) == other.map.output(t, i))
+        })
     }
 }
 
@@ -159,7 +176,7 @@ impl<A: Algebra> FinitelyPresentedModule<A> {
                 op_gen.operation_degree + op_gen.generator_degree
             })
             .max()
-            .unwrap();
+            .unwrap_or(min_degree - 1);
         let mut relations_by_degree = BiVec::with_capacity(min_degree, max_relation_degree + 1);
         for _ in min_degree..=max_relation_degree {
             relations_by_degree.push(Vec::new());
@@ -3,9 +3,9 @@ use std::sync::Arc;
 
 use crate::algebra::Algebra;
 use crate::module::homomorphism::{IdentityHomomorphism, ModuleHomomorphism, ZeroHomomorphism};
-use crate::module::{BoundedModule, Module};
+use crate::module::{BoundedModule, FDModule, Module};
 use bivec::BiVec;
-use fp::matrix::{Matrix, QuasiInverse, Subspace};
+use fp::matrix::{AugmentedMatrix2, Matrix, QuasiInverse, Subspace};
 use fp::vector::{FpVector, FpVectorT};
 use once::OnceBiVec;
 
@@ -199,6 +199,297 @@ where
             kernels: self.kernels,
         }
     }
+
+    /// Returns the image of the source basis elements of degree `degree`, as the rows of a
+    /// matrix.
+    fn outputs(&self, degree: i32) -> Matrix {
+        let p = self.prime();
+        let output_degree = degree - self.degree_shift;
+        let target_dimension = if output_degree < self.target.min_degree() {
+            0
+        } else {
+            self.target.dimension(output_degree)
+        };
+        let mut matrix = Matrix::new(p, self.source.dimension(degree), target_dimension);
+        if target_dimension > 0 {
+            for (i, row) in matrix.iter_mut().enumerate() {
+                self.apply_to_basis_element(row, 1, degree, i);
+            }
+        }
+        matrix
+    }
+
+    /// Returns the image of the homomorphism in degree `degree` of the target, as a subspace of
+    /// the target.
+    fn image_subspace(&self, degree: i32) -> Subspace {
+        let p = self.prime();
+        let dimension = self.target.dimension(degree);
+        let mut result = Subspace::new(p, dimension + 1, dimension);
+        let source_degree = degree + self.degree_shift;
+        if source_degree >= self.source.min_degree() {
+            result.add_vectors(self.outputs(source_degree).iter().cloned());
+        }
+        result
+    }
+
+    /// Returns the kernel of the homomorphism as an `FDModule`, together with its inclusion into
+    /// the source. The kernel is the source of the returned homomorphism, and its basis elements
+    /// are named after the corresponding elements of the source.
+    pub fn kernel_inclusion(&self) -> BoundedModuleHomomorphism<FDModule<A>, S> {
+        let p = self.prime();
+        let min_degree = self.source.min_degree();
+        let max_degree = self.source.max_degree();
+        self.source.compute_basis(max_degree);
+        self.target.compute_basis(max_degree - self.degree_shift);
+
+        let mut kernels = BiVec::with_capacity(min_degree, max_degree + 1);
+        for t in min_degree..=max_degree {
+            let outputs = self.outputs(t);
+            let source_dimension = outputs.rows();
+            let target_dimension = outputs.columns();
+
+            let mut matrix =
+                AugmentedMatrix2::new(p, source_dimension, &[target_dimension, source_dimension]);
+            for (i, row) in outputs.iter().enumerate() {
+                for (j, c) in row.iter().enumerate() {
+                    matrix[i].set_entry(j, c);
+                }
+            }
+            matrix.segment(1, 1).add_identity(source_dimension, 0, 0);
+            matrix.initialize_pivots();
+            matrix.row_reduce();
+
+            let mut kernel = Subspace::new(p, source_dimension + 1, source_dimension);
+            kernel.add_vectors(matrix.compute_kernel().basis().iter().cloned());
+            kernels.push(kernel);
+        }
+
+        let kernel = Arc::new(fd_module_from_subspaces(
+            &*self.source,
+            format!("ker({})", self.source.name()),
+            &kernels,
+        ));
+        BoundedModuleHomomorphism::inclusion_of_subspaces(kernel, Arc::clone(&self.source), &kernels)
+    }
+
+    /// Factors the homomorphism as a surjection onto its image followed by the inclusion of the
+    /// image into the target. The image is an `FDModule` whose basis elements are named after the
+    /// corresponding elements of the target.
+    pub fn image_factorization(
+        &self,
+    ) -> (
+        BoundedModuleHomomorphism<S, FDModule<A>>,
+        BoundedModuleHomomorphism<FDModule<A>, T>,
+    ) {
+        let p = self.prime();
+        let min_degree = std::cmp::max(
+            self.source.min_degree() - self.degree_shift,
+            self.target.min_degree(),
+        );
+        let max_degree = std::cmp::max(min_degree, self.source.max_degree() - self.degree_shift);
+        self.source.compute_basis(max_degree + self.degree_shift);
+        self.target.compute_basis(max_degree);
+
+        let mut images: Vec<Subspace> = (min_degree..=max_degree)
+            .map(|t| self.image_subspace(t))
+            .collect();
+        // Drop the degrees above the top of the image.
+        while images.len() > 1 && images.last().unwrap().dimension() == 0 {
+            images.pop();
+        }
+        let images = BiVec::from_vec(min_degree, images);
+        let max_degree = images.max_degree();
+
+        let image = Arc::new(fd_module_from_subspaces(
+            &*self.target,
+            format!("im({})", self.source.name()),
+            &images,
+        ));
+
+        let mut matrices = BiVec::with_capacity(min_degree, max_degree + 1);
+        for (t, image_subspace) in images.iter_enum() {
+            let source_degree = t + self.degree_shift;
+            let rows = self
+                .outputs(source_degree)
+                .iter()
+                .map(|v| subspace_coordinates(image_subspace, v))
+                .collect();
+            matrices.push(Matrix::from_rows(p, rows, image_subspace.dimension()));
+        }
+        let projection = BoundedModuleHomomorphism::from_matrices(
+            Arc::clone(&self.source),
+            Arc::clone(&image),
+            self.degree_shift,
+            matrices,
+        );
+        let inclusion = BoundedModuleHomomorphism::inclusion_of_subspaces(
+            image,
+            Arc::clone(&self.target),
+            &images,
+        );
+        (projection, inclusion)
+    }
+}
+
+impl<A, S, T> BoundedModuleHomomorphism<S, T>
+where
+    A: Algebra,
+    S: BoundedModule<Algebra = A>,
+    T: BoundedModule<Algebra = A>,
+{
+    /// Returns the cokernel of the homomorphism as an `FDModule`, together with the projection
+    /// from the target. The cokernel is the target of the returned homomorphism, and its basis
+    /// consists of the basis elements of the target that are not pivots of the image.
+    pub fn cokernel_projection(&self) -> BoundedModuleHomomorphism<T, FDModule<A>> {
+        let p = self.prime();
+        let min_degree = self.target.min_degree();
+        let max_degree = self.target.max_degree();
+        self.source.compute_basis(max_degree + self.degree_shift);
+        self.target.compute_basis(max_degree);
+
+        let mut images = BiVec::with_capacity(min_degree, max_degree + 1);
+        let mut graded_dimension = BiVec::with_capacity(min_degree, max_degree + 1);
+        for t in min_degree..=max_degree {
+            let image = self.image_subspace(t);
+            graded_dimension.push(self.target.dimension(t) - image.dimension());
+            images.push(image);
+        }
+
+        let mut cokernel = FDModule::new(
+            self.target.algebra(),
+            format!("coker({})", self.source.name()),
+            graded_dimension,
+        );
+        let mut matrices = BiVec::with_capacity(min_degree, max_degree + 1);
+        for (t, image) in images.iter_enum() {
+            let dimension = self.target.dimension(t);
+            let rows = (0..dimension)
+                .map(|i| {
+                    let mut v = FpVector::new(p, dimension);
+                    v.set_entry(i, 1);
+                    quotient_coordinates(image, v)
+                })
+                .collect();
+            matrices.push(Matrix::from_rows(p, rows, cokernel.dimension(t)));
+
+            let complement = (0..dimension).filter(|&i| image.pivots()[i] < 0);
+            for (idx, i) in complement.enumerate() {
+                cokernel.set_basis_element_name(t, idx, self.target.basis_element_to_string(t, i));
+            }
+        }
+
+        let algebra = self.target.algebra();
+        for (input_degree, image) in images.iter_enum() {
+            let complement: Vec<usize> = (0..self.target.dimension(input_degree))
+                .filter(|&i| image.pivots()[i] < 0)
+                .collect();
+            for output_degree in input_degree + 1..=max_degree {
+                let output_dimension = self.target.dimension(output_degree);
+                if cokernel.dimension(output_degree) == 0 {
+                    continue;
+                }
+                let op_degree = output_degree - input_degree;
+                for (input_idx, &i) in complement.iter().enumerate() {
+                    for op_idx in 0..algebra.dimension(op_degree, -1) {
+                        let mut output = FpVector::new(p, output_dimension);
+                        self.target
+                            .act_on_basis(&mut output, 1, op_degree, op_idx, input_degree, i);
+                        cokernel
+                            .action_mut(op_degree, op_idx, input_degree, input_idx)
+                            .assign(&quotient_coordinates(&images[output_degree], output));
+                    }
+                }
+            }
+        }
+
+        BoundedModuleHomomorphism::from_matrices(
+            Arc::clone(&self.target),
+            Arc::new(cokernel),
+            0,
+            matrices,
+        )
+    }
+}
+
+impl<A: Algebra, T: Module<Algebra = A>> BoundedModuleHomomorphism<FDModule<A>, T> {
+    /// The inclusion of `source` into `target`, where the basis of `source` in degree `t` is the
+    /// basis of `subspaces[t]`.
+    fn inclusion_of_subspaces(
+        source: Arc<FDModule<A>>,
+        target: Arc<T>,
+        subspaces: &BiVec<Subspace>,
+    ) -> Self {
+        let p = source.prime();
+        let mut matrices = BiVec::with_capacity(subspaces.min_degree(), subspaces.len());
+        for subspace in subspaces.iter() {
+            matrices.push(Matrix::from_rows(p, subspace.basis().to_vec(), subspace.columns()));
+        }
+        Self::from_matrices(source, target, 0, matrices)
+    }
+}
+
+/// The coordinates of an element of `subspace` in the basis `subspace.basis()`.
+fn subspace_coordinates(subspace: &Subspace, v: &FpVector) -> FpVector {
+    let mut result = FpVector::new(subspace.prime(), subspace.dimension());
+    for (i, &row) in subspace.pivots().iter().enumerate() {
+        if row >= 0 {
+            result.set_entry(row as usize, v.entry(i));
+        }
+    }
+    result
+}
+
+/// The coordinates of the image of `v` in the quotient by `subspace`, whose basis is given by the
+/// non-pivot columns of `subspace`.
+fn quotient_coordinates(subspace: &Subspace, mut v: FpVector) -> FpVector {
+    subspace.reduce(&mut v);
+    let mut result = FpVector::new(subspace.prime(), v.dimension() - subspace.dimension());
+    let complement = (0..v.dimension()).filter(|&i| subspace.pivots()[i] < 0);
+    for (idx, i) in complement.enumerate() {
+        result.set_entry(idx, v.entry(i));
+    }
+    result
+}
+
+/// The submodule of `module` whose basis in degree `t` is the basis of `subspaces[t]`. The basis
+/// elements are named after the corresponding elements of `module`.
+fn fd_module_from_subspaces<M: Module>(
+    module: &M,
+    name: String,
+    subspaces: &BiVec<Subspace>,
+) -> FDModule<M::Algebra> {
+    let p = module.prime();
+    let algebra = module.algebra();
+    let max_degree = subspaces.max_degree();
+    let graded_dimension = BiVec::from_vec(
+        subspaces.min_degree(),
+        subspaces.iter().map(Subspace::dimension).collect(),
+    );
+    let mut result = FDModule::new(Arc::clone(&algebra), name, graded_dimension);
+
+    for (input_degree, subspace) in subspaces.iter_enum() {
+        for (input_idx, input) in subspace.basis().iter().enumerate() {
+            result.set_basis_element_name(
+                input_degree,
+                input_idx,
+                module.element_to_string(input_degree, input),
+            );
+            for output_degree in input_degree + 1..=max_degree {
+                if subspaces[output_degree].dimension() == 0 {
+                    continue;
+                }
+                let op_degree = output_degree - input_degree;
+                for op_idx in 0..algebra.dimension(op_degree, -1) {
+                    let mut output = FpVector::new(p, module.dimension(output_degree));
+                    module.act(&mut output, 1, op_degree, op_idx, input_degree, input);
+                    result
+                        .action_mut(op_degree, op_idx, input_degree, input_idx)
+                        .assign(&subspace_coordinates(&subspaces[output_degree], &output));
+                }
+            }
+        }
+    }
+    result
 }
 
 impl<S: BoundedModule, T: Module<Algebra = S::Algebra>> ZeroHomomorphism<S, T>
@@ -237,3 +528,117 @@ impl<S: BoundedModule> IdentityHomomorphism<S> for BoundedModuleHomomorphism<S,
         Self::from_matrices(Arc::clone(&source), source, 0, matrices)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algebra::{AdemAlgebra, SteenrodAlgebra};
+    use fp::prime::ValidPrime;
+    use serde_json::json;
+
+    fn graded_dimension<M: BoundedModule>(module: &M) -> Vec<usize> {
+        (module.min_degree()..=module.max_degree())
+            .map(|t| module.dimension(t))
+            .collect()
+    }
+
+    fn check_validity(module: &FDModule<SteenrodAlgebra>) {
+        for input_degree in module.min_degree()..=module.max_degree() {
+            for output_degree in input_degree + 1..=module.max_degree() {
+                module.check_validity(input_degree, output_degree).unwrap();
+            }
+        }
+    }
+
+    /// Checks that `g` composed with `f` agrees with `h` on the basis of the source of `f`.
+    fn check_composite<M1, M2, M3, M4>(
+        f: &BoundedModuleHomomorphism<M1, M2>,
+        g: &BoundedModuleHomomorphism<M2, M3>,
+        h: &BoundedModuleHomomorphism<M1, M4>,
+    ) where
+        M1: BoundedModule<Algebra = SteenrodAlgebra>,
+        M2: BoundedModule<Algebra = SteenrodAlgebra>,
+        M3: Module<Algebra = SteenrodAlgebra>,
+        M4: Module<Algebra = SteenrodAlgebra>,
+    {
+        let p = f.prime();
+        let source = f.source();
+        for t in source.min_degree()..=source.max_degree() {
+            let middle_degree = t - f.degree_shift();
+            let output_degree = middle_degree - g.degree_shift();
+            if middle_degree < f.target().min_degree() || output_degree < g.target().min_degree() {
+                continue;
+            }
+            for i in 0..source.dimension(t) {
+                let mut middle = FpVector::new(p, f.target().dimension(middle_degree));
+                f.apply_to_basis_element(&mut middle, 1, t, i);
+                let mut result = FpVector::new(p, g.target().dimension(output_degree));
+                g.apply(&mut result, 1, middle_degree, &middle);
+                let mut expected = FpVector::new(p, h.target().dimension(output_degree));
+                h.apply_to_basis_element(&mut expected, 1, t, i);
+                assert_eq!(result, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernel_image_cokernel() {
+        let p = ValidPrime::new(2);
+        let algebra = Arc::new(SteenrodAlgebra::from(AdemAlgebra::new(p, false, false)));
+        algebra.compute_basis(10);
+        let joker = Arc::new(
+            FDModule::from_json(
+                Arc::clone(&algebra),
+                &mut json!({
+                    "p": 2,
+                    "gens": {"x0": 0, "x1": 1, "x2": 2, "x3": 3, "x4": 4},
+                    "actions": ["Sq1 x0 = x1", "Sq2 x0 = x2", "Sq2 x1 = x3", "Sq2 x2 = x4", "Sq1 x3 = x4"]
+                }),
+            )
+            .unwrap(),
+        );
+        let point = Arc::new(
+            FDModule::from_json(Arc::clone(&algebra), &mut json!({"p": 2, "gens": {"y0": 0}, "actions": []}))
+                .unwrap(),
+        );
+
+        // The projection of the Joker onto its bottom cell.
+        let mut matrices = BiVec::new(0);
+        matrices.push(Matrix::from_vec(p, &[vec![1]]));
+        let f = BoundedModuleHomomorphism::from_matrices(Arc::clone(&joker), Arc::clone(&point), 0, matrices);
+
+        let kernel = f.kernel_inclusion();
+        assert_eq!(graded_dimension(&*kernel.source()), vec![0, 1, 1, 1, 1]);
+        assert_eq!(kernel.source().basis_element_to_string(2, 0), "x2");
+        check_validity(&kernel.source());
+        let zero = BoundedModuleHomomorphism::zero_homomorphism(kernel.source(), Arc::clone(&point), 0);
+        check_composite(&kernel, &f, &zero);
+
+        let (projection, inclusion) = f.image_factorization();
+        assert_eq!(graded_dimension(&*projection.target()), vec![1]);
+        check_composite(&projection, &inclusion, &f);
+
+        let cokernel = f.cokernel_projection();
+        assert_eq!(graded_dimension(&*cokernel.target()), vec![0]);
+
+        // The inclusion of the top cell of the Joker.
+        let mut matrices = BiVec::new(4);
+        matrices.push(Matrix::from_vec(p, &[vec![1]]));
+        let g = BoundedModuleHomomorphism::from_matrices(Arc::clone(&point), Arc::clone(&joker), -4, matrices);
+
+        let kernel = g.kernel_inclusion();
+        assert_eq!(graded_dimension(&*kernel.source()), vec![0]);
+
+        let (projection, inclusion) = g.image_factorization();
+        assert_eq!(projection.target().min_degree(), 4);
+        assert_eq!(graded_dimension(&*projection.target()), vec![1]);
+        assert_eq!(projection.target().basis_element_to_string(4, 0), "x4");
+        check_composite(&projection, &inclusion, &g);
+
+        let cokernel = g.cokernel_projection();
+        assert_eq!(graded_dimension(&*cokernel.target()), vec![1, 1, 1, 1, 0]);
+        check_validity(&cokernel.target());
+        let zero = BoundedModuleHomomorphism::zero_homomorphism(Arc::clone(&point), cokernel.target(), -4);
+        check_composite(&g, &cokernel, &zero);
+    }
+}
@@ -22,6 +22,20 @@ impl BoundedModule for FiniteModule {
                     panic!("Real Projective Space is not bounded")
                 }
             }
+            FiniteModule::ProjectiveSpace(m) => {
+                if let Some(x) = m.max_degree() {
+                    x
+                } else {
+                    panic!("Projective Space is not bounded")
+                }
+            }
+            FiniteModule::LensSpace(m) => {
+                if let Some(x) = m.max_degree() {
+                    x
+                } else {
+                    panic!("Lens Space is not bounded")
+                }
+            }
         }
     }
 }
@@ -35,6 +49,12 @@ impl FPModuleT for FiniteModule {
             FiniteModule::RealProjectiveSpace(_) => {
                 panic!("RealProjectiveSpace is not finitely presented")
             }
+            FiniteModule::ProjectiveSpace(_) => {
+                panic!("ProjectiveSpace is not finitely presented")
+            }
+            FiniteModule::LensSpace(_) => {
+                panic!("LensSpace is not finitely presented")
+            }
             FiniteModule::FPModule(m) => m.fp_idx_to_gen_idx(degree, index),
         }
     }
@@ -46,6 +66,12 @@ impl FPModuleT for FiniteModule {
             FiniteModule::RealProjectiveSpace(_) => {
                 panic!("RealProjectiveSpace is not finitely presented")
             }
+            FiniteModule::ProjectiveSpace(_) => {
+                panic!("ProjectiveSpace is not finitely presented")
+            }
+            FiniteModule::LensSpace(_) => {
+                panic!("LensSpace is not finitely presented")
+            }
             FiniteModule::FPModule(m) => m.gen_idx_to_fp_idx(degree, index),
         }
     }
@@ -58,6 +84,12 @@ impl FPModuleT for FiniteModule {
             FiniteModule::RealProjectiveSpace(_) => {
                 panic!("RealProjectiveSpace is not finitely presented")
             }
+            FiniteModule::ProjectiveSpace(_) => {
+                panic!("ProjectiveSpace is not finitely presented")
+            }
+            FiniteModule::LensSpace(_) => {
+                panic!("LensSpace is not finitely presented")
+            }
             FiniteModule::FPModule(m) => &m.generators,
         }
     }
@@ -167,7 +199,9 @@ impl<M: SteenrodModule> ZeroHomomorphism<FiniteModule, M> for FiniteModuleHomomo
                 Arc::clone(&target),
                 degree_shift,
             )),
-            FiniteModule::RealProjectiveSpace(_) => {
+            FiniteModule::RealProjectiveSpace(_)
+            | FiniteModule::ProjectiveSpace(_)
+            | FiniteModule::LensSpace(_) => {
                 FMHI::RP(GenericZeroHomomorphism::zero_homomorphism(
                     Arc::clone(&source),
                     Arc::clone(&target),
@@ -197,6 +231,12 @@ impl IdentityHomomorphism<FiniteModule> for FiniteModuleHomomorphism<FiniteModul
             FiniteModule::RealProjectiveSpace(_) => {
                 panic!("Identity morphism not supported for RealProjectiveSpace")
             }
+            FiniteModule::ProjectiveSpace(_) => {
+                panic!("Identity morphism not supported for ProjectiveSpace")
+            }
+            FiniteModule::LensSpace(_) => {
+                panic!("Identity morphism not supported for LensSpace")
+            }
             FiniteModule::FPModule(_) => FMHI::FP(FPModuleHomomorphism::identity_homomorphism(
                 Arc::clone(&source),
             )),
@@ -0,0 +1,220 @@
+use crate::algebra::{
+    adem_algebra::AdemBasisElement, milnor_algebra::MilnorBasisElement, AdemAlgebra, Algebra,
+    MilnorAlgebra, SteenrodAlgebraBorrow, SteenrodAlgebraT,
+};
+use crate::module::projective_space::{milnor_p_part_coefficient, signed_binomial};
+use crate::module::{Module, ZeroModule};
+use fp::vector::{FpVector, FpVectorT};
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// This is the stunted lens space $L_{\mathrm{min}}^{\mathrm{max}}$ at an odd prime $p$, whose
+/// cells are in degrees $\mathrm{min}$ through $\mathrm{max}$. The cohomology of $L^\infty_0 =
+/// B\mathbb{Z}/p$ is $E[x] \otimes \mathbb{F}_p[y]$ with $|x| = 1$ and $y = \beta x$, and the
+/// stunted lens spaces are the subquotients of $E[x] \otimes \mathbb{F}_p[y^\pm]$ spanned by the
+/// elements in the given range of degrees. If `min` is negative, these are Thom spectra of
+/// multiples of the tautological bundle.
+///
+/// The action is determined by $\beta x = y$ and $P(y) = y + y^p$, so that $P^i y^k =
+/// \binom{k}{i} y^{k + i(p - 1)}$ and $P^i (x y^k) = \binom{k}{i} x y^{k + i(p - 1)}$.
+pub struct LensSpace<A: SteenrodAlgebraT> {
+    algebra: Arc<A>,
+    pub min: i32,
+    pub max: Option<i32>, // If None, then the infinite lens space
+}
+
+impl<A: SteenrodAlgebraT> PartialEq for LensSpace<A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.min == other.min && self.max == other.max
+    }
+}
+
+impl<A: SteenrodAlgebraT> Eq for LensSpace<A> {}
+
+impl<A: SteenrodAlgebraT> Module for LensSpace<A> {
+    type Algebra = A;
+
+    fn name(&self) -> String {
+        if let Some(max) = self.max {
+            format!("L^{}_{}", max, self.min)
+        } else {
+            format!("L_{}", self.min)
+        }
+    }
+
+    fn algebra(&self) -> Arc<A> {
+        Arc::clone(&self.algebra)
+    }
+
+    fn min_degree(&self) -> i32 {
+        self.min
+    }
+
+    fn max_computed_degree(&self) -> i32 {
+        i32::MAX
+    }
+
+    fn dimension(&self, degree: i32) -> usize {
+        if degree < self.min {
+            return 0;
+        }
+        if let Some(max) = self.max {
+            if degree > max {
+                return 0;
+            }
+        }
+        1
+    }
+
+    fn basis_element_to_string(&self, degree: i32, _idx: usize) -> String {
+        // It is an error to call the function if self.dimension(degree) == 0
+        let k = degree.div_euclid(2);
+        if degree.rem_euclid(2) == 0 {
+            format!("y^{{{}}}", k)
+        } else {
+            format!("x y^{{{}}}", k)
+        }
+    }
+
+    fn act_on_basis(
+        &self,
+        result: &mut FpVector,
+        coeff: u32,
+        op_degree: i32,
+        op_index: usize,
+        mod_degree: i32,
+        mod_index: usize,
+    ) {
+        assert!(op_index < self.algebra().dimension(op_degree, mod_degree));
+        assert!(mod_index < self.dimension(mod_degree));
+
+        let output_degree = mod_degree + op_degree;
+
+        if op_degree == 0 {
+            result.add_basis_element(0, coeff);
+            return;
+        }
+        if coeff == 0 || self.dimension(output_degree) == 0 {
+            return;
+        }
+
+        let c = match self.algebra.to_steenrod_algebra() {
+            SteenrodAlgebraBorrow::BorrowAdem(a) => coef_adem(a, op_degree, op_index, mod_degree),
+            SteenrodAlgebraBorrow::BorrowMilnor(a) => {
+                coef_milnor(a, op_degree, op_index, mod_degree)
+            }
+        };
+        result.add_basis_element(0, c * coeff % *self.prime());
+    }
+}
+
+/// Computes the coefficient of the operation on the basis element in degree `mod_degree`. Since
+/// the module is one dimensional in each degree, the basis element $x^\epsilon y^k$ is determined
+/// by the degree $\epsilon + 2k$.
+fn coef_adem(algebra: &AdemAlgebra, op_deg: i32, op_idx: usize, mod_degree: i32) -> u32 {
+    let elt: &AdemBasisElement = algebra.basis_element_from_index(op_deg, op_idx);
+    let p = algebra.prime();
+    let q = *p as i32 - 1;
+
+    let mut has_x = mod_degree.rem_euclid(2) == 1;
+    let mut k = mod_degree.div_euclid(2);
+
+    // The Bockstein sends $x y^k$ to $y^{k + 1}$ and $y^k$ to zero. Bit i of `bocksteins` is the
+    // Bockstein to the left of the ith P, and the last bit is the rightmost Bockstein.
+    let has_bockstein = |i: usize| (elt.bocksteins >> i) & 1 == 1;
+    if has_bockstein(elt.ps.len()) {
+        if !has_x {
+            return 0;
+        }
+        has_x = false;
+        k += 1;
+    }
+
+    let mut result = 1;
+    for (i, &pi) in elt.ps.iter().enumerate().rev() {
+        result = result * signed_binomial(p, k, pi as i32) % *p;
+        if result == 0 {
+            return 0;
+        }
+        k += pi as i32 * q;
+
+        if has_bockstein(i) {
+            if !has_x {
+                return 0;
+            }
+            has_x = false;
+            k += 1;
+        }
+    }
+    result
+}
+
+/// See `coef_adem`. The class $x$ is primitive in the Milnor basis except for $Q_i x = y^{p^i}$,
+/// so $Q(E) P(R)$ acts trivially unless $E$ has at most one element, and $Q_i$ acts by zero on
+/// $y^k$.
+fn coef_milnor(algebra: &MilnorAlgebra, op_deg: i32, op_idx: usize, mod_degree: i32) -> u32 {
+    let elt: &MilnorBasisElement = algebra.basis_element_from_index(op_deg, op_idx);
+    if elt.q_part.count_ones() > 1 || (elt.q_part != 0 && mod_degree.rem_euclid(2) == 0) {
+        return 0;
+    }
+    milnor_p_part_coefficient(algebra.prime(), &elt.p_part, mod_degree.div_euclid(2))
+}
+
+impl<A: SteenrodAlgebraT> ZeroModule for LensSpace<A> {
+    fn zero_module(algebra: Arc<A>, min_degree: i32) -> Self {
+        Self::new(algebra, min_degree, Some(min_degree - 1))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct LensSpaceSpec {
+    min: i32,
+    max: Option<i32>,
+}
+
+impl<A: SteenrodAlgebraT> LensSpace<A> {
+    pub fn new(algebra: Arc<A>, min: i32, max: Option<i32>) -> Self {
+        assert_ne!(*algebra.prime(), 2);
+        if let Some(max) = max {
+            assert!(max >= min - 1);
+        }
+        Self { algebra, min, max }
+    }
+
+    pub fn max_degree(&self) -> Option<i32> {
+        self.max
+    }
+
+    pub fn from_json(algebra: Arc<A>, json: &mut Value) -> error::Result<Self> {
+        let spec: LensSpaceSpec = serde_json::from_value(json.clone())?;
+        if *algebra.prime() == 2 {
+            return Err(error::GenericError::new(
+                "Lens spaces are only defined at odd primes. Use a real projective space instead"
+                    .to_string(),
+            )
+            .into());
+        }
+        if let Some(max) = spec.max {
+            if max < spec.min {
+                return Err(error::GenericError::new(format!(
+                    "The top cell {} of a lens space is below the bottom cell {}",
+                    max, spec.min
+                ))
+                .into());
+            }
+        }
+        Ok(Self::new(algebra, spec.min, spec.max))
+    }
+
+    pub fn to_json(&self, json: &mut Value) {
+        json["name"] = Value::String(self.name());
+        json["type"] = Value::from("lens space");
+        json["min"] = Value::from(self.min);
+        if let Some(max) = self.max {
+            json["max"] = Value::from(max);
+        }
+    }
+}
@@ -1,13 +1,18 @@
+mod families;
 mod finite_dimensional_module;
 mod finite_module;
 mod finitely_presented_module;
 mod free_module;
 mod hom_module;
+mod lens_space;
+mod projective_space;
 mod quotient_module;
 mod rpn;
+mod spec_error;
 mod sum_module;
 mod tensor_module;
 mod truncated_module;
+mod unstable;
 mod zero_module;
 mod bounded_module;
 mod module_trait;
@@ -16,16 +21,21 @@ pub mod block_structure;
 pub mod homomorphism;
 
 
+pub use families::{a_mod_a_n_module, brown_gitler_module};
 pub use finite_dimensional_module::FiniteDimensionalModule as FDModule;
 pub use finite_module::FiniteModule;
 pub use finitely_presented_module::FinitelyPresentedModule as FPModule;
 pub use free_module::{FreeModule, OperationGeneratorPair};
 pub use hom_module::HomModule;
+pub use lens_space::LensSpace;
+pub use projective_space::{ProjectiveSpace, ProjectiveSpaceKind};
 pub use quotient_module::QuotientModule;
 pub use rpn::RealProjectiveSpace;
+pub use spec_error::ModuleSpecError;
 pub use sum_module::SumModule;
 pub use tensor_module::TensorModule;
 pub use truncated_module::TruncatedModule;
+pub use unstable::{CupProducts, UnstableModuleError};
 pub use zero_module::ZeroModule;
 pub use bounded_module::BoundedModule;
 pub use module_trait::{Module, ModuleFailedRelationError};
@@ -0,0 +1,261 @@
+use crate::algebra::{
+    adem_algebra::AdemBasisElement, milnor_algebra::MilnorBasisElement, AdemAlgebra, Algebra,
+    MilnorAlgebra, SteenrodAlgebraBorrow, SteenrodAlgebraT,
+};
+use crate::module::Module;
+use fp::prime::ValidPrime;
+use fp::prime::{binomial, multinomial};
+use fp::vector::{FpVector, FpVectorT};
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Whether a `ProjectiveSpace` is complex or quaternionic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProjectiveSpaceKind {
+    Complex,
+    Quaternionic,
+}
+
+impl ProjectiveSpaceKind {
+    /// The dimension of the cells.
+    pub fn cell_dimension(self) -> i32 {
+        match self {
+            Self::Complex => 2,
+            Self::Quaternionic => 4,
+        }
+    }
+
+    /// The value of the `type` field of the JSON specification.
+    pub fn module_type(self) -> &'static str {
+        match self {
+            Self::Complex => "complex projective space",
+            Self::Quaternionic => "quaternionic projective space",
+        }
+    }
+}
+
+/// This is $\mathbb{CP}_{\mathrm{min}}^{\mathrm{max}}$ or $\mathbb{HP}_{\mathrm{min}}^{\mathrm{max}}$,
+/// whose cells are in degrees $2i$ or $4i$ respectively, for $\mathrm{min} \leq i \leq
+/// \mathrm{max}$. The cohomology of $\mathbb{CP}_{\mathrm{min}}^{\mathrm{max}}$ is the subquotient
+/// of $\mathbb{F}_p[y^\pm]$ given by the elements $y^i$ in this range, and that of
+/// $\mathbb{HP}_{\mathrm{min}}^{\mathrm{max}}$ is spanned by $z^i = y^{2i}$. If `min` is
+/// negative, this is the Thom spectrum of `min` times the tautological line bundle over
+/// $\mathbb{CP}^{\mathrm{max} - \mathrm{min}}$, and similarly for $\mathbb{HP}$.
+///
+/// The action is determined by $P(y) = y + y^p$, or $\mathrm{Sq}(y) = y + y^2$ at the prime 2,
+/// which extends to negative powers of $y$. The Bocksteins act trivially.
+pub struct ProjectiveSpace<A: SteenrodAlgebraT> {
+    algebra: Arc<A>,
+    pub kind: ProjectiveSpaceKind,
+    pub min: i32,
+    pub max: Option<i32>, // If None, then the infinite projective space
+}
+
+impl<A: SteenrodAlgebraT> PartialEq for ProjectiveSpace<A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.min == other.min && self.max == other.max
+    }
+}
+
+impl<A: SteenrodAlgebraT> Eq for ProjectiveSpace<A> {}
+
+impl<A: SteenrodAlgebraT> Module for ProjectiveSpace<A> {
+    type Algebra = A;
+
+    fn name(&self) -> String {
+        let space = match self.kind {
+            ProjectiveSpaceKind::Complex => "CP",
+            ProjectiveSpaceKind::Quaternionic => "HP",
+        };
+        if let Some(max) = self.max {
+            format!("{}^{}_{}", space, max, self.min)
+        } else {
+            format!("{}_{}", space, self.min)
+        }
+    }
+
+    fn algebra(&self) -> Arc<A> {
+        Arc::clone(&self.algebra)
+    }
+
+    fn min_degree(&self) -> i32 {
+        self.min * self.kind.cell_dimension()
+    }
+
+    fn max_computed_degree(&self) -> i32 {
+        i32::MAX
+    }
+
+    fn dimension(&self, degree: i32) -> usize {
+        let cell_dimension = self.kind.cell_dimension();
+        if degree.rem_euclid(cell_dimension) != 0 {
+            return 0;
+        }
+        let cell = degree.div_euclid(cell_dimension);
+        if cell < self.min {
+            return 0;
+        }
+        if let Some(max) = self.max {
+            if cell > max {
+                return 0;
+            }
+        }
+        1
+    }
+
+    fn basis_element_to_string(&self, degree: i32, _idx: usize) -> String {
+        // It is an error to call the function if self.dimension(degree) == 0
+        match self.kind {
+            ProjectiveSpaceKind::Complex => format!("y^{{{}}}", degree / 2),
+            ProjectiveSpaceKind::Quaternionic => format!("z^{{{}}}", degree / 4),
+        }
+    }
+
+    fn act_on_basis(
+        &self,
+        result: &mut FpVector,
+        coeff: u32,
+        op_degree: i32,
+        op_index: usize,
+        mod_degree: i32,
+        mod_index: usize,
+    ) {
+        assert!(op_index < self.algebra().dimension(op_degree, mod_degree));
+        assert!(mod_index < self.dimension(mod_degree));
+
+        let output_degree = mod_degree + op_degree;
+
+        if op_degree == 0 {
+            result.add_basis_element(0, coeff);
+            return;
+        }
+        if coeff == 0 || self.dimension(output_degree) == 0 {
+            return;
+        }
+
+        // We write the basis element as a power of the class $x$ of degree 1 at the prime 2, and
+        // of the class $y$ of degree 2 at odd primes. These are the generators of the cohomology
+        // of $\mathbb{RP}^\infty$ and $\mathbb{CP}^\infty$ respectively.
+        let p = self.prime();
+        let exponent = if *p == 2 { mod_degree } else { mod_degree / 2 };
+        let c = match self.algebra.to_steenrod_algebra() {
+            SteenrodAlgebraBorrow::BorrowAdem(a) => coef_adem(a, op_degree, op_index, exponent),
+            SteenrodAlgebraBorrow::BorrowMilnor(a) => coef_milnor(a, op_degree, op_index, exponent),
+        };
+        result.add_basis_element(0, c * coeff % *p);
+    }
+}
+
+/// The binomial coefficient $\binom{n}{k}$ mod p, where n may be negative.
+pub(super) fn signed_binomial(p: ValidPrime, n: i32, k: i32) -> u32 {
+    if n >= 0 {
+        return binomial(p, n, k);
+    }
+    // $\binom{n}{k} = (-1)^k \binom{k - n - 1}{k}$
+    let c = binomial(p, k - n - 1, k);
+    if k % 2 == 0 {
+        c
+    } else {
+        (*p - c) % *p
+    }
+}
+
+/// Computes the coefficient of the operation on the generator raised to the power `j`, where the
+/// generator is as in `ProjectiveSpace::act_on_basis`.
+fn coef_adem(algebra: &AdemAlgebra, op_deg: i32, op_idx: usize, mut j: i32) -> u32 {
+    let elt: &AdemBasisElement = algebra.basis_element_from_index(op_deg, op_idx);
+    if elt.bocksteins != 0 {
+        return 0;
+    }
+    let p = algebra.prime();
+    let q = if *p == 2 { 1 } else { *p as i32 - 1 };
+
+    // Apply P^i to y^j, which gives $\binom{j}{i} y^{j + i(p - 1)}$.
+    let mut result = 1;
+    for &i in elt.ps.iter().rev() {
+        result = result * signed_binomial(p, j, i as i32) % *p;
+        if result == 0 {
+            return 0;
+        }
+        j += i as i32 * q;
+    }
+    result
+}
+
+/// See `coef_adem`. Since the generator $y$ has coaction $\sum_i y^{p^i} \otimes \xi_i$, the
+/// coefficient of $P(r_1, r_2, \ldots)$ on $y^j$ is $\binom{j}{n} \binom{n}{r_1, r_2, \ldots}$,
+/// where $n = \sum r_i$.
+fn coef_milnor(algebra: &MilnorAlgebra, op_deg: i32, op_idx: usize, j: i32) -> u32 {
+    let elt: &MilnorBasisElement = algebra.basis_element_from_index(op_deg, op_idx);
+    if elt.q_part != 0 {
+        return 0;
+    }
+    milnor_p_part_coefficient(algebra.prime(), &elt.p_part, j)
+}
+
+/// The coefficient of $P(r_1, r_2, \ldots)$ on $y^j$, where $y$ has coaction $\sum_i y^{p^i}
+/// \otimes \xi_i$. See `coef_milnor`.
+pub(super) fn milnor_p_part_coefficient(p: ValidPrime, p_part: &[u32], j: i32) -> u32 {
+    let n: u32 = p_part.iter().sum();
+    let c = signed_binomial(p, j, n as i32);
+    if c == 0 {
+        return 0;
+    }
+    let mut list = p_part.to_vec();
+    c * multinomial(p, &mut list) % *p
+}
+
+#[derive(Deserialize, Debug)]
+struct ProjectiveSpaceSpec {
+    min: i32,
+    max: Option<i32>,
+}
+
+impl<A: SteenrodAlgebraT> ProjectiveSpace<A> {
+    pub fn new(algebra: Arc<A>, kind: ProjectiveSpaceKind, min: i32, max: Option<i32>) -> Self {
+        if let Some(max) = max {
+            assert!(max >= min - 1);
+        }
+        Self {
+            algebra,
+            kind,
+            min,
+            max,
+        }
+    }
+
+    /// The degree of the top cell, or `None` if the projective space is infinite.
+    pub fn max_degree(&self) -> Option<i32> {
+        self.max.map(|max| max * self.kind.cell_dimension())
+    }
+
+    pub fn from_json(
+        algebra: Arc<A>,
+        kind: ProjectiveSpaceKind,
+        json: &mut Value,
+    ) -> error::Result<Self> {
+        let spec: ProjectiveSpaceSpec = serde_json::from_value(json.clone())?;
+        if let Some(max) = spec.max {
+            if max < spec.min {
+                return Err(error::GenericError::new(format!(
+                    "The top cell {} of a projective space is below the bottom cell {}",
+                    max, spec.min
+                ))
+                .into());
+            }
+        }
+        Ok(Self::new(algebra, kind, spec.min, spec.max))
+    }
+
+    pub fn to_json(&self, json: &mut Value) {
+        json["name"] = Value::String(self.name());
+        json["type"] = Value::from(self.kind.module_type());
+        json["min"] = Value::from(self.min);
+        if let Some(max) = self.max {
+            json["max"] = Value::from(max);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Errors produced when loading a module from its JSON specification.
+
+use crate::steenrod_parser::ParseError;
+
+use serde_json::Value;
+
+/// An error in a module specification. Each variant records the JSON path of the offending
+/// field, e.g. `actions[3]` or `self_maps[0].map_data[1][2]`. The path may be empty if the
+/// error did not come from a JSON field, e.g. when `FDModule::parse_action` is called directly.
+#[derive(Debug)]
+pub enum ModuleSpecError {
+    /// The field is missing or does not have the `expected` type.
+    InvalidField {
+        path: String,
+        expected: &'static str,
+    },
+    /// The string `input` failed to parse. `position` is the character offset at which parsing
+    /// failed.
+    Parse {
+        path: String,
+        input: String,
+        position: usize,
+        info: String,
+    },
+    /// The string `input` refers to a generator that does not exist.
+    UnknownGenerator {
+        path: String,
+        input: String,
+        generator: String,
+    },
+    /// The string `input` parses but does not make sense, e.g. because the degrees do not match.
+    Invalid {
+        path: String,
+        input: String,
+        info: String,
+    },
+}
+
+impl ModuleSpecError {
+    /// Sets the JSON path of the error.
+    pub fn at(mut self, new_path: String) -> Self {
+        match &mut self {
+            Self::InvalidField { path, .. }
+            | Self::Parse { path, .. }
+            | Self::UnknownGenerator { path, .. }
+            | Self::Invalid { path, .. } => *path = new_path,
+        }
+        self
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            Self::InvalidField { path, .. }
+            | Self::Parse { path, .. }
+            | Self::UnknownGenerator { path, .. }
+            | Self::Invalid { path, .. } => path,
+        }
+    }
+
+    pub fn from_parse_error(input: &str, err: ParseError) -> Self {
+        Self::Parse {
+            path: String::new(),
+            input: input.to_string(),
+            position: err.position,
+            info: err.info,
+        }
+    }
+
+    /// A parse error at the start of `rest`, which must be a slice of `input`.
+    pub fn parse_error_at(input: &str, rest: &str, info: &str) -> Self {
+        Self::from_parse_error(input, ParseError::new(input, rest, info.to_string()))
+    }
+
+    /// Converts the error of a nom parser run on a slice of `input`.
+    pub fn from_nom(input: &str, err: nom::Err<(&str, nom::error::ErrorKind)>) -> Self {
+        Self::from_parse_error(input, ParseError::from_nom(input, err))
+    }
+
+    pub fn expect_u64(json: &Value, path: &str) -> Result<u64, Self> {
+        json.as_u64()
+            .ok_or_else(|| Self::invalid_field(path, "a non-negative integer"))
+    }
+
+    pub fn expect_i64(json: &Value, path: &str) -> Result<i64, Self> {
+        json.as_i64()
+            .ok_or_else(|| Self::invalid_field(path, "an integer"))
+    }
+
+    pub fn expect_str<'a>(json: &'a Value, path: &str) -> Result<&'a str, Self> {
+        json.as_str()
+            .ok_or_else(|| Self::invalid_field(path, "a string"))
+    }
+
+    pub fn expect_array<'a>(json: &'a Value, path: &str) -> Result<&'a Vec<Value>, Self> {
+        json.as_array()
+            .ok_or_else(|| Self::invalid_field(path, "an array"))
+    }
+
+    fn invalid_field(path: &str, expected: &'static str) -> Self {
+        Self::InvalidField {
+            path: path.to_string(),
+            expected,
+        }
+    }
+}
+
+impl std::fmt::Display for ModuleSpecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self.path();
+        if !path.is_empty() {
+            write!(f, "{}: ", path)?;
+        }
+        match self {
+            Self::InvalidField { expected, .. } => write!(f, "expected {}", expected),
+            Self::Parse {
+                input,
+                position,
+                info,
+                ..
+            } => write!(
+                f,
+                "failed to parse at position {} ({}):\n    {}\n    {:>width$}",
+                position,
+                info,
+                input,
+                "^",
+                width = position + 1
+            ),
+            Self::UnknownGenerator {
+                input, generator, ..
+            } => write!(f, "invalid generator {} in \"{}\"", generator, input),
+            Self::Invalid { input, info, .. } => write!(f, "{} in \"{}\"", info, input),
+        }
+    }
+}
+
+impl std::error::Error for ModuleSpecError {}
@@ -0,0 +1,456 @@
+//! Checks that a finite dimensional module is the cohomology of a space, as far as the Steenrod
+//! operations can tell.
+//!
+//! The cohomology of a space is an *unstable* module, i.e. $\mathrm{Sq}^i x = 0$ for $i > |x|$ at
+//! the prime 2, and $\beta^\epsilon P^i x = 0$ for $2i + \epsilon > |x|$ at odd primes. If the
+//! cup products are known, they must in addition satisfy the Cartan formula.
+
+use crate::algebra::milnor_algebra::MilnorBasisElement;
+use crate::algebra::{Algebra, MilnorAlgebra, SteenrodAlgebraT};
+use crate::margolis::milnor_to_algebra;
+use crate::module::{BoundedModule, FDModule, Module, ModuleSpecError};
+use fp::vector::{FpVector, FpVectorT};
+
+use serde_json::Value;
+
+use std::collections::HashMap;
+
+#[derive(Debug)]
+pub enum UnstableModuleError {
+    /// The operation `operation` acts non-trivially on `generator` despite having excess greater
+    /// than the degree of `generator`.
+    Instability {
+        operation: String,
+        generator: String,
+        value: String,
+    },
+    /// The operation `operation` sends `generator` to `value` instead of its `p`th power
+    /// `expected`, where `operation` is $\mathrm{Sq}^{|x|}$ or $P^{|x|/2}$.
+    Power {
+        operation: String,
+        generator: String,
+        value: String,
+        expected: String,
+    },
+    /// The action of `operation` on `left * right` is `value` instead of `expected`, which is the
+    /// value predicted by the Cartan formula.
+    Cartan {
+        operation: String,
+        left: String,
+        right: String,
+        value: String,
+        expected: String,
+    },
+}
+
+impl std::fmt::Display for UnstableModuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Instability {
+                operation,
+                generator,
+                value,
+            } => write!(
+                f,
+                "Module is not unstable:\n    {} {} = {}  !=  0",
+                operation, generator, value
+            ),
+            Self::Power {
+                operation,
+                generator,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Unstable condition failed:\n    {} {} = {}\nbut the power of {} is {}",
+                operation, generator, value, generator, expected
+            ),
+            Self::Cartan {
+                operation,
+                left,
+                right,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Cartan formula failed:\n    {} ({} * {}) = {}\nbut the Cartan formula gives {}",
+                operation, left, right, value, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UnstableModuleError {}
+
+/// A graded commutative product on a finite dimensional module, which is specified on pairs of
+/// basis elements. If only one of $xy$ and $yx$ is specified, the other is given by
+/// $yx = (-1)^{|x||y|} xy$. The products of pairs that are not specified in either order are
+/// zero. This is typically the cup product on the cohomology of a space.
+#[derive(Clone, Default)]
+pub struct CupProducts {
+    products: HashMap<(i32, usize, i32, usize), FpVector>,
+}
+
+impl CupProducts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_product(&mut self, left: (i32, usize), right: (i32, usize), value: FpVector) {
+        self.products
+            .insert((left.0, left.1, right.0, right.1), value);
+    }
+
+    /// The sign $(-1)^{|x||y|}$ of graded commutativity, as an element of $\mathbb{F}_p$.
+    fn commutativity_sign(p: u32, left_degree: i32, right_degree: i32) -> u32 {
+        if left_degree % 2 != 0 && right_degree % 2 != 0 {
+            p - 1
+        } else {
+            1
+        }
+    }
+
+    /// The product of two basis elements, together with the coefficient it has to be multiplied
+    /// with, if the product is specified in either order.
+    fn product(&self, left: (i32, usize), right: (i32, usize)) -> Option<(&FpVector, u32)> {
+        if let Some(v) = self.products.get(&(left.0, left.1, right.0, right.1)) {
+            return Some((v, 1));
+        }
+        let v = self.products.get(&(right.0, right.1, left.0, left.1))?;
+        Some((v, Self::commutativity_sign(*v.prime(), left.0, right.0)))
+    }
+
+    /// Parses a list of products of the form `x1 * x2 = x3 + 2 x4`. The JSON paths in errors
+    /// are relative to the key `cup_products`.
+    pub fn from_json<A: Algebra>(
+        module: &FDModule<A>,
+        json: &Value,
+    ) -> Result<Self, ModuleSpecError> {
+        let p = module.prime();
+        let entries: Vec<String> =
+            serde_json::from_value(json.clone()).map_err(|_| ModuleSpecError::InvalidField {
+                path: "cup_products".to_string(),
+                expected: "an array of strings",
+            })?;
+        let mut result = Self::new();
+        for (i, entry) in entries.iter().enumerate() {
+            let path = format!("cup_products[{}]", i);
+            let invalid = || ModuleSpecError::Invalid {
+                path: path.clone(),
+                input: entry.clone(),
+                info: "Expected a product of the form x * y = z".to_string(),
+            };
+            let mut sides = entry.split('=');
+            let (lhs, rhs) = match (sides.next(), sides.next(), sides.next()) {
+                (Some(lhs), Some(rhs), None) => (lhs, rhs),
+                _ => return Err(invalid()),
+            };
+            let mut factors = lhs.split('*').map(|x| {
+                module.string_to_basis_element(x.trim()).ok_or_else(|| {
+                    ModuleSpecError::UnknownGenerator {
+                        path: path.clone(),
+                        input: entry.clone(),
+                        generator: x.trim().to_string(),
+                    }
+                })
+            });
+            let (left, right) = match (factors.next(), factors.next(), factors.next()) {
+                (Some(left), Some(right), None) => (left?, right?),
+                _ => return Err(invalid()),
+            };
+            let degree = left.0 + right.0;
+            let mut value = FpVector::new(p, module.dimension(degree));
+            module
+                .parse_element(rhs, degree, &mut value)
+                .map_err(|err| err.at(path.clone()))?;
+            // If y * x is also specified, it has to agree with x * y up to sign. This includes
+            // the case x = y, where x * x has to vanish if x has odd degree at an odd prime.
+            let mut swapped = value.clone();
+            swapped.scale(Self::commutativity_sign(*p, left.0, right.0));
+            let other = if left == right {
+                Some(&value)
+            } else {
+                result.products.get(&(right.0, right.1, left.0, left.1))
+            };
+            if matches!(other, Some(other) if other != &swapped) {
+                return Err(ModuleSpecError::Invalid {
+                    path,
+                    input: entry.clone(),
+                    info: "The product is not graded commutative".to_string(),
+                });
+            }
+            result.set_product(left, right, value);
+        }
+        Ok(result)
+    }
+
+    /// Adds `coeff` times the product of `left` and `right` to `result`.
+    pub fn multiply(
+        &self,
+        result: &mut FpVector,
+        coeff: u32,
+        left_degree: i32,
+        left: &FpVector,
+        right_degree: i32,
+        right: &FpVector,
+    ) {
+        let p = *result.prime();
+        for (i, c) in left.iter().enumerate() {
+            if c == 0 {
+                continue;
+            }
+            for (j, d) in right.iter().enumerate() {
+                if d == 0 {
+                    continue;
+                }
+                if let Some((v, sign)) = self.product((left_degree, i), (right_degree, j)) {
+                    result.add(v, coeff * c % p * d % p * sign % p);
+                }
+            }
+        }
+    }
+}
+
+/// The element $\beta^\epsilon P^i$ of `algebra`, or $\beta^\epsilon \mathrm{Sq}^i$ at the prime
+/// 2, where $\epsilon$ is `bockstein`. Returns `None` if the element is not in `algebra`.
+fn operation<A: SteenrodAlgebraT>(
+    algebra: &A,
+    milnor: &MilnorAlgebra,
+    bockstein: bool,
+    i: u32,
+) -> Option<(i32, FpVector)> {
+    let p = algebra.prime();
+    let q = if *p == 2 { 1 } else { 2 * *p as i32 - 2 };
+    let degree = i as i32 * q + bockstein as i32;
+    algebra.compute_basis(degree);
+    let mut result = FpVector::new(p, algebra.dimension(degree, -1));
+    if degree == 0 {
+        result.set_entry(0, 1);
+        return Some((0, result));
+    }
+    let elt = MilnorBasisElement {
+        q_part: bockstein as u32,
+        p_part: if i == 0 { vec![] } else { vec![i] },
+        degree,
+    };
+    milnor_to_algebra(algebra, milnor, &mut result, &elt).ok()?;
+    Some((degree, result))
+}
+
+impl<A: SteenrodAlgebraT> FDModule<A> {
+    fn act_on_basis_by_element(&self, op: &(i32, FpVector), degree: i32, idx: usize) -> FpVector {
+        let p = self.prime();
+        let mut input = FpVector::new(p, self.dimension(degree));
+        input.set_entry(idx, 1);
+        let mut result = FpVector::new(p, self.dimension(degree + op.0));
+        self.act_by_element(&mut result, 1, op.0, &op.1, degree, &input);
+        result
+    }
+
+    /// Checks that the module is unstable. This checks the instability condition on the operations
+    /// $\mathrm{Sq}^i$ (resp. $\beta^\epsilon P^i$) that lie in the algebra. An unstable module
+    /// also vanishes in negative degrees, which is checked using $\mathrm{Sq}^0$.
+    pub fn check_unstable(&self) -> Result<(), UnstableModuleError> {
+        let p = self.prime();
+        let algebra = self.algebra();
+        let milnor = MilnorAlgebra::new(p);
+        let q = if *p == 2 { 1 } else { 2 * *p as i32 - 2 };
+        let max_degree = self.max_degree();
+
+        for degree in self.min_degree()..=max_degree {
+            for idx in 0..self.dimension(degree) {
+                let generator = self.basis_element_to_string(degree, idx);
+                if degree < 0 {
+                    return Err(UnstableModuleError::Instability {
+                        operation: algebra.basis_element_to_string(0, 0),
+                        generator: generator.clone(),
+                        value: generator,
+                    });
+                }
+                for bockstein in &[false, true] {
+                    if *p == 2 && *bockstein {
+                        continue;
+                    }
+                    // The excess of b^e P^i is 2i + e, or i at the prime 2.
+                    let mut i = if *p == 2 {
+                        degree + 1
+                    } else {
+                        (degree - *bockstein as i32).div_euclid(2) + 1
+                    };
+                    while i * q + (*bockstein as i32) + degree <= max_degree {
+                        if let Some(op) = operation(&*algebra, &milnor, *bockstein, i as u32) {
+                            let value = self.act_on_basis_by_element(&op, degree, idx);
+                            if !value.is_zero() {
+                                return Err(UnstableModuleError::Instability {
+                                    operation: algebra.element_to_string(op.0, &op.1),
+                                    generator,
+                                    value: self.element_to_string(degree + op.0, &value),
+                                });
+                            }
+                        }
+                        i += 1;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that `products` satisfies the Cartan formula with respect to the action of the
+    /// algebra. It suffices to check this for $\mathrm{Sq}^k$ at the prime 2, and for $\beta$ and
+    /// $P^k$ at odd primes. This also checks that $\mathrm{Sq}^{|x|} x = x^2$, resp.
+    /// $P^{|x|/2} x = x^p$ if $|x|$ is even, which is the part of instability that involves the
+    /// products.
+    pub fn check_cartan_formula(&self, products: &CupProducts) -> Result<(), UnstableModuleError> {
+        let p = self.prime();
+        let algebra = self.algebra();
+        let milnor = MilnorAlgebra::new(p);
+        let min_degree = self.min_degree();
+        let max_degree = self.max_degree();
+
+        for degree in std::cmp::max(0, min_degree)..=max_degree / *p as i32 {
+            if *p != 2 && degree % 2 != 0 {
+                continue;
+            }
+            let k = if *p == 2 { degree } else { degree / 2 };
+            let op = match operation(&*algebra, &milnor, false, k as u32) {
+                Some(op) => op,
+                None => continue,
+            };
+            for idx in 0..self.dimension(degree) {
+                let mut x = FpVector::new(p, self.dimension(degree));
+                x.set_entry(idx, 1);
+                let mut power = x.clone();
+                for n in 1..*p as i32 {
+                    let mut next = FpVector::new(p, self.dimension((n + 1) * degree));
+                    products.multiply(&mut next, 1, n * degree, &power, degree, &x);
+                    power = next;
+                }
+                let value = self.act_on_basis_by_element(&op, degree, idx);
+                if value != power {
+                    let output_degree = *p as i32 * degree;
+                    return Err(UnstableModuleError::Power {
+                        operation: algebra.element_to_string(op.0, &op.1),
+                        generator: self.basis_element_to_string(degree, idx),
+                        value: self.element_to_string(output_degree, &value),
+                        expected: self.element_to_string(output_degree, &power),
+                    });
+                }
+            }
+        }
+
+        // The operations Sq^k or P^k, indexed by k. These are None if they are not in the algebra.
+        let q = if *p == 2 { 1 } else { 2 * *p as i32 - 2 };
+        let max_k = std::cmp::max(0, (max_degree - 2 * min_degree) / q) as u32;
+        let ops: Vec<_> = (0..=max_k)
+            .map(|k| operation(&*algebra, &milnor, false, k))
+            .collect();
+        let bockstein = if *p == 2 {
+            None
+        } else {
+            operation(&*algebra, &milnor, true, 0)
+        };
+
+        for left_degree in min_degree..=max_degree {
+            for right_degree in min_degree..=max_degree - left_degree {
+                let product_degree = left_degree + right_degree;
+                if product_degree < min_degree {
+                    continue;
+                }
+                for i in 0..self.dimension(left_degree) {
+                    for j in 0..self.dimension(right_degree) {
+                        let mut x = FpVector::new(p, self.dimension(left_degree));
+                        x.set_entry(i, 1);
+                        let mut y = FpVector::new(p, self.dimension(right_degree));
+                        y.set_entry(j, 1);
+                        let mut xy = FpVector::new(p, self.dimension(product_degree));
+                        products.multiply(&mut xy, 1, left_degree, &x, right_degree, &y);
+
+                        let check = |op: &(i32, FpVector), expected: &FpVector| {
+                            let mut value = FpVector::new(p, expected.dimension());
+                            self.act_by_element(&mut value, 1, op.0, &op.1, product_degree, &xy);
+                            if &value == expected {
+                                return Ok(());
+                            }
+                            let output_degree = product_degree + op.0;
+                            Err(UnstableModuleError::Cartan {
+                                operation: algebra.element_to_string(op.0, &op.1),
+                                left: self.basis_element_to_string(left_degree, i),
+                                right: self.basis_element_to_string(right_degree, j),
+                                value: self.element_to_string(output_degree, &value),
+                                expected: self.element_to_string(output_degree, expected),
+                            })
+                        };
+
+                        // b(xy) = b(x) y + (-1)^{|x|} x b(y)
+                        if let Some(b) = &bockstein {
+                            let output_degree = product_degree + 1;
+                            if output_degree <= max_degree {
+                                let mut expected = FpVector::new(p, self.dimension(output_degree));
+                                let bx = self.act_on_basis_by_element(b, left_degree, i);
+                                let by = self.act_on_basis_by_element(b, right_degree, j);
+                                let sign = if left_degree % 2 == 0 { 1 } else { *p - 1 };
+                                products.multiply(
+                                    &mut expected,
+                                    1,
+                                    left_degree + 1,
+                                    &bx,
+                                    right_degree,
+                                    &y,
+                                );
+                                products.multiply(
+                                    &mut expected,
+                                    sign,
+                                    left_degree,
+                                    &x,
+                                    right_degree + 1,
+                                    &by,
+                                );
+                                check(b, &expected)?;
+                            }
+                        }
+
+                        // P^k(xy) = sum_i P^i(x) P^{k - i}(y)
+                        for (k, op) in ops.iter().enumerate().skip(1) {
+                            let op = match op {
+                                Some(op) => op,
+                                None => continue,
+                            };
+                            let output_degree = product_degree + op.0;
+                            if output_degree > max_degree {
+                                break;
+                            }
+                            let mut expected = FpVector::new(p, self.dimension(output_degree));
+                            let mut complete = true;
+                            for l in 0..=k {
+                                match (&ops[l], &ops[k - l]) {
+                                    (Some(left_op), Some(right_op)) => {
+                                        let px =
+                                            self.act_on_basis_by_element(left_op, left_degree, i);
+                                        let py =
+                                            self.act_on_basis_by_element(right_op, right_degree, j);
+                                        products.multiply(
+                                            &mut expected,
+                                            1,
+                                            left_degree + left_op.0,
+                                            &px,
+                                            right_degree + right_op.0,
+                                            &py,
+                                        );
+                                    }
+                                    _ => complete = false,
+                                }
+                            }
+                            // If some term of the Cartan formula is not in the algebra, we cannot
+                            // evaluate it.
+                            if complete {
+                                check(op, &expected)?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use fp::prime::ValidPrime;
 use fp::vector::{FpVector, FpVectorT};
-use crate::algebra::{Algebra, AdemAlgebra, MilnorAlgebra};
+use crate::algebra::{Algebra, AdemAlgebra, HopfAlgebra, MilnorAlgebra};
 use crate::algebra::adem_algebra::AdemBasisElement;
 use crate::steenrod_parser::BocksteinOrSq;
 use crate::module::Module;
@@ -21,6 +21,10 @@ impl SteenrodCalculator {
         }
     }
 
+    pub fn prime(&self) -> ValidPrime {
+        self.adem_algebra.prime()
+    }
+
     pub fn compute_basis(&self, degree : i32){
         self.adem_algebra.compute_basis(degree);
         self.milnor_algebra.compute_basis(degree);
@@ -41,6 +45,18 @@ impl SteenrodCalculator {
     pub fn evaluate_milnor(&self, input : &str) -> error::Result<(i32, FpVector)> {
         evaluate_algebra_milnor(&self.adem_algebra, &self.milnor_algebra, input)
     }
+
+    /// Evaluates an element of `module` such as `Sq2 * x0 + x2`. The module must be defined over
+    /// the Adem algebra at the same prime, and `basis_elt_lookup` maps the names of the basis
+    /// elements of `module` to their degree and index.
+    pub fn evaluate_module<M : Module>(
+        &self,
+        module : &M,
+        basis_elt_lookup : &HashMap<String, (i32, usize)>,
+        input : &str
+    ) -> error::Result<(i32, FpVector)> {
+        evaluate_module(&self.adem_algebra, &self.milnor_algebra, module, basis_elt_lookup, input)
+    }
 }
 
 // Outputs in the Adem basis.
@@ -96,6 +112,33 @@ fn evaluate_algebra_tree_helper(
         AlgebraParseNode::BasisElt(basis_elt) => {
             evaluate_basis_element(adem_algebra, milnor_algebra, output_degree, basis_elt)
         },
+        AlgebraParseNode::Conjugate(x) => {
+            let (degree, input) = evaluate_algebra_tree_helper(adem_algebra, milnor_algebra, output_degree, *x)?;
+            let mut result = FpVector::new(p, input.dimension());
+            adem_algebra.antipode(&mut result, 1, degree, &input);
+            Ok((degree, result))
+        },
+        AlgebraParseNode::Power(x, n) => {
+            let (degree, input) = evaluate_algebra_tree_helper(adem_algebra, milnor_algebra, None, *x)?;
+            let mut result_degree = 0;
+            let mut result = FpVector::new(p, 1);
+            result.set_entry(0, 1);
+            for _ in 0..n {
+                let new_degree = result_degree + degree;
+                adem_algebra.compute_basis(new_degree);
+                milnor_algebra.compute_basis(new_degree);
+                let mut product = FpVector::new(p, adem_algebra.dimension(new_degree, -1));
+                adem_algebra.multiply_element_by_element(&mut product, 1, result_degree, &result, degree, &input, -1);
+                result = product;
+                result_degree = new_degree;
+            }
+            if let Some(requested_degree) = output_degree {
+                if result_degree != requested_degree {
+                    return Err(DegreeError{}.into());
+                }
+            }
+            Ok((result_degree, result))
+        },
         AlgebraParseNode::Scalar(x) => {
             if let Some(degree) = output_degree {
                 if degree != 0 {
@@ -119,6 +162,30 @@ fn evaluate_basis_element(
     let q = if adem_algebra.generic { 2 * (*p) - 2 } else { 1 };
     let degree : i32;
     let mut result;
+    let basis_elt = match basis_elt {
+        AlgebraBasisElt::Pst(s, t) => {
+            if t == 0 {
+                return Err(error::GenericError::new("P^s_t requires t > 0".to_string()).into());
+            }
+            let mut p_list = vec![0; t as usize];
+            p_list[t as usize - 1] = fp::prime::integer_power(*p, s);
+            AlgebraBasisElt::PList(p_list)
+        }
+        AlgebraBasisElt::ArnonX(m, k) | AlgebraBasisElt::WallQ(m, k) => {
+            if *p != 2 {
+                return Err(error::GenericError::new("X^m_k and Q^m_k are only defined at p = 2".to_string()).into());
+            }
+            if k > m {
+                return Err(error::GenericError::new(format!("Invalid factor with m = {} < k = {}", m, k)).into());
+            }
+            let mut list = (k ..= m).map(|i| BocksteinOrSq::Sq(1 << i)).collect::<Vec<_>>();
+            if let AlgebraBasisElt::ArnonX(..) = basis_elt {
+                list.reverse();
+            }
+            AlgebraBasisElt::AList(list)
+        }
+        _ => basis_elt
+    };
     match basis_elt {
         AlgebraBasisElt::AList(p_or_b_list) => {
             let degree_result = evaluate_p_or_b_list(adem_algebra, &p_or_b_list);
@@ -155,6 +222,7 @@ fn evaluate_basis_element(
             result = FpVector::new(p, adem_algebra.dimension(degree, -1));
             change_of_basis::adem_q(adem_algebra, milnor_algebra, &mut result, 1, x);
         }
+        AlgebraBasisElt::Pst(..) | AlgebraBasisElt::ArnonX(..) | AlgebraBasisElt::WallQ(..) => unreachable!()
     }
     if let Some(requested_degree) = output_degree {
         if degree != requested_degree {
@@ -208,6 +276,7 @@ fn evaluate_module_tree_helper<M : Module>(
             }
             let (degree_right, output_right) = evaluate_module_tree_helper(adem_algebra, milnor_algebra, module, basis_elt_lookup, output_degree, *right)?;
             let degree = degree_left + degree_right;
+            module.compute_basis(degree);
             let mut result = FpVector::new(p, module.dimension(degree));
             module.act_by_element(&mut result, 1, degree_left, &output_left, degree_right, &output_right);
             Ok((degree, result))
@@ -343,6 +412,15 @@ mod tests {
             ("Sq2 * (Sq2 * Sq2 + Sq4)", "P6"),
             ("Sq7 + Q2","P5 P2 + P6 P1 + P4 P2 P1"),            
             ("(Q2 + Sq7) * Q1", "P6 P3 P1"),
+            ("Q_1 + Sq^3", "P2 P1"),
+            ("P^0_2", "P3 + P2 P1"),
+            ("χ(Sq3)", "P2 P1"),
+            ("chi(Sq2 * Sq1) + χSq1 * χSq2", "0"),
+            ("Sq1^2 + Sq2^1", "P2"),
+            ("Sq2^0", "1"),
+            ("(Sq3 + Sq2 Sq1)^2", "0"),
+            ("(Sq2 + Sq1 Sq1)^2", "P3 P1"),
+            ("Sq(0, 1) + Sq(3)", "P2 P1"),
         ]{
             let (degree, result) = evaluate_algebra_adem(&adem, &milnor, input).unwrap();
             println!("{} ==> {}", input, adem.element_to_string(degree, &result));
@@ -359,10 +437,61 @@ mod tests {
             ("A(1 1)", "2 * P2"),
             ("A(1 b 1)", "b P2 + P2 b"),
             ("A(4 2)", "2 * P5 P1"),
+            ("b P1^2", "2 * b P2"),
+            ("Q(0, 1) + Q_1 * b", "0"),
+            ("χ(P1 * b)", "b P1"),
+            ("Q_1 - P1 b", "2 * b P1"),
         ]{
             let (degree, result) = evaluate_algebra_adem(&adem, &milnor, input).unwrap();
             println!("{} ==> {}", input, adem.element_to_string(degree, &result));
             assert_eq!(adem.element_to_string(degree, &result), *output);
         }
     }
+
+    #[test]
+    fn test_subtraction() {
+        // a - b is a + (-1) b, and subtraction is left associative.
+        let p = ValidPrime::new(3);
+        let adem = AdemAlgebra::new(p, *p != 2, false);
+        let milnor = MilnorAlgebra::new(p);
+        adem.compute_basis(10);
+        milnor.compute_basis(10);
+        for (input, output) in &[
+            ("P1 - P1", "0"),
+            ("P1 + P1", "2 * P1"),
+            ("P1 - P1 - P1", "2 * P1"),
+            ("P1 - (P1 - P1)", "P1"),
+            ("-P1 - 2 P1", "0"),
+            ("P1 * P1 - 2 * P2", "0"),
+        ]{
+            let (degree, result) = evaluate_algebra_adem(&adem, &milnor, input).unwrap();
+            assert_eq!(adem.element_to_string(degree, &result), *output, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let max_degree = 40;
+        for &p in &[2, 3, 5] {
+            let p = ValidPrime::new(p);
+            let adem = AdemAlgebra::new(p, *p != 2, false);
+            let milnor = MilnorAlgebra::new(p);
+            adem.compute_basis(max_degree);
+            milnor.compute_basis(max_degree);
+            for degree in 0 ..= max_degree {
+                for idx in 0 .. adem.dimension(degree, -1) {
+                    let input = adem.basis_element_to_string(degree, idx);
+                    let (d, result) = evaluate_algebra_adem(&adem, &milnor, &input).unwrap();
+                    assert_eq!(d, degree, "{}", input);
+                    assert_eq!(adem.element_to_string(d, &result), input);
+                }
+                for idx in 0 .. milnor.dimension(degree, -1) {
+                    let input = milnor.basis_element_to_string(degree, idx);
+                    let (d, result) = evaluate_algebra_milnor(&adem, &milnor, &input).unwrap();
+                    assert_eq!(d, degree, "{}", input);
+                    assert_eq!(milnor.element_to_string(d, &result), input);
+                }
+            }
+        }
+    }
 }
@@ -2,13 +2,13 @@ use nom::{
   IResult,
   branch::alt,
   bytes::complete::tag,  
-  combinator::{map_res, opt},
-  character::complete::{char, alphanumeric0, alpha1},
+  combinator::{cut, map, map_res, not, opt},
+  character::complete::{char, alphanumeric0, alphanumeric1, alpha1},
   character::complete::{digit1 as digit, space0 as space},
   error::ErrorKind::Char,
   multi::fold_many0,
   multi::many0,
-  sequence::{delimited, pair, tuple}
+  sequence::{delimited, pair, preceded, terminated, tuple}
 };
 
 use std::str::FromStr;
@@ -19,7 +19,10 @@ pub enum AlgebraBasisElt {
     AList(Vec<BocksteinOrSq>), // Admissible list.
     PList(Vec<u32>),
     P(u32),
-    Q(u32)
+    Q(u32),
+    Pst(u32, u32), // P^s_t
+    ArnonX(u32, u32), // X^m_k = Sq^{2^m} ... Sq^{2^k}
+    WallQ(u32, u32), // Q^m_k = Sq^{2^k} ... Sq^{2^m}
 }
 
 #[derive(Debug)]
@@ -28,7 +31,9 @@ pub enum AlgebraParseNode {
     Product(Box<AlgebraParseNode>, Box<AlgebraParseNode>),
     Sum(Box<AlgebraParseNode>, Box<AlgebraParseNode>),
     BasisElt(AlgebraBasisElt),
-    Scalar(i32)
+    Scalar(i32),
+    Conjugate(Box<AlgebraParseNode>), // χ(x)
+    Power(Box<AlgebraParseNode>, u32) // x^n
 }
 
 #[derive(Debug)]
@@ -53,11 +58,12 @@ fn comma_separated_integer_list(i : &str) -> IResult<&str, Vec<u32>> {
 }
 
 
+// Once we have read the opening parenthesis, the rest of the sequence must follow.
 fn comma_separated_sequence(i : &str) -> IResult<&str, Vec<u32>> {
     delimited(
       tag("("),
-      comma_separated_integer_list,
-      tag(")")
+      cut(comma_separated_integer_list),
+      cut(tag(")"))
     )(i)
 }
 
@@ -69,7 +75,7 @@ fn space_separated_sequence(i : &str) -> IResult<&str, Vec<u32>> {
     delimited(
       tag("("),
       space_separated_integer_list,
-      tag(")")
+      cut(tag(")"))
     )(i)
 }
 
@@ -97,12 +103,48 @@ fn space_separated_bockstein_or_sq_sequence(i : &str) -> IResult<&str, Vec<Bocks
     delimited(
       tag("("),
       space_separated_bockstein_or_sq_list,
-      tag(")")
+      cut(tag(")"))
     )(i)
 }
 
 
+fn superscript_subscript(i : &str) -> IResult<&str, (u32, u32)> {
+    pair(preceded(char('^'), digits), preceded(char('_'), digits))(i)
+}
+
+// The product Q_{i_1} Q_{i_2} ... of the list of indices, or 1 if the list is empty.
+fn q_product(list : Vec<u32>) -> AlgebraParseNode {
+    list.into_iter()
+        .map(|x| AlgebraParseNode::BasisElt(AlgebraBasisElt::Q(x)))
+        .fold(AlgebraParseNode::Scalar(1), |acc, x| AlgebraParseNode::Product(Box::new(acc), Box::new(x)))
+}
+
 fn algebra_generator(i : &str) -> IResult<&str, AlgebraParseNode> {
+    if let Ok((rest, (c, (x, y)))) = pair(alt((char('P'), char('X'), char('Q'))), superscript_subscript)(i) {
+        let result = match c {
+            'P' => AlgebraBasisElt::Pst(x, y),
+            'X' => AlgebraBasisElt::ArnonX(x, y),
+            'Q' => AlgebraBasisElt::WallQ(x, y),
+            _ => unreachable!()
+        };
+        return Ok((rest, AlgebraParseNode::BasisElt(result)));
+    }
+    // Sq^n and P^n
+    if let Ok((rest, x)) = preceded(pair(alt((tag("Sq"), tag("P"))), char('^')), digits)(i) {
+        return Ok((rest, AlgebraParseNode::BasisElt(AlgebraBasisElt::P(x))));
+    }
+    // Q_i, and the Bockstein b = Q_0. We do not want to parse the start of a module generator
+    // such as "bx" as a Bockstein.
+    if let Ok((rest, x)) = alt((
+        preceded(tag("Q_"), digits),
+        map(terminated(char('b'), not(alphanumeric1)), |_| 0)
+    ))(i) {
+        return Ok((rest, AlgebraParseNode::BasisElt(AlgebraBasisElt::Q(x))));
+    }
+    // Q(i_1, i_2, ...) = Q_{i_1} Q_{i_2} ..., as in Q(0, 2) P(1, 1).
+    if let (rest, Some(list)) = opt(preceded(tag("Q"), comma_separated_sequence))(i)? {
+        return Ok((rest, q_product(list)));
+    }
     let (rest, opt_elt) = opt(alt((
         pair(tag("Q"), digits),
         pair(tag("P"), digits),
@@ -117,11 +159,11 @@ fn algebra_generator(i : &str) -> IResult<&str, AlgebraParseNode> {
         };
         return Ok((rest, AlgebraParseNode::BasisElt(result)));
     }
-    if let Ok((rest, elt)) = alt((
+    if let (rest, Some(elt)) = opt(alt((
             pair(tag("P"), comma_separated_sequence),
             pair(tag("Sq"), comma_separated_sequence),
             pair(tag("M"), space_separated_sequence),
-        ))(i) {
+        )))(i)? {
         let result = match elt {
             ("P", x ) | ( "Sq", x) | ("M", x) => AlgebraBasisElt::PList(x),
             _ => unreachable!()
@@ -159,14 +201,46 @@ fn algebra_parens(i: &str) -> IResult<&str, AlgebraParseNode> {
   )(i)
 }
 
-fn algebra_factor(i: &str) -> IResult<&str, AlgebraParseNode> {
+// χ(x), or χ applied to a single generator. This can also be written as chi.
+fn algebra_conjugate(i: &str) -> IResult<&str, AlgebraParseNode> {
+  map(
+    preceded(
+      delimited(space, alt((tag("χ"), tag("chi"))), space),
+      alt((
+        algebra_parens,
+        delimited(space, algebra_generator, space)
+      ))
+    ),
+    |x| AlgebraParseNode::Conjugate(Box::new(x))
+  )(i)
+}
+
+fn algebra_atom(i: &str) -> IResult<&str, AlgebraParseNode> {
   alt((
+    algebra_conjugate,
     delimited(space, algebra_generator, space),
-    scalar,
+    map(digits, |x| AlgebraParseNode::Scalar(x as i32)),
     algebra_parens
   ))(i)
 }
 
+// A factor that is not preceded by a sign, so that "a - b" is not parsed as a product. This may
+// be raised to a power, which binds more tightly than the sign.
+fn algebra_unsigned_factor(i: &str) -> IResult<&str, AlgebraParseNode> {
+  let (rest, (atom, power)) = pair(algebra_atom, opt(preceded(char('^'), digits)))(i)?;
+  match power {
+    Some(n) => Ok((rest, AlgebraParseNode::Power(Box::new(atom), n))),
+    None => Ok((rest, atom))
+  }
+}
+
+fn algebra_factor(i: &str) -> IResult<&str, AlgebraParseNode> {
+  alt((
+    algebra_unsigned_factor,
+    scalar
+  ))(i)
+}
+
 // We read an initial factor and for each time we find
 // a * or / operator followed by another factor, we do
 // the math by folding everything
@@ -177,10 +251,11 @@ fn algebra_term(i: &str) -> IResult<&str, AlgebraParseNode> {
         (Some('-'), fact) => AlgebraParseNode::Product(Box::new(AlgebraParseNode::Scalar(-1)), Box::new(fact)),
         _ => unreachable!()
     };  
+    // Factors are separated by a * or by whitespace, which `algebra_factor` has already consumed.
     fold_many0(
-        pair(alt((char('*'), char(' '))), algebra_factor),
+        pair(opt(char('*')), algebra_unsigned_factor),
         first_factor,
-        |acc, (_op, val): (char, AlgebraParseNode)| {
+        |acc, (_op, val): (Option<char>, AlgebraParseNode)| {
             AlgebraParseNode::Product(Box::new(acc), Box::new(val))
         }
     )(i)
@@ -189,10 +264,17 @@ fn algebra_term(i: &str) -> IResult<&str, AlgebraParseNode> {
 fn algebra_expr(i: &str) -> IResult<&str, AlgebraParseNode> {
   let (i, init) = algebra_term(i)?;
 
+  // Once we have read an operator, a term must follow, so report the error in the term instead of
+  // backtracking to before the operator.
   fold_many0(
-    pair(alt((char('+'), char('-'))), algebra_term),
+    pair(alt((char('+'), char('-'))), cut(algebra_term)),
     init,
-    |acc, (_op, val): (char, AlgebraParseNode)| {
+    |acc, (op, val): (char, AlgebraParseNode)| {
+        let val = if op == '-' {
+            AlgebraParseNode::Product(Box::new(AlgebraParseNode::Scalar(-1)), Box::new(val))
+        } else {
+            val
+        };
         AlgebraParseNode::Sum(Box::new(acc), Box::new(val))
     }
   )(i)
@@ -241,42 +323,88 @@ fn module_term(i: &str) -> IResult<&str, ModuleParseNode> {
 fn module_expr(i: &str) -> IResult<&str, ModuleParseNode> {
   let (i, init) = module_term(i)?;
   fold_many0(
-    pair(alt((char('+'), char('-'))), module_term),
+    pair(alt((char('+'), char('-'))), cut(module_term)),
     init,
-    |acc, (_op, val): (char, ModuleParseNode)| {
+    |acc, (op, val): (char, ModuleParseNode)| {
+        let val = if op == '-' {
+            ModuleParseNode::Act(Box::new(AlgebraParseNode::Scalar(-1)), Box::new(val))
+        } else {
+            val
+        };
         ModuleParseNode::Sum(Box::new(acc), Box::new(val))
     }
   )(i)
 }
 
+/// Parses an element of the Steenrod algebra. This is a sum of products of factors, which are
+/// integers, parenthesized expressions, or one of
+///  - `Sq4`, `Sq^4`, `P1` or `P^1` for $\mathrm{Sq}^4$ or $P^1$;
+///  - `b` for the Bockstein, and `Q_i` or `Qi` for the Milnor primitive $Q_i$;
+///  - `Sq(1, 2)` or `P(1, 2)` for Milnor basis elements, optionally preceded by Bocksteins
+///    written as `Q(0, 2)`, which is the product $Q_0 Q_2$;
+///  - `A(2 b 2 3)` for admissible monomials, and `M(1 2)` for Milnor basis elements;
+///  - `P^s_t`, `X^m_k` and `Q^m_k`;
+///  - `χ(x)`, or `chi(x)`, for the conjugate of `x`.
+///
+/// Any factor can be raised to a power, as in `(Sq2 + Sq1 Sq1)^2`. A difference `a - b` is parsed
+/// as `a + (-1) b`. Parse errors record the character offset at which parsing failed.
 pub fn parse_algebra(i : &str) -> Result<AlgebraParseNode, ParseError> {
     let (rest, parse_tree) = algebra_expr(i)
-        .or_else(|err| Err(ParseError{info : format!("{:#?}", err) }))?;
+        .map_err(|err| ParseError::from_nom(i, err))?;
     if rest.is_empty() {
         Ok(parse_tree)
     } else {
-        Err(ParseError {info : "Failed to consume all of input".to_string()})
+        Err(ParseError::new(i, rest, "Failed to consume all of input".to_string()))
     }
 }
 
 pub fn parse_module(i : &str) -> Result<ModuleParseNode, ParseError> {
     let (rest, parse_tree) = module_expr(i)
-        .or_else(|err| Err(ParseError{info : format!("{:#?}", err) }))?;
+        .map_err(|err| ParseError::from_nom(i, err))?;
     if rest.is_empty() {
         Ok(parse_tree)
     } else {
-        Err(ParseError {info : "Failed to consume all of input".to_string()})
+        Err(ParseError::new(i, rest, "Failed to consume all of input".to_string()))
     }
 }
 
 #[derive(Debug)]
 pub struct ParseError {
     pub info : String,
+    /// The character offset into the input at which parsing failed.
+    pub position : usize,
+}
+
+impl ParseError {
+    /// Creates an error at the start of `rest`, which should be a slice of `input`.
+    pub fn new(input : &str, rest : &str, info : String) -> Self {
+        Self { info, position : substring_position(input, rest) }
+    }
+
+    pub fn from_nom(input : &str, err : nom::Err<(&str, nom::error::ErrorKind)>) -> Self {
+        match err {
+            nom::Err::Error((rest, kind)) | nom::Err::Failure((rest, kind)) =>
+                Self::new(input, rest, format!("{:?}", kind)),
+            nom::Err::Incomplete(_) =>
+                Self::new(input, &input[input.len()..], "Incomplete input".to_string()),
+        }
+    }
+}
+
+/// The character offset of `substring` in `input`, where `substring` is a slice of `input`. Some
+/// of our parsers return errors that do not point into the input, in which case we return 0.
+pub(crate) fn substring_position(input : &str, substring : &str) -> usize {
+    let offset = (substring.as_ptr() as usize).wrapping_sub(input.as_ptr() as usize);
+    if offset <= input.len() && input.is_char_boundary(offset) {
+        input[..offset].chars().count()
+    } else {
+        0
+    }
 }
 
 impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Parse error:\n    {}\n", &self.info)
+        write!(f, "Parse error at position {}:\n    {}\n", self.position, &self.info)
     }
 }
 
@@ -299,6 +427,53 @@ mod tests {
         
         println!();
     }
+
+    #[test]
+    fn test_parse_error_incomplete() {
+        // Incomplete input is reported at the end, counted in characters.
+        let err = ParseError::from_nom("χ(Sq2", nom::Err::Incomplete(nom::Needed::Unknown));
+        assert_eq!(err.position, 5);
+    }
+
+    #[test]
+    fn test_parser_extensions() {
+        for input in &[
+            "χ(Sq2 * Sq1) + chi Sq4",
+            "Q_0 Q_2 P(1, 1) + Q(0, 2) Sq(1, 1)",
+            "b P2 b",
+            "P^3_1 + Sq^4 + P^2 * X^2_0",
+            "Sq2^2 + (Sq1 + Sq2)^3 - 2 Sq1^0",
+        ] {
+            assert!(parse_algebra(input).is_ok(), "{}", input);
+        }
+        assert!(matches!(parse_algebra("-Sq1^2").unwrap(), AlgebraParseNode::Product(_, b) if matches!(*b, AlgebraParseNode::Power(_, 2))));
+    }
+
+    #[test]
+    fn test_parse_difference() {
+        let is_negated = |node : &ModuleParseNode| matches!(node,
+            ModuleParseNode::Act(a, _) if matches!(**a, AlgebraParseNode::Scalar(-1)));
+        match parse_module("x0 - Sq1 * x1").unwrap() {
+            ModuleParseNode::Sum(a, b) => assert!(!is_negated(&a) && is_negated(&b)),
+            tree => panic!("{:?}", tree),
+        }
+        match parse_module("x0 + x1").unwrap() {
+            ModuleParseNode::Sum(a, b) => assert!(!is_negated(&a) && !is_negated(&b)),
+            tree => panic!("{:?}", tree),
+        }
+    }
+
+    #[test]
+    fn test_parse_error_position() {
+        for &(input, position) in &[
+            ("Sq2 * Sq2 )", 10),
+            ("Sq2 + Sq(1,2", 12),
+            ("P1 + + P1", 5),
+        ] {
+            let err = parse_algebra(input).unwrap_err();
+            assert_eq!(err.position, position, "{}", input);
+        }
+    }
     // use rstest::rstest_parametrize;
 
 }
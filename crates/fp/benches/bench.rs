@@ -1,7 +1,9 @@
 use bencher::benchmark_main;
 mod multinomial;
 mod row_reduce;
+mod vector;
 use crate::multinomial::main as multinomial; 
 use crate::row_reduce::main as row_reduce;
+use crate::vector::main as vector;
 
-benchmark_main!(multinomial, row_reduce);
+benchmark_main!(multinomial, row_reduce, vector);
@@ -0,0 +1,49 @@
+use bencher::{Bencher, benchmark_group};
+use rand::prelude::*;
+
+use fp::prime::ValidPrime;
+use fp::vector::{FpVector, FpVectorT, initialize_limb_bit_index_table};
+
+const DIMENSION : usize = 10000;
+
+fn random_vector(p : ValidPrime) -> FpVector {
+    let mut rng = rand::thread_rng();
+    let entries : Vec<u32> = (0 .. DIMENSION).map(|_| rng.gen_range(0, *p)).collect();
+    FpVector::from_vec(p, &entries)
+}
+
+fn add(bench : &mut Bencher, p : u32) {
+    let p = ValidPrime::new(p);
+    initialize_limb_bit_index_table(p);
+    let mut v = random_vector(p);
+    let w = random_vector(p);
+    let c = *p - 1;
+    bench.iter(|| {
+        for _ in 0 .. 100 {
+            v.add(&w, c);
+        }
+    });
+}
+
+fn scale(bench : &mut Bencher, p : u32) {
+    let p = ValidPrime::new(p);
+    initialize_limb_bit_index_table(p);
+    let mut v = random_vector(p);
+    let c = *p - 1;
+    bench.iter(|| {
+        for _ in 0 .. 100 {
+            v.scale(c);
+        }
+    });
+}
+
+fn add_2(bench : &mut Bencher) { add(bench, 2) }
+fn add_3(bench : &mut Bencher) { add(bench, 3) }
+fn add_5(bench : &mut Bencher) { add(bench, 5) }
+fn add_7(bench : &mut Bencher) { add(bench, 7) }
+
+fn scale_3(bench : &mut Bencher) { scale(bench, 3) }
+fn scale_5(bench : &mut Bencher) { scale(bench, 5) }
+fn scale_7(bench : &mut Bencher) { scale(bench, 7) }
+
+benchmark_group!(main, add_2, add_3, add_5, add_7, scale_3, scale_5, scale_7);
@@ -0,0 +1,189 @@
+//! Row reduction at p = 2 by the Method of Four Russians. Pivots are found in blocks of up to
+//! `MAX_BLOCK_SIZE` columns. The block's pivot columns are then cleared from every other row at
+//! once, by adding the matching entry of a table holding all combinations of the block's pivot
+//! rows. The table is filled in Gray code order, so that each entry costs a single addition.
+
+use super::Matrix;
+use crate::vector::{FpVector, FpVectorT};
+
+/// The maximum number of pivots in a block, so that the table has at most `1 << MAX_BLOCK_SIZE`
+/// rows.
+const MAX_BLOCK_SIZE: usize = 8;
+
+/// The entry of `row` in column `column`, as a bit. This assumes p = 2.
+#[inline]
+fn bit(row: &FpVector, column: usize) -> usize {
+    let index = row.min_index() + column;
+    ((row.limbs()[index / 64] >> (index % 64)) & 1) as usize
+}
+
+impl Matrix {
+    /// Row reduces the matrix with the Method of Four Russians. This gives the same result as
+    /// `row_reduce_into_pivots`, but is much faster for large matrices. This is only valid at
+    /// p = 2.
+    pub fn row_reduce_m4ri_into_pivots(&mut self, column_to_pivot_row: &mut [isize]) {
+        assert_eq!(*self.prime(), 2);
+        debug_assert!(self.columns() <= column_to_pivot_row.len());
+        for x in column_to_pivot_row.iter_mut() {
+            *x = -1;
+        }
+        let rows = self.rows();
+        let columns = self.columns();
+        if rows == 0 || columns == 0 {
+            return;
+        }
+        let min_limb = self[0].min_limb();
+        let max_limb = self[0].max_limb();
+
+        // The pivot columns of the current block. The pivot rows are pivot, pivot + 1, ...
+        let mut block: Vec<usize> = Vec::with_capacity(MAX_BLOCK_SIZE);
+        let mut table: Vec<u64> = Vec::new();
+        let mut pivot = 0;
+        let mut column = 0;
+        while pivot < rows && column < columns {
+            // The rows below `pivot` vanish before `column`, so the table only needs the limbs
+            // from here on.
+            let start_limb = (self[0].min_index() + column) / 64;
+            let width = max_limb - start_limb;
+
+            // Find the pivots of the block. The pivot rows are kept reduced against each other,
+            // while the other rows are left alone, so the entries of a candidate row are those it
+            // would have after being reduced by the block.
+            block.clear();
+            while block.len() < MAX_BLOCK_SIZE && pivot + block.len() < rows && column < columns {
+                let new_row = pivot + block.len();
+                let reduced_entry = |row: &FpVector| {
+                    block
+                        .iter()
+                        .enumerate()
+                        .filter(|&(_, &c)| bit(row, c) != 0)
+                        .fold(bit(row, column), |e, (j, _)| {
+                            e ^ bit(&self[pivot + j], column)
+                        })
+                };
+                if let Some(i) = (new_row..rows).find(|&i| reduced_entry(&self[i]) != 0) {
+                    self.swap_rows(new_row, i);
+                    for (j, &c) in block.iter().enumerate() {
+                        if bit(&self[new_row], c) != 0 {
+                            self.row_op(new_row, pivot + j, 1);
+                        }
+                    }
+                    for j in pivot..new_row {
+                        if bit(&self[j], column) != 0 {
+                            self.row_op(j, new_row, 1);
+                        }
+                    }
+                    block.push(column);
+                }
+                column += 1;
+            }
+            if block.is_empty() {
+                break;
+            }
+
+            // Fill in the table. The ith and (i - 1)st Gray codes differ in the bit
+            // i.trailing_zeros(), so each entry is the sum of an earlier one and a pivot row.
+            table.clear();
+            table.resize(width << block.len(), 0);
+            for i in 1..1usize << block.len() {
+                let bit_changed = i.trailing_zeros() as usize;
+                let gray = i ^ (i >> 1);
+                let previous = gray ^ (1 << bit_changed);
+                let row = &self[pivot + bit_changed];
+                for l in 0..width {
+                    // Mask out the entries outside the column slice.
+                    let limb =
+                        row.limbs()[start_limb + l] & row.limb_mask(start_limb + l - min_limb);
+                    table[gray * width + l] = table[previous * width + l] ^ limb;
+                }
+            }
+
+            // Clear the pivot columns from the other rows.
+            for i in (0..pivot).chain(pivot + block.len()..rows) {
+                let index = block
+                    .iter()
+                    .enumerate()
+                    .fold(0, |index, (j, &c)| index | (bit(&self[i], c) << j));
+                if index == 0 {
+                    continue;
+                }
+                let entry = &table[index * width..(index + 1) * width];
+                let limbs = &mut self[i].limbs_mut()[start_limb..max_limb];
+                for (limb, t) in limbs.iter_mut().zip(entry) {
+                    *limb ^= t;
+                }
+            }
+
+            for (j, &c) in block.iter().enumerate() {
+                column_to_pivot_row[c] = (pivot + j) as isize;
+            }
+            pivot += block.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::{AugmentedMatrix3, RowReduction};
+    use crate::prime::ValidPrime;
+    use rand::Rng;
+
+    fn random_matrix(rows: usize, columns: usize) -> Matrix {
+        let p = ValidPrime::new(2);
+        let mut rng = rand::thread_rng();
+        let mut m = Matrix::new(p, rows, columns);
+        let mut row = vec![0; columns];
+        for i in 0..rows {
+            for x in row.iter_mut() {
+                // Sparse rows give more interesting pivots.
+                *x = (rng.gen_range(0, 4) == 0) as u32;
+            }
+            m[i].pack(&row);
+        }
+        m.initialize_pivots();
+        m
+    }
+
+    #[test]
+    fn test_row_reduce_m4ri() {
+        for &(rows, columns) in &[
+            (0, 5),
+            (1, 1),
+            (7, 15),
+            (20, 10),
+            (10, 200),
+            (100, 100),
+            (130, 70),
+            (300, 257),
+        ] {
+            let mut m = random_matrix(rows, columns);
+            let mut n = m.clone();
+            m.row_reduce_with(RowReduction::Gaussian);
+            n.row_reduce_with(RowReduction::FourRussians);
+            assert_eq!(m, n);
+            assert_eq!(m.pivots(), n.pivots());
+        }
+    }
+
+    #[test]
+    fn test_row_reduce_m4ri_segment() {
+        for cols in &[[3, 70, 5], [64, 64, 64], [10, 130, 100], [100, 1, 65]] {
+            let mut m = AugmentedMatrix3::new(ValidPrime::new(2), 150, cols);
+            m.initialize_pivots();
+            let random = random_matrix(150, m.columns());
+            for i in 0..150 {
+                m[i].assign(&random[i]);
+            }
+            let mut n = AugmentedMatrix3 {
+                start: m.start,
+                end: m.end,
+                inner: m.inner.clone(),
+            };
+            m.segment(1, 2).row_reduce_with(RowReduction::Gaussian);
+            n.segment(1, 2).row_reduce_with(RowReduction::FourRussians);
+            assert_eq!(*m, *n);
+            assert_eq!(m.pivots(), n.pivots());
+        }
+    }
+}
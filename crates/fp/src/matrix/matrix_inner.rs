@@ -353,6 +353,27 @@ impl std::ops::IndexMut<usize> for Matrix {
 }
 
 
+/// The algorithm used by `Matrix::row_reduce_with`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RowReduction {
+    /// Gaussian elimination, clearing one pivot column at a time.
+    Gaussian,
+    /// The Method of Four Russians, which clears several pivot columns at once using a table of
+    /// combinations of pivot rows. This is only implemented for p = 2.
+    FourRussians,
+}
+
+impl RowReduction {
+    /// The fastest algorithm available at the prime `p`.
+    pub fn best(p : ValidPrime) -> Self {
+        if *p == 2 {
+            Self::FourRussians
+        } else {
+            Self::Gaussian
+        }
+    }
+}
+
 impl Matrix {
     pub fn swap_rows(&mut self, i : usize, j : usize){
         self.vectors.swap(i + self.slice_row_start, j + self.slice_row_start);
@@ -405,6 +426,17 @@ impl Matrix {
         self.row_reduce_offset_into_pivots(&mut column_to_pivot_row, 0);
         self.set_pivots(column_to_pivot_row);
     }
+
+    /// Same as `row_reduce`, but using the given algorithm. The result does not depend on the
+    /// algorithm. `RowReduction::FourRussians` falls back to Gaussian elimination if p != 2.
+    pub fn row_reduce_with(&mut self, method : RowReduction) {
+        let mut column_to_pivot_row = self.take_pivots();
+        match method {
+            RowReduction::FourRussians if *self.p == 2 => self.row_reduce_m4ri_into_pivots(&mut column_to_pivot_row),
+            _ => self.row_reduce_into_pivots(&mut column_to_pivot_row),
+        }
+        self.set_pivots(column_to_pivot_row);
+    }
     
     pub fn row_reduce_into_pivots(&mut self, column_to_pivot_row: &mut Vec<isize>) {
         self.row_reduce_offset_into_pivots(column_to_pivot_row, 0);
@@ -998,6 +1030,13 @@ impl AugmentedMatrix2 {
 }
 
 impl AugmentedMatrix3 {
+    /// Row reduces the matrix with `RowReduction::best`. These matrices are the largest ones we
+    /// reduce, so this is where the Method of Four Russians pays off.
+    pub fn row_reduce(&mut self) {
+        let method = RowReduction::best(self.prime());
+        self.inner.row_reduce_with(method);
+    }
+
     pub fn compute_quasi_inverses(&mut self) -> (QuasiInverse, QuasiInverse) {
         self.inner.compute_quasi_inverses(self.start[1], self.end[1], self.start[2])
     }
@@ -34,6 +34,8 @@ use crate::prime::ValidPrime;
 use crate::prime::PRIME_TO_INDEX_MAP;
 use crate::prime::NUM_PRIMES;
 
+mod simd;
+
 pub const MAX_DIMENSION : usize = 147500;
 
 // Generated with Mathematica:
@@ -466,8 +468,10 @@ pub trait FpVectorT {
         let number_of_limbs = max_source_limb - min_source_limb;
         let target_limbs = self.limbs_mut();
         let source_limbs = other.limbs();
-        for i in 1..number_of_limbs-1 {
-            target_limbs[i + min_target_limb] = FpVector::add_limb(p, target_limbs[i + min_target_limb], source_limbs[i + min_source_limb], c);
+        if number_of_limbs > 2 {
+            simd::add_limbs(p,
+                &mut target_limbs[min_target_limb + 1 .. max_target_limb - 1],
+                &source_limbs[min_source_limb + 1 .. max_source_limb - 1], c);
         }
         let mut i = 0; {
             let mask = other.limb_mask(i);
@@ -590,9 +594,8 @@ pub trait FpVectorT {
         if number_of_limbs == 0 {
             return;
         }
-        for i in 1..number_of_limbs-1 {
-            let limbs = self.limbs_mut();
-            limbs[i + min_limb] *= c;
+        if number_of_limbs > 2 {
+            simd::scale_limbs(&mut self.limbs_mut()[min_limb + 1 .. max_limb - 1], c as u32);
         }
         let mut i = 0; {
             let mask = self.limb_mask(i);
@@ -709,15 +712,7 @@ impl FpVectorT for FpVector2 {
 
 impl FpVectorT for FpVector3 {
     fn reduce_limbs(&mut self, start_limb : usize, end_limb : usize ){
-        let limbs = &mut self.vector_container.limbs;
-        for limb in &mut limbs[start_limb..end_limb] {
-            let top_bit_set_in_each_field = 0x4924924924924924u64;
-            let mut limb_2 = ((*limb & top_bit_set_in_each_field) >> 2) + (*limb & (!top_bit_set_in_each_field));
-            let mut limb_3s = limb_2 & (limb_2 >> 1);
-            limb_3s |= limb_3s << 1;
-            limb_2 ^= limb_3s;
-            *limb = limb_2;
-        }
+        simd::reduce_limbs(self.prime(), &mut self.vector_container.limbs[start_limb..end_limb]);
     }
 
     fn prime (&self) -> ValidPrime { ValidPrime::new(3) }
@@ -727,19 +722,7 @@ impl FpVectorT for FpVector3 {
 
 impl FpVectorT for FpVector5 {
     fn reduce_limbs(&mut self, start_limb : usize, end_limb : usize ){
-        let limbs = &mut self.vector_container.limbs;
-        for limb in &mut limbs[start_limb..end_limb] {
-            let bottom_bit = 0x84210842108421u64;
-            let bottom_two_bits = bottom_bit | (bottom_bit << 1);
-            let bottom_three_bits = bottom_bit | (bottom_two_bits << 1);
-            let a = (*limb >> 2) & bottom_three_bits;
-            let b = *limb & bottom_two_bits;
-            let m = (bottom_bit << 3) - a + b;
-            let mut c = (m >> 3) & bottom_bit;
-            c |= c << 1;
-            let d = m & bottom_three_bits;
-            *limb = d + c - bottom_two_bits;
-        }
+        simd::reduce_limbs(self.prime(), &mut self.vector_container.limbs[start_limb..end_limb]);
     }
 
     fn prime(&self) -> ValidPrime { ValidPrime::new(5) }
@@ -749,21 +732,7 @@ impl FpVectorT for FpVector5 {
 
 impl FpVectorT for FpVectorGeneric {
     fn reduce_limbs(&mut self, start_limb : usize, end_limb : usize){
-        let p = self.p;
-        let entries_per_64_bits = entries_per_64_bits(p);
-        let mut unpacked_limb = Vec::with_capacity(entries_per_64_bits);
-        for _ in 0..entries_per_64_bits {
-            unpacked_limb.push(0);
-        }
-        let dimension = self.vector_container.dimension;
-        let limbs = &mut self.vector_container.limbs;
-        for i in start_limb..end_limb {
-            FpVector::unpack_limb(p, dimension, 0, &mut unpacked_limb, limbs, i);
-            for limb in &mut unpacked_limb {
-                *limb %= *p;
-            }
-            FpVector::pack_limb(p, dimension, 0, &unpacked_limb, limbs, i);
-        }
+        simd::reduce_limbs(self.p, &mut self.vector_container.limbs[start_limb..end_limb]);
     }
 
     fn prime (&self) -> ValidPrime { self.p }
@@ -1220,7 +1189,7 @@ mod tests {
     }
     use rstest::rstest;
 
-    #[rstest(p, case(3), case(5), case(7))]
+    #[rstest(p, case(3), case(5), case(7), case(13), case(17))]
     fn test_reduce_limb(p : u32){
         let p_ = ValidPrime::new(p);
         initialize_limb_bit_index_table(p_);
@@ -1242,7 +1211,7 @@ mod tests {
         }
     }
 
-    #[rstest(p,  case(2), case(3), case(5), case(7))]
+    #[rstest(p,  case(2), case(3), case(5), case(7), case(13), case(17))]
     fn test_add(p : u32){
         let p_ = ValidPrime::new(p);
         initialize_limb_bit_index_table(p_);
@@ -1253,12 +1222,13 @@ mod tests {
             let mut v_arr = random_vector(p, dim);
             let w_arr = random_vector(p, dim);
             let mut result = vec![0; dim];
+            let c = rand::thread_rng().gen::<u32>() % p;
             v.pack(&v_arr);
             w.pack(&w_arr);
-            v.add(&w, 1);
+            v.add(&w, c);
             v.unpack(&mut result);
             for i in 0..dim {
-                v_arr[i] = (v_arr[i] + w_arr[i]) % p;
+                v_arr[i] = (v_arr[i] + c * w_arr[i]) % p;
             }
             let mut diffs = Vec::new();
             for i in 0..dim {
@@ -1270,7 +1240,7 @@ mod tests {
         }
     }
 
-    #[rstest(p,  case(2), case(3), case(5), case(7))]
+    #[rstest(p,  case(2), case(3), case(5), case(7), case(13), case(17))]
     fn test_scale(p : u32){
         let p_ = ValidPrime::new(p);
         initialize_limb_bit_index_table(p_);
@@ -0,0 +1,563 @@
+//! Limb arithmetic on whole runs of limbs, used for the interior limbs of `add` and `scale` and by
+//! `reduce_limbs`. The operations are written once against the `Lanes` trait, which is
+//! implemented by `u64` and, on x86_64, by the AVX2 and AVX-512 registers. Which one is used is
+//! decided at runtime, and `u64` doubles as the scalar fallback and handles the limbs left over
+//! at the end.
+//!
+//! All operations work entrywise on packed limbs, so a limb is processed the same way no matter
+//! which lane it is in.
+
+use super::{bit_length, entries_per_64_bits};
+use crate::prime::ValidPrime;
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+/// A register holding `WIDTH` limbs. All the arithmetic is wrapping.
+trait Lanes: Copy {
+    const WIDTH: usize;
+
+    unsafe fn load(ptr: *const u64) -> Self;
+    unsafe fn store(self, ptr: *mut u64);
+    unsafe fn splat(x: u64) -> Self;
+    unsafe fn add(self, other: Self) -> Self;
+    unsafe fn sub(self, other: Self) -> Self;
+    unsafe fn and(self, other: Self) -> Self;
+    unsafe fn or(self, other: Self) -> Self;
+    unsafe fn xor(self, other: Self) -> Self;
+    unsafe fn shl(self, n: u32) -> Self;
+    unsafe fn shr(self, n: u32) -> Self;
+    /// Multiplies each limb by `c`. The product must not overflow.
+    unsafe fn mul(self, c: u32) -> Self;
+}
+
+impl Lanes for u64 {
+    const WIDTH: usize = 1;
+
+    #[inline(always)]
+    unsafe fn load(ptr: *const u64) -> Self {
+        *ptr
+    }
+    #[inline(always)]
+    unsafe fn store(self, ptr: *mut u64) {
+        *ptr = self;
+    }
+    #[inline(always)]
+    unsafe fn splat(x: u64) -> Self {
+        x
+    }
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        self.wrapping_add(other)
+    }
+    #[inline(always)]
+    unsafe fn sub(self, other: Self) -> Self {
+        self.wrapping_sub(other)
+    }
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        self & other
+    }
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        self | other
+    }
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        self ^ other
+    }
+    #[inline(always)]
+    unsafe fn shl(self, n: u32) -> Self {
+        self << n
+    }
+    #[inline(always)]
+    unsafe fn shr(self, n: u32) -> Self {
+        self >> n
+    }
+    #[inline(always)]
+    unsafe fn mul(self, c: u32) -> Self {
+        self.wrapping_mul(c as u64)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Lanes for __m256i {
+    const WIDTH: usize = 4;
+
+    #[inline(always)]
+    unsafe fn load(ptr: *const u64) -> Self {
+        _mm256_loadu_si256(ptr as *const _)
+    }
+    #[inline(always)]
+    unsafe fn store(self, ptr: *mut u64) {
+        _mm256_storeu_si256(ptr as *mut _, self)
+    }
+    #[inline(always)]
+    unsafe fn splat(x: u64) -> Self {
+        _mm256_set1_epi64x(x as i64)
+    }
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        _mm256_add_epi64(self, other)
+    }
+    #[inline(always)]
+    unsafe fn sub(self, other: Self) -> Self {
+        _mm256_sub_epi64(self, other)
+    }
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        _mm256_and_si256(self, other)
+    }
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        _mm256_or_si256(self, other)
+    }
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        _mm256_xor_si256(self, other)
+    }
+    #[inline(always)]
+    unsafe fn shl(self, n: u32) -> Self {
+        _mm256_sll_epi64(self, _mm_cvtsi32_si128(n as i32))
+    }
+    #[inline(always)]
+    unsafe fn shr(self, n: u32) -> Self {
+        _mm256_srl_epi64(self, _mm_cvtsi32_si128(n as i32))
+    }
+    #[inline(always)]
+    unsafe fn mul(self, c: u32) -> Self {
+        // There is no 64 bit multiplication in AVX2, so multiply the two halves separately.
+        let c = Self::splat(c as u64);
+        let low = _mm256_mul_epu32(self, c);
+        let high = _mm256_mul_epu32(self.shr(32), c);
+        low.add(high.shl(32))
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Lanes for __m512i {
+    const WIDTH: usize = 8;
+
+    #[inline(always)]
+    unsafe fn load(ptr: *const u64) -> Self {
+        _mm512_loadu_si512(ptr as *const _)
+    }
+    #[inline(always)]
+    unsafe fn store(self, ptr: *mut u64) {
+        _mm512_storeu_si512(ptr as *mut _, self)
+    }
+    #[inline(always)]
+    unsafe fn splat(x: u64) -> Self {
+        _mm512_set1_epi64(x as i64)
+    }
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        _mm512_add_epi64(self, other)
+    }
+    #[inline(always)]
+    unsafe fn sub(self, other: Self) -> Self {
+        _mm512_sub_epi64(self, other)
+    }
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        _mm512_and_si512(self, other)
+    }
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        _mm512_or_si512(self, other)
+    }
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        _mm512_xor_si512(self, other)
+    }
+    #[inline(always)]
+    unsafe fn shl(self, n: u32) -> Self {
+        _mm512_sll_epi64(self, _mm_cvtsi32_si128(n as i32))
+    }
+    #[inline(always)]
+    unsafe fn shr(self, n: u32) -> Self {
+        _mm512_srl_epi64(self, _mm_cvtsi32_si128(n as i32))
+    }
+    #[inline(always)]
+    unsafe fn mul(self, c: u32) -> Self {
+        // _mm512_mullo_epi64 needs AVX-512DQ, so do the same as for AVX2.
+        let c = Self::splat(c as u64);
+        let low = _mm512_mul_epu32(self, c);
+        let high = _mm512_mul_epu32(self.shr(32), c);
+        low.add(high.shl(32))
+    }
+}
+
+/// An operation on a single run of limbs.
+trait UnaryOp {
+    unsafe fn apply<L: Lanes>(&self, x: L) -> L;
+}
+
+/// An operation combining a run of target limbs with a run of source limbs.
+trait BinaryOp {
+    unsafe fn apply<L: Lanes>(&self, target: L, source: L) -> L;
+}
+
+#[inline(always)]
+unsafe fn map<L: Lanes, O: UnaryOp>(limbs: &mut [u64], op: &O) {
+    let mut chunks = limbs.chunks_exact_mut(L::WIDTH);
+    for chunk in &mut chunks {
+        op.apply(L::load(chunk.as_ptr())).store(chunk.as_mut_ptr());
+    }
+    for limb in chunks.into_remainder() {
+        *limb = op.apply(*limb);
+    }
+}
+
+#[inline(always)]
+unsafe fn zip<L: Lanes, O: BinaryOp>(target: &mut [u64], source: &[u64], op: &O) {
+    let mut target_chunks = target.chunks_exact_mut(L::WIDTH);
+    let mut source_chunks = source.chunks_exact(L::WIDTH);
+    for (t, s) in (&mut target_chunks).zip(&mut source_chunks) {
+        op.apply(L::load(t.as_ptr()), L::load(s.as_ptr()))
+            .store(t.as_mut_ptr());
+    }
+    let source_remainder = source_chunks.remainder();
+    for (t, s) in target_chunks
+        .into_remainder()
+        .iter_mut()
+        .zip(source_remainder)
+    {
+        *t = op.apply(*t, *s);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn map_avx2<O: UnaryOp>(limbs: &mut [u64], op: &O) {
+    map::<__m256i, O>(limbs, op)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+unsafe fn map_avx512<O: UnaryOp>(limbs: &mut [u64], op: &O) {
+    map::<__m512i, O>(limbs, op)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn zip_avx2<O: BinaryOp>(target: &mut [u64], source: &[u64], op: &O) {
+    zip::<__m256i, O>(target, source, op)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+unsafe fn zip_avx512<O: BinaryOp>(target: &mut [u64], source: &[u64], op: &O) {
+    zip::<__m512i, O>(target, source, op)
+}
+
+/// The instruction sets we have implementations for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Level {
+    Scalar,
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+    #[cfg(target_arch = "x86_64")]
+    Avx512,
+}
+
+/// The best instruction set supported by the CPU. The feature detection is cached by std.
+#[inline]
+fn level() -> Level {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx512f") {
+            return Level::Avx512;
+        }
+        if is_x86_feature_detected!("avx2") {
+            return Level::Avx2;
+        }
+    }
+    Level::Scalar
+}
+
+fn map_at<O: UnaryOp>(level: Level, limbs: &mut [u64], op: &O) {
+    unsafe {
+        match level {
+            Level::Scalar => map::<u64, O>(limbs, op),
+            #[cfg(target_arch = "x86_64")]
+            Level::Avx2 => map_avx2(limbs, op),
+            #[cfg(target_arch = "x86_64")]
+            Level::Avx512 => map_avx512(limbs, op),
+        }
+    }
+}
+
+fn zip_at<O: BinaryOp>(level: Level, target: &mut [u64], source: &[u64], op: &O) {
+    assert_eq!(target.len(), source.len());
+    unsafe {
+        match level {
+            Level::Scalar => zip::<u64, O>(target, source, op),
+            #[cfg(target_arch = "x86_64")]
+            Level::Avx2 => zip_avx2(target, source, op),
+            #[cfg(target_arch = "x86_64")]
+            Level::Avx512 => zip_avx512(target, source, op),
+        }
+    }
+}
+
+/// Adds `c` times the source to the target at p = 2.
+struct Add2;
+
+impl BinaryOp for Add2 {
+    #[inline(always)]
+    unsafe fn apply<L: Lanes>(&self, target: L, source: L) -> L {
+        target.xor(source)
+    }
+}
+
+/// Adds `c` times the source to the target at an odd prime, without reducing.
+struct AddOdd {
+    c: u32,
+}
+
+impl BinaryOp for AddOdd {
+    #[inline(always)]
+    unsafe fn apply<L: Lanes>(&self, target: L, source: L) -> L {
+        if self.c == 1 {
+            target.add(source)
+        } else {
+            target.add(source.mul(self.c))
+        }
+    }
+}
+
+/// Multiplies every entry by `c`, without reducing.
+struct Scale {
+    c: u32,
+}
+
+impl UnaryOp for Scale {
+    #[inline(always)]
+    unsafe fn apply<L: Lanes>(&self, x: L) -> L {
+        x.mul(self.c)
+    }
+}
+
+/// Reduces entries at most 6 mod 3.
+struct Reduce3;
+
+impl UnaryOp for Reduce3 {
+    #[inline(always)]
+    unsafe fn apply<L: Lanes>(&self, x: L) -> L {
+        let top_bit_set_in_each_field = L::splat(0x4924924924924924);
+        let limb_2 = x
+            .and(top_bit_set_in_each_field)
+            .shr(2)
+            .add(x.and(top_bit_set_in_each_field.xor(L::splat(!0))));
+        let limb_3s = limb_2.and(limb_2.shr(1));
+        limb_2.xor(limb_3s.or(limb_3s.shl(1)))
+    }
+}
+
+/// Reduces entries at most 20 mod 5.
+struct Reduce5;
+
+impl UnaryOp for Reduce5 {
+    #[inline(always)]
+    unsafe fn apply<L: Lanes>(&self, x: L) -> L {
+        let bottom_bit = 0x84210842108421u64;
+        let bottom_two_bits = bottom_bit | (bottom_bit << 1);
+        let bottom_three_bits = bottom_bit | (bottom_two_bits << 1);
+        let a = x.shr(2).and(L::splat(bottom_three_bits));
+        let b = x.and(L::splat(bottom_two_bits));
+        let m = L::splat(bottom_bit << 3).sub(a).add(b);
+        let c = m.shr(3).and(L::splat(bottom_bit));
+        let d = m.and(L::splat(bottom_three_bits));
+        d.add(c.or(c.shl(1))).sub(L::splat(bottom_two_bits))
+    }
+}
+
+/// Reduces entries at most p(p - 1) mod a general prime p. The even and odd entries are split
+/// into separate limbs, so that every entry has `bit_length` free bits above it. Then we subtract
+/// p 2^k from the entries that are at least that large, for k decreasing to 0. The comparison is
+/// done by adding 2^bit_length - p 2^k and looking at the carry bit.
+struct ReduceGeneric {
+    bit_length: u32,
+    even_entries: u64,
+    /// The limb with a 1 in the bottom bit of every even entry.
+    ones: u64,
+    /// The values of p 2^k to subtract, in decreasing order. Only the first `num_multiples` are
+    /// used.
+    multiples: [u64; 5],
+    num_multiples: usize,
+}
+
+impl ReduceGeneric {
+    fn new(p: ValidPrime) -> Self {
+        let bit_length = bit_length(p);
+        let mut even_entries = 0;
+        let mut ones = 0;
+        for i in (0..entries_per_64_bits(p)).step_by(2) {
+            even_entries |= ((1 << bit_length) - 1) << (i * bit_length);
+            ones |= 1 << (i * bit_length);
+        }
+        // The quotient is at most p - 1, so its largest bit is at most log_2(p - 1).
+        let max_k = 31 - (*p - 1).leading_zeros();
+        let mut multiples = [0; 5];
+        for (i, k) in (0..=max_k).rev().enumerate() {
+            multiples[i] = (*p as u64) << k;
+        }
+        Self {
+            bit_length: bit_length as u32,
+            even_entries,
+            ones,
+            multiples,
+            num_multiples: max_k as usize + 1,
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn reduce_half<L: Lanes>(&self, mut x: L) -> L {
+        let ones = L::splat(self.ones);
+        for &m in &self.multiples[..self.num_multiples] {
+            let t = x.add(L::splat(self.ones * ((1 << self.bit_length) - m)));
+            let flag = t.shr(self.bit_length).and(ones);
+            let mask = flag.shl(self.bit_length).sub(flag);
+            x = x.sub(mask.and(L::splat(self.ones * m)));
+        }
+        x
+    }
+}
+
+impl UnaryOp for ReduceGeneric {
+    #[inline(always)]
+    unsafe fn apply<L: Lanes>(&self, x: L) -> L {
+        let even_entries = L::splat(self.even_entries);
+        let even = self.reduce_half(x.and(even_entries));
+        let odd = self.reduce_half(x.shr(self.bit_length).and(even_entries));
+        even.or(odd.shl(self.bit_length))
+    }
+}
+
+fn add_limbs_at(level: Level, p: ValidPrime, target: &mut [u64], source: &[u64], c: u32) {
+    if c == 0 {
+        return;
+    }
+    if *p == 2 {
+        zip_at(level, target, source, &Add2);
+    } else {
+        zip_at(level, target, source, &AddOdd { c });
+    }
+}
+
+fn scale_limbs_at(level: Level, target: &mut [u64], c: u32) {
+    if c != 1 {
+        map_at(level, target, &Scale { c });
+    }
+}
+
+fn reduce_limbs_at(level: Level, p: ValidPrime, limbs: &mut [u64]) {
+    match *p {
+        2 => (),
+        3 => map_at(level, limbs, &Reduce3),
+        5 => map_at(level, limbs, &Reduce5),
+        _ => map_at(level, limbs, &ReduceGeneric::new(p)),
+    }
+}
+
+/// Adds `c` times `source` to `target`. At odd primes the result has to be reduced afterwards.
+pub(super) fn add_limbs(p: ValidPrime, target: &mut [u64], source: &[u64], c: u32) {
+    add_limbs_at(level(), p, target, source, c)
+}
+
+/// Multiplies `limbs` by `c`. At odd primes the result has to be reduced afterwards.
+pub(super) fn scale_limbs(limbs: &mut [u64], c: u32) {
+    scale_limbs_at(level(), limbs, c)
+}
+
+/// Reduces the entries of `limbs`, which must be at most p(p - 1), mod p.
+pub(super) fn reduce_limbs(p: ValidPrime, limbs: &mut [u64]) {
+    reduce_limbs_at(level(), p, limbs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::{FpVector, FpVectorT};
+    use rand::Rng;
+    use rstest::rstest;
+
+    fn levels() -> Vec<Level> {
+        let mut levels = vec![Level::Scalar];
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                levels.push(Level::Avx2);
+            }
+            if is_x86_feature_detected!("avx512f") {
+                levels.push(Level::Avx512);
+            }
+        }
+        levels
+    }
+
+    /// Random limbs whose entries are less than `max`.
+    fn random_limbs(p: ValidPrime, max: u32, num_limbs: usize) -> Vec<u64> {
+        let mut rng = rand::thread_rng();
+        let entries: Vec<u32> = (0..num_limbs * entries_per_64_bits(p))
+            .map(|_| rng.gen_range(0, max))
+            .collect();
+        let mut v = FpVector::new(p, entries.len());
+        v.pack(&entries);
+        v.limbs().clone()
+    }
+
+    fn entries(p: ValidPrime, limbs: &[u64]) -> Vec<u64> {
+        let bit_length = bit_length(p);
+        let mask = (1 << bit_length) - 1;
+        limbs
+            .iter()
+            .flat_map(|&limb| {
+                (0..entries_per_64_bits(p)).map(move |i| (limb >> (i * bit_length)) & mask)
+            })
+            .collect()
+    }
+
+    #[rstest(p, case(2), case(3), case(5), case(7), case(13), case(17))]
+    fn test_simd_levels(p: u32) {
+        let p = ValidPrime::new(p);
+        crate::vector::initialize_limb_bit_index_table(p);
+        // Lengths that are not multiples of the register widths exercise the remainder.
+        for &num_limbs in &[0, 1, 3, 4, 7, 8, 13, 64] {
+            let target = random_limbs(p, *p, num_limbs);
+            let source = random_limbs(p, *p, num_limbs);
+            let unreduced = random_limbs(p, std::cmp::max(*p * (*p - 1), 1) + 1, num_limbs);
+            for &level in &levels() {
+                for c in 0..*p {
+                    let mut result = target.clone();
+                    add_limbs_at(level, p, &mut result, &source, c);
+                    reduce_limbs_at(level, p, &mut result);
+                    let expected: Vec<u64> = entries(p, &target)
+                        .iter()
+                        .zip(entries(p, &source))
+                        .map(|(&t, s)| (t + c as u64 * s) % *p as u64)
+                        .collect();
+                    assert_eq!(entries(p, &result), expected, "add {:?} {}", level, c);
+
+                    let mut result = target.clone();
+                    scale_limbs_at(level, &mut result, c);
+                    reduce_limbs_at(level, p, &mut result);
+                    let expected: Vec<u64> = entries(p, &target)
+                        .iter()
+                        .map(|&t| (t * c as u64) % *p as u64)
+                        .collect();
+                    assert_eq!(entries(p, &result), expected, "scale {:?} {}", level, c);
+                }
+
+                let mut result = unreduced.clone();
+                reduce_limbs_at(level, p, &mut result);
+                let expected: Vec<u64> = entries(p, &unreduced)
+                    .iter()
+                    .map(|&x| x % *p as u64)
+                    .collect();
+                assert_eq!(entries(p, &result), expected, "reduce {:?}", level);
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+#ifndef EXT_H
+#define EXT_H
+
+/* This file is generated by cbindgen from src/capi.rs. Regenerate it with `UPDATE_HEADER=1 cargo test --test capi`. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The status code returned by every function of the C API.
+ */
+typedef enum ExtError {
+  EXT_ERROR_OK = 0,
+  /**
+   * A pointer argument is null.
+   */
+  EXT_ERROR_NULL_POINTER,
+  /**
+   * A string argument is not valid UTF-8.
+   */
+  EXT_ERROR_INVALID_UTF8,
+  /**
+   * The module specification is invalid.
+   */
+  EXT_ERROR_INVALID_MODULE,
+  /**
+   * An argument is out of range, e.g. an index or the length of a buffer.
+   */
+  EXT_ERROR_INVALID_ARGUMENT,
+  /**
+   * The requested bidegree or product has not been computed.
+   */
+  EXT_ERROR_NOT_COMPUTED,
+  /**
+   * Reading or writing a save file failed.
+   */
+  EXT_ERROR_IO,
+  /**
+   * The resolver panicked. The resolution should not be used afterwards.
+   */
+  EXT_ERROR_PANIC,
+} ExtError;
+
+/**
+ * A resolution together with the products computed so far, keyed by the name of the product
+ * and the bidegree of the multiplicand.
+ */
+typedef struct ExtResolution ExtResolution;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Returns a description of the last error on this thread. The string is owned by the library
+ * and remains valid until the next failing call on this thread.
+ */
+const char *ext_last_error(void);
+
+/**
+ * Constructs a resolution of the module specified by the JSON string `json` over `algebra`,
+ * which is either `adem` or `milnor`, and stores it in `*out`. The resolution must be freed
+ * with `ext_resolution_free`.
+ *
+ * # Safety
+ * `json` and `algebra` must be null-terminated strings and `out` must be valid for writes.
+ */
+enum ExtError ext_resolution_new(const char *json, const char *algebra, struct ExtResolution **out);
+
+/**
+ * Loads a resolution saved by `ext_resolution_save`. The module must be specified in the same
+ * way as when the resolution was saved. Products and self maps in the specification are not
+ * loaded, and have to be added again with `ext_add_product`.
+ *
+ * # Safety
+ * `json`, `algebra` and `path` must be null-terminated strings and `out` must be valid for
+ * writes.
+ */
+enum ExtError ext_resolution_load(const char *json,
+                                  const char *algebra,
+                                  const char *path,
+                                  struct ExtResolution **out);
+
+/**
+ * Frees a resolution. This does nothing if `resolution` is null.
+ *
+ * # Safety
+ * `resolution` must be null or have been returned by this library, and must not be used
+ * afterwards.
+ */
+void ext_resolution_free(struct ExtResolution *resolution);
+
+/**
+ * Saves the resolution to the file at `path`.
+ *
+ * # Safety
+ * `resolution` must be a valid resolution and `path` a null-terminated string.
+ */
+enum ExtError ext_resolution_save(const struct ExtResolution *resolution, const char *path);
+
+/**
+ * Resolves through homological degree `s` and internal degree `t`.
+ *
+ * # Safety
+ * `resolution` must be a valid resolution.
+ */
+enum ExtError ext_resolve_through_bidegree(struct ExtResolution *resolution, uint32_t s, int32_t t);
+
+/**
+ * Stores the dimension of $\mathrm{Ext}^{s, t}$ in `*out`. This fails with
+ * `EXT_ERROR_NOT_COMPUTED` if the bidegree has not been resolved.
+ *
+ * # Safety
+ * `resolution` must be a valid resolution and `out` must be valid for writes.
+ */
+enum ExtError ext_number_of_gens_in_bidegree(const struct ExtResolution *resolution,
+                                             uint32_t s,
+                                             int32_t t,
+                                             size_t *out);
+
+/**
+ * Adds the product by the class in $\mathrm{Ext}^{s, t}$ of the unit whose coordinates are
+ * `class_data[0 .. class_len]`. The products with everything computed so far are computed
+ * immediately, and the rest are computed as the resolution is extended. The products can then
+ * be queried with `ext_product` under the name `name`. This fails with
+ * `EXT_ERROR_INVALID_ARGUMENT` if `class_len` is not the dimension of Ext of the unit in this
+ * bidegree, or if a coordinate is not less than p.
+ *
+ * # Safety
+ * `resolution` must be a valid resolution, `class_data` must point to `class_len` integers and
+ * `name` must be a null-terminated string.
+ */
+enum ExtError ext_add_product(struct ExtResolution *resolution,
+                              uint32_t s,
+                              int32_t t,
+                              const uint32_t *class_data,
+                              size_t class_len,
+                              const char *name);
+
+/**
+ * Writes the product of the `idx`th generator of $\mathrm{Ext}^{s, t}$ by the product `name`
+ * to `result`, whose length `result_len` must be the dimension of Ext in the bidegree of the
+ * product. The product may be one added by `ext_add_product` or one of the default filtration
+ * one products such as `h_0`.
+ *
+ * # Safety
+ * `resolution` must be a valid resolution, `name` must be a null-terminated string and
+ * `result` must be valid for `result_len` writes.
+ */
+enum ExtError ext_product(const struct ExtResolution *resolution,
+                          const char *name,
+                          uint32_t s,
+                          int32_t t,
+                          size_t idx,
+                          uint32_t *result,
+                          size_t result_len);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* EXT_H */
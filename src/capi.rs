@@ -0,0 +1,388 @@
+//! A C interface to the resolver, for driving resolutions from other languages. A resolution is
+//! passed around as an opaque `ExtResolution` pointer, and every function returns an `ExtError`.
+//! If this is not `EXT_ERROR_OK`, `ext_last_error` describes what went wrong.
+//!
+//! The header `include/ext.h` is generated from this file by running
+//! `cbindgen --config cbindgen.toml --crate ext --output include/ext.h`, and `bin/test.sh` checks
+//! that it is up to date.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::rc::Rc;
+
+use saveload::{Load, Save};
+use serde_json::Value;
+
+use crate::chain_complex::ChainComplex;
+use crate::resolution::Resolution;
+use crate::utils::{construct_from_json, AlgebraicObjectsBundle};
+
+/// The status code returned by every function of the C API.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExtError {
+    Ok = 0,
+    /// A pointer argument is null.
+    NullPointer,
+    /// A string argument is not valid UTF-8.
+    InvalidUtf8,
+    /// The module specification is invalid.
+    InvalidModule,
+    /// An argument is out of range, e.g. an index or the length of a buffer.
+    InvalidArgument,
+    /// The requested bidegree or product has not been computed.
+    NotComputed,
+    /// Reading or writing a save file failed.
+    Io,
+    /// The resolver panicked. The resolution should not be used afterwards.
+    Panic,
+}
+
+/// A resolution together with the products computed so far, keyed by the name of the product
+/// and the bidegree of the multiplicand.
+pub struct ExtResolution {
+    bundle: AlgebraicObjectsBundle,
+    products: Rc<RefCell<HashMap<(String, u32, i32), Vec<Vec<u32>>>>>,
+}
+
+impl ExtResolution {
+    fn new(bundle: AlgebraicObjectsBundle) -> Self {
+        let products = Rc::new(RefCell::new(HashMap::new()));
+        let products_ = Rc::clone(&products);
+        bundle.resolution.write().add_structline = Some(Box::new(
+            move |name: &str,
+                  source_s: u32,
+                  source_t: i32,
+                  _target_s: u32,
+                  _target_t: i32,
+                  _left: bool,
+                  result: Vec<Vec<u32>>| {
+                products_
+                    .borrow_mut()
+                    .insert((name.to_string(), source_s, source_t), result);
+            },
+        ));
+        Self { bundle, products }
+    }
+
+    fn number_of_gens_in_bidegree(&self, s: u32, t: i32) -> Result<usize, Failure> {
+        let resolution = self.bundle.resolution.read();
+        if t < resolution.min_degree() {
+            Ok(0)
+        } else if resolution.inner.has_computed_bidegree(s, t) {
+            Ok(resolution.inner.number_of_gens_in_bidegree(s, t))
+        } else {
+            Err(Failure(
+                ExtError::NotComputed,
+                format!("Bidegree ({}, {}) has not been computed", s, t),
+            ))
+        }
+    }
+}
+
+struct Failure(ExtError, String);
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Runs `f`, recording the error message of a failure and turning panics into
+/// `ExtError::Panic`.
+fn run(f: impl FnOnce() -> Result<(), Failure>) -> ExtError {
+    let Failure(code, message) = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return ExtError::Ok,
+        Ok(Err(failure)) => failure,
+        Err(payload) => {
+            let message = if let Some(s) = payload.downcast_ref::<&str>() {
+                (*s).to_string()
+            } else if let Some(s) = payload.downcast_ref::<String>() {
+                s.clone()
+            } else {
+                "Unknown panic".to_string()
+            };
+            Failure(ExtError::Panic, message)
+        }
+    };
+    let message = CString::new(message.replace('\0', "")).unwrap();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = message);
+    code
+}
+
+fn non_null<T>(ptr: *const T, name: &str) -> Result<(), Failure> {
+    if ptr.is_null() {
+        Err(Failure(ExtError::NullPointer, format!("{} is null", name)))
+    } else {
+        Ok(())
+    }
+}
+
+unsafe fn to_str<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, Failure> {
+    non_null(ptr, name)?;
+    CStr::from_ptr(ptr).to_str().map_err(|_| {
+        Failure(
+            ExtError::InvalidUtf8,
+            format!("{} is not valid UTF-8", name),
+        )
+    })
+}
+
+unsafe fn construct(
+    json: *const c_char,
+    algebra: *const c_char,
+) -> Result<AlgebraicObjectsBundle, Failure> {
+    let json: Value = serde_json::from_str(to_str(json, "json")?)
+        .map_err(|err| Failure(ExtError::InvalidModule, err.to_string()))?;
+    let algebra = to_str(algebra, "algebra")?;
+    construct_from_json(json, algebra.to_string())
+        .map_err(|err| Failure(ExtError::InvalidModule, err.inner().to_string()))
+}
+
+fn io_error(err: std::io::Error) -> Failure {
+    Failure(ExtError::Io, err.to_string())
+}
+
+/// Returns a description of the last error on this thread. The string is owned by the library
+/// and remains valid until the next failing call on this thread.
+#[no_mangle]
+pub extern "C" fn ext_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| last_error.borrow().as_ptr())
+}
+
+/// Constructs a resolution of the module specified by the JSON string `json` over `algebra`,
+/// which is either `adem` or `milnor`, and stores it in `*out`. The resolution must be freed
+/// with `ext_resolution_free`.
+///
+/// # Safety
+/// `json` and `algebra` must be null-terminated strings and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ext_resolution_new(
+    json: *const c_char,
+    algebra: *const c_char,
+    out: *mut *mut ExtResolution,
+) -> ExtError {
+    run(|| {
+        non_null(out, "out")?;
+        let bundle = construct(json, algebra)?;
+        *out = Box::into_raw(Box::new(ExtResolution::new(bundle)));
+        Ok(())
+    })
+}
+
+/// Loads a resolution saved by `ext_resolution_save`. The module must be specified in the same
+/// way as when the resolution was saved. Products and self maps in the specification are not
+/// loaded, and have to be added again with `ext_add_product`.
+///
+/// # Safety
+/// `json`, `algebra` and `path` must be null-terminated strings and `out` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn ext_resolution_load(
+    json: *const c_char,
+    algebra: *const c_char,
+    path: *const c_char,
+    out: *mut *mut ExtResolution,
+) -> ExtError {
+    run(|| {
+        non_null(out, "out")?;
+        let bundle = construct(json, algebra)?;
+        let file = File::open(to_str(path, "path")?).map_err(io_error)?;
+        let resolution =
+            Resolution::load(&mut BufReader::new(file), &bundle.chain_complex).map_err(io_error)?;
+        *bundle.resolution.write() = resolution;
+        *out = Box::into_raw(Box::new(ExtResolution::new(bundle)));
+        Ok(())
+    })
+}
+
+/// Frees a resolution. This does nothing if `resolution` is null.
+///
+/// # Safety
+/// `resolution` must be null or have been returned by this library, and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ext_resolution_free(resolution: *mut ExtResolution) {
+    if !resolution.is_null() {
+        drop(Box::from_raw(resolution));
+    }
+}
+
+/// Saves the resolution to the file at `path`.
+///
+/// # Safety
+/// `resolution` must be a valid resolution and `path` a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ext_resolution_save(
+    resolution: *const ExtResolution,
+    path: *const c_char,
+) -> ExtError {
+    run(|| {
+        non_null(resolution, "resolution")?;
+        let file = File::create(to_str(path, "path")?).map_err(io_error)?;
+        (*resolution)
+            .bundle
+            .resolution
+            .read()
+            .save(&mut BufWriter::new(file))
+            .map_err(io_error)
+    })
+}
+
+/// Resolves through homological degree `s` and internal degree `t`.
+///
+/// # Safety
+/// `resolution` must be a valid resolution.
+#[no_mangle]
+pub unsafe extern "C" fn ext_resolve_through_bidegree(
+    resolution: *mut ExtResolution,
+    s: u32,
+    t: i32,
+) -> ExtError {
+    run(|| {
+        non_null(resolution, "resolution")?;
+        (*resolution)
+            .bundle
+            .resolution
+            .read()
+            .resolve_through_bidegree(s, t);
+        Ok(())
+    })
+}
+
+/// Stores the dimension of $\mathrm{Ext}^{s, t}$ in `*out`. This fails with
+/// `EXT_ERROR_NOT_COMPUTED` if the bidegree has not been resolved.
+///
+/// # Safety
+/// `resolution` must be a valid resolution and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ext_number_of_gens_in_bidegree(
+    resolution: *const ExtResolution,
+    s: u32,
+    t: i32,
+    out: *mut usize,
+) -> ExtError {
+    run(|| {
+        non_null(resolution, "resolution")?;
+        non_null(out, "out")?;
+        *out = (*resolution).number_of_gens_in_bidegree(s, t)?;
+        Ok(())
+    })
+}
+
+/// Adds the product by the class in $\mathrm{Ext}^{s, t}$ of the unit whose coordinates are
+/// `class_data[0 .. class_len]`. The products with everything computed so far are computed
+/// immediately, and the rest are computed as the resolution is extended. The products can then
+/// be queried with `ext_product` under the name `name`. This fails with
+/// `EXT_ERROR_INVALID_ARGUMENT` if `class_len` is not the dimension of Ext of the unit in this
+/// bidegree, or if a coordinate is not less than p.
+///
+/// # Safety
+/// `resolution` must be a valid resolution, `class_data` must point to `class_len` integers and
+/// `name` must be a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ext_add_product(
+    resolution: *mut ExtResolution,
+    s: u32,
+    t: i32,
+    class_data: *const u32,
+    class_len: usize,
+    name: *const c_char,
+) -> ExtError {
+    run(|| {
+        non_null(resolution, "resolution")?;
+        non_null(class_data, "class_data")?;
+        let name = to_str(name, "name")?;
+        let class = std::slice::from_raw_parts(class_data, class_len).to_vec();
+
+        let bundle = &(*resolution).bundle;
+        bundle.resolution.write().construct_unit_resolution();
+        let (dimension, p) = {
+            let resolution = bundle.resolution.read();
+            (resolution.unit_ext_dimension(s, t), *resolution.prime())
+        };
+        if class.len() != dimension {
+            return Err(Failure(
+                ExtError::InvalidArgument,
+                format!(
+                    "The class has length {}, but Ext^({}, {}) of the unit has dimension {}",
+                    class.len(),
+                    s,
+                    t,
+                    dimension
+                ),
+            ));
+        }
+        if class.iter().any(|&x| x >= p) {
+            return Err(Failure(
+                ExtError::InvalidArgument,
+                format!("The coordinates of the class must be less than {}", p),
+            ));
+        }
+        if !bundle.resolution.write().add_product(s, t, class, name) {
+            return Err(Failure(
+                ExtError::InvalidArgument,
+                format!("There is already a product named {}", name),
+            ));
+        }
+        bundle.resolution.read().catch_up_products();
+        Ok(())
+    })
+}
+
+/// Writes the product of the `idx`th generator of $\mathrm{Ext}^{s, t}$ by the product `name`
+/// to `result`, whose length `result_len` must be the dimension of Ext in the bidegree of the
+/// product. The product may be one added by `ext_add_product` or one of the default filtration
+/// one products such as `h_0`.
+///
+/// # Safety
+/// `resolution` must be a valid resolution, `name` must be a null-terminated string and
+/// `result` must be valid for `result_len` writes.
+#[no_mangle]
+pub unsafe extern "C" fn ext_product(
+    resolution: *const ExtResolution,
+    name: *const c_char,
+    s: u32,
+    t: i32,
+    idx: usize,
+    result: *mut u32,
+    result_len: usize,
+) -> ExtError {
+    run(|| {
+        non_null(resolution, "resolution")?;
+        non_null(result, "result")?;
+        let name = to_str(name, "name")?;
+
+        let products = (*resolution).products.borrow();
+        let products = products.get(&(name.to_string(), s, t)).ok_or_else(|| {
+            Failure(
+                ExtError::NotComputed,
+                format!(
+                    "The product {} on ({}, {}) has not been computed",
+                    name, s, t
+                ),
+            )
+        })?;
+        let product = products.get(idx).ok_or_else(|| {
+            Failure(
+                ExtError::InvalidArgument,
+                format!("Ext^({}, {}) only has {} generators", s, t, products.len()),
+            )
+        })?;
+        if product.len() != result_len {
+            return Err(Failure(
+                ExtError::InvalidArgument,
+                format!(
+                    "The product has length {}, not {}",
+                    product.len(),
+                    result_len
+                ),
+            ));
+        }
+        std::slice::from_raw_parts_mut(result, result_len).copy_from_slice(product);
+        Ok(())
+    })
+}
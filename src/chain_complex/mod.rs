@@ -5,7 +5,7 @@ mod tensor_product_chain_complex;
 use crate::algebra::Algebra;
 use crate::module::homomorphism::{FreeModuleHomomorphism, ModuleHomomorphism};
 use crate::module::{FDModule, FiniteModule, FreeModule, Module};
-use crate::CCC;
+use crate::{CCC, FDChainComplex};
 use bivec::BiVec;
 use fp::matrix::Subquotient;
 use fp::prime::ValidPrime;
@@ -252,3 +252,14 @@ impl UnitChainComplex for CCC {
         FiniteChainComplex::ccdz(unit_module)
     }
 }
+
+impl<A: Algebra> UnitChainComplex for FDChainComplex<A> {
+    fn unit_chain_complex(algebra: Arc<Self::Algebra>) -> Self {
+        let unit_module = Arc::new(FDModule::new(
+            algebra,
+            String::from("unit"),
+            BiVec::from_vec(0, vec![1]),
+        ));
+        FiniteChainComplex::ccdz(unit_module)
+    }
+}
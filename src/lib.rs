@@ -13,7 +13,9 @@
 pub use algebra::combinatorics;
 pub use algebra::module::block_structure;
 pub use algebra;
+pub use algebra::bruner;
 pub use algebra::change_of_basis;
+pub use algebra::margolis;
 pub use algebra::steenrod_parser;
 pub use algebra::steenrod_evaluator;
 pub use algebra::module;
@@ -25,8 +27,16 @@ pub mod resolution_homomorphism;
 pub mod yoneda;
 
 use crate::chain_complex::FiniteChainComplex;
-use crate::module::FiniteModule;
-use crate::module::homomorphism::FiniteModuleHomomorphism;
+use crate::module::{FDModule, FiniteModule};
+use crate::module::homomorphism::{BoundedModuleHomomorphism, FiniteModuleHomomorphism};
 pub type CCC = FiniteChainComplex<FiniteModule, FiniteModuleHomomorphism<FiniteModule>>;
+/// A chain complex of finite dimensional modules over an arbitrary algebra, e.g. a group algebra.
+pub type FDChainComplex<A> = FiniteChainComplex<FDModule<A>, BoundedModuleHomomorphism<FDModule<A>, FDModule<A>>>;
 
 pub mod utils;
+pub mod capi;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -31,9 +31,58 @@ fn main() -> error::Result<()> {
         ("yoneda", Some(_)) => {
             run::yoneda(&get_config(matches))?
         }
+        ("export", Some(_)) => {
+            // Print without formatting so that the output can be redirected to a file.
+            println!("{}", run::export(&get_config(matches))?);
+            return Ok(());
+        }
+        ("presentation", Some(_)) => {
+            println!("{}", run::presentation(&get_config(matches))?);
+            return Ok(());
+        }
+        ("bruner", Some(sub_matches)) => {
+            let resolution = sub_matches.is_present("resolution");
+            println!("{}", run::bruner(&get_config(matches), resolution)?);
+            return Ok(());
+        }
+        ("margolis", Some(_)) => {
+            run::margolis(&get_config(matches))?
+        }
+        ("serve", Some(sub_matches)) => {
+            run::serve(
+                &get_config(matches.clone()),
+                sub_matches.value_of("websocket"),
+                sub_matches.value_of("save_dir"),
+            )?;
+            return Ok(());
+        }
         ("steenrod", Some(_)) => {
             run::steenrod()?
         }
+        ("calc", Some(sub_matches)) => {
+            let p = if sub_matches.is_present("prime") {
+                value_t!(sub_matches, "prime", u32)
+                    .unwrap_or_else(|e| panic!("Invalid prime: {}", e))
+            } else {
+                2
+            };
+            let module_config = sub_matches.value_of("module").map(|module| Config {
+                module_file_name: module.to_string(),
+                algebra_name: "adem".to_string(),
+                max_degree: value_t!(sub_matches, "degree", i32)
+                    .unwrap_or_else(|e| panic!("Invalid degree: {}", e)),
+                ..get_config(matches.clone())
+            });
+            match run::calc(p, module_config.as_ref(), sub_matches.value_of("expression")) {
+                Ok(result) => println!("{}", result),
+                // Print errors as the REPL does, without the backtrace.
+                Err(err) => {
+                    eprintln!("{}", err.inner());
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
         (_, _) => {
             run::resolve(&get_config(matches))?
         }
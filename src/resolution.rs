@@ -6,11 +6,12 @@ use std::collections::HashSet;
 use fp::prime::ValidPrime;
 use fp::vector::{FpVector, FpVectorT};
 use fp::matrix::{Matrix, Subspace, AugmentedMatrix3};
-use crate::algebra::Algebra;
-use crate::module::{Module, FreeModule};
+use crate::algebra::{Algebra, MilnorAlgebra, SteenrodAlgebraT};
+use crate::bruner::element_to_bruner;
+use crate::module::{Module, FreeModule, FPModule, ZeroModule};
 use once::{OnceVec, OnceBiVec};
-use crate::module::homomorphism::{ModuleHomomorphism, FreeModuleHomomorphism};
-use crate::chain_complex::{ChainComplex, AugmentedChainComplex, UnitChainComplex, FreeChainComplex};
+use crate::module::homomorphism::{ModuleHomomorphism, FreeModuleHomomorphism, GenericZeroHomomorphism};
+use crate::chain_complex::{ChainComplex, AugmentedChainComplex, UnitChainComplex, FreeChainComplex, FiniteChainComplex};
 use crate::resolution_homomorphism::{ResolutionHomomorphism, ResolutionHomomorphismToUnit};
 
 #[cfg(feature = "concurrent")]
@@ -269,6 +270,32 @@ impl<CC : ChainComplex> ResolutionInner<CC> {
         current_differential.add_generators_from_matrix_rows(&differential_lock, t, &*matrix.segment(1, 1));
         matrix.clear_row_slice();
 
+        if !self.is_connected() {
+            // Each new generator brings along its translates under the degree 0 part of the
+            // algebra, which are not rows of `matrix`. So we recompute the kernel and
+            // quasi-inverses from the matrix of the full map instead.
+            drop(matrix);
+            let source_dimension = source.dimension(t);
+            let mut matrix = AugmentedMatrix3::new(p, source_dimension, &[target_cc_dimension, target_res_dimension, source_dimension]);
+            current_chain_map.get_matrix(&mut matrix.segment(0,0), t);
+            current_differential.get_matrix(&mut matrix.segment(1,1), t);
+            matrix.segment(2,2).add_identity(source_dimension, 0, 0);
+            matrix.initialize_pivots();
+            matrix.row_reduce();
+
+            let temp = matrix.start[2];
+            let new_kernel = matrix.compute_kernel(temp);
+            let (cm_qi, res_qi) = matrix.compute_quasi_inverses();
+
+            current_chain_map.set_quasi_inverse(&chain_map_lock, t, cm_qi);
+            current_chain_map.set_kernel(&chain_map_lock, t, Subspace::new(p, 0, 0));
+            current_differential.set_quasi_inverse(&differential_lock, t, res_qi);
+            current_differential.set_kernel(&differential_lock, t, Subspace::new(p, 0, 0));
+
+            *old_kernel = Some(self.kernel_generators(s, t, new_kernel));
+            return;
+        }
+
         // Record the quasi-inverses for future use.
         // The part of the matrix that contains interesting information is occupied_rows x (target_dimension + source_dimension + kernel_size).
         let image_rows = first_new_row + num_new_gens;
@@ -498,6 +525,47 @@ impl<CC : ChainComplex> ResolutionInner<CC> {
 
 
 
+    /// Whether the algebra is connected, i.e. its degree 0 part is spanned by the unit. If this is
+    /// not the case, we assume the algebra is concentrated in degree 0, e.g. a group algebra.
+    fn is_connected(&self) -> bool {
+        self.algebra().dimension(0, i32::MAX) == 1
+    }
+
+    /// Given the kernel $K$ of $X_{s, t} \to X_{s - 1, t} \oplus C_{s, t}$, returns a subspace of
+    /// $K$ that maps isomorphically onto $K/IK$, where $I$ is the augmentation ideal of the degree
+    /// 0 part of the algebra. Adding generators that hit this subspace gives a minimal resolution
+    /// when the degree 0 part of the algebra is local, e.g. the group algebra of a $p$-group.
+    fn kernel_generators(&self, s : u32, t : i32, kernel : Subspace) -> Subspace {
+        let p = self.prime();
+        let algebra = self.algebra();
+        let module = self.module(s);
+        let dimension = kernel.columns();
+        let basis = kernel.basis();
+
+        // The augmentation ideal is spanned by a - e(a) for a running over a basis.
+        let mut decomposables = Subspace::new(p, basis.len() + 1, dimension);
+        decomposables.add_vectors(basis.iter().flat_map(|v| {
+            (0 .. algebra.dimension(0, t)).map(|op_idx| {
+                let mut result = FpVector::new(p, dimension);
+                module.act(&mut result, 1, 0, op_idx, t, v);
+                result.add(v, (*p - algebra.augmentation(op_idx) % *p) % *p);
+                result
+            }).collect::<Vec<_>>()
+        }));
+
+        let mut generators = Vec::new();
+        for v in basis {
+            if !decomposables.contains(v) {
+                decomposables.add_vector(v);
+                generators.push(v.clone());
+            }
+        }
+
+        let mut result = Subspace::new(p, generators.len() + 1, dimension);
+        result.add_vectors(generators.into_iter());
+        result
+    }
+
     pub fn cocycle_string(&self, hom_deg : u32, int_deg : i32, idx : usize) -> String {
         let p = self.prime();
         let d = self.differential(hom_deg);
@@ -522,6 +590,62 @@ impl<CC : ChainComplex> ResolutionInner<CC> {
     pub fn prime(&self) -> ValidPrime {
         self.complex.prime()
     }
+
+    /// Returns the presentation of $H_0$ of the complex whose generators are the generators of
+    /// $X_0$ and whose relations are the images of the generators of $X_1$ in degree at most
+    /// `max_t`. This is a minimal presentation and the resolution must have been computed through
+    /// bidegree $(1, \text{max\_t})$.
+    ///
+    /// A generator is named after its image in $C_0$ if that is a basis element, and `x{t}_{i}`
+    /// otherwise.
+    pub fn minimal_presentation(&self, max_t : i32) -> FPModule<CC::Algebra> {
+        let min_degree = self.min_degree();
+        let module = self.complex.module(0);
+        let chain_map = self.chain_map(0);
+        let result = FPModule::new(self.algebra(), module.name(), min_degree);
+
+        for t in min_degree ..= max_t {
+            let names = (0 .. self.number_of_gens_in_bidegree(0, t)).map(|i| {
+                let output = chain_map.output(t, i);
+                let mut terms = output.iter().enumerate().filter(|&(_, c)| c != 0);
+                match (terms.next(), terms.next()) {
+                    (Some((idx, 1)), None) => module.basis_element_to_string(t, idx),
+                    _ => format!("x{}_{}", t, i),
+                }
+            }).collect();
+            result.add_generators(t, names);
+        }
+        result.generators.extend_by_zero(max_t);
+
+        let d = self.differential(1);
+        for t in min_degree ..= max_t {
+            let num_relations = self.number_of_gens_in_bidegree(1, t);
+            let mut matrix = Matrix::new(self.prime(), num_relations, result.generators.dimension(t));
+            for i in 0 .. num_relations {
+                matrix[i].assign(d.output(t, i));
+            }
+            result.add_relations(t, &mut matrix);
+        }
+        result
+    }
+}
+
+/// Computes a minimal presentation of `module`, including the relations in degree at most
+/// `max_degree`, from a minimal resolution of the module through homological degree 1. See
+/// `ResolutionInner::minimal_presentation`.
+pub fn minimal_presentation<M : Module + ZeroModule>(module : Arc<M>, max_degree : i32) -> FPModule<M::Algebra> {
+    let complex = FiniteChainComplex::<M, GenericZeroHomomorphism<M, M>>::ccdz(module);
+    let resolution = ResolutionInner::new(Arc::new(complex));
+    let min_degree = resolution.min_degree();
+
+    resolution.complex().compute_through_bidegree(1, max_degree);
+    resolution.extend_through_degree(0, 1, min_degree, max_degree);
+    resolution.algebra().compute_basis(max_degree - min_degree);
+    for t in min_degree ..= max_degree {
+        resolution.step_resolution(0, t);
+        resolution.step_resolution(1, t);
+    }
+    resolution.minimal_presentation(max_degree)
 }
 
 impl<CC : ChainComplex> ChainComplex for ResolutionInner<CC> {
@@ -601,6 +725,19 @@ fn ptr_eq<T, S>(a : &Arc<T>, b : &Arc<S>) -> bool {
     eq
 }
 
+/// Evaluates the cocycle dual to the `idx`th generator of `module` in degree `t` on `v`. This is
+/// the coefficient of the generator in `v`, where we apply the augmentation to the operations of
+/// degree 0 acting on it.
+fn evaluate_dual_generator<A : Algebra>(module : &FreeModule<A>, t : i32, idx : usize, v : &FpVector) -> u32 {
+    let algebra = module.algebra();
+    let mut result = 0;
+    for op_idx in 0 .. algebra.dimension(0, t) {
+        let vector_idx = module.operation_generator_to_index(0, op_idx, t, idx);
+        result += algebra.augmentation(op_idx) * v.entry(vector_idx);
+    }
+    result % *algebra.prime()
+}
+
 #[derive(Clone)]
 struct Cocycle {
     s : u32,
@@ -779,14 +916,7 @@ impl<CC : UnitChainComplex> Resolution<CC> {
         self.inner.extend_through_degree(*next_s, max_s, *next_t, max_t);
         self.algebra().compute_basis(max_t - min_degree);
 
-        if let Some(unit_res) = &self.unit_resolution {
-            let unit_res = unit_res.upgrade().unwrap();
-            let unit_res = unit_res.read();
-            // Avoid a deadlock
-            if !ptr_eq(&unit_res.inner, &self.inner) {
-                unit_res.resolve_through_bidegree(self.max_product_homological_degree, max_t - min_degree);
-            }
-        }
+        self.resolve_unit_resolution(max_t);
 
         for t in min_degree ..= max_t {
             let start = if t < *next_t { *next_s } else { 0 };
@@ -809,7 +939,7 @@ impl<CC : UnitChainComplex> Resolution<CC> {
     }
 
     fn step_after(&self, s : u32, t : i32) {
-        if t - (s as i32) < self.min_degree() {
+        if self.below_vanishing_line(s, t) {
             return;
         }
         let module = self.module(s);
@@ -838,7 +968,7 @@ impl<CC : UnitChainComplex> Resolution<CC> {
 
         for (op_name, op_degree, op_index) in &self.filtration_one_products {
             let source_t = target_t - *op_degree;
-            if source_t - (source_s as i32) < self.min_degree(){
+            if self.below_vanishing_line(source_s, source_t) {
                 continue;
             }
             let source_dim = source.number_of_gens_in_degree(source_t);
@@ -919,6 +1049,10 @@ impl<CC: UnitChainComplex> Resolution<CC> {
             let max_s = next_s - 1;
             let max_t = *self.next_t.lock() - 1;
 
+            // The new product may have a larger homological degree than the products we resolved
+            // the unit resolution for.
+            self.resolve_unit_resolution(max_t);
+
             self.construct_maps_to_unit(max_s, max_t);
 
             self.extend_maps_to_unit(max_s, max_t);
@@ -960,6 +1094,32 @@ impl<CC: UnitChainComplex> Resolution<CC> {
         }
     }
 
+    /// Resolves the unit resolution as far as is needed to compute the products with classes in
+    /// internal degree at most `max_t`.
+    fn resolve_unit_resolution(&self, max_t : i32) {
+        if let Some(unit_res) = &self.unit_resolution {
+            let unit_res = unit_res.upgrade().unwrap();
+            let unit_res = unit_res.read();
+            // Avoid a deadlock
+            if !ptr_eq(&unit_res.inner, &self.inner) {
+                unit_res.resolve_through_bidegree(self.max_product_homological_degree, max_t - self.min_degree());
+            }
+        }
+    }
+
+    /// Resolves the unit resolution through bidegree (s, t) and returns the dimension of Ext of
+    /// the unit there, which is the length of the class of a product in this bidegree. The unit
+    /// resolution must have been constructed, e.g. by `construct_unit_resolution`.
+    pub fn unit_ext_dimension(&self, s : u32, t : i32) -> usize {
+        let unit_res = self.unit_resolution.as_ref().unwrap().upgrade().unwrap();
+        let unit_res = unit_res.read();
+        if t < unit_res.min_degree() {
+            return 0;
+        }
+        unit_res.resolve_through_bidegree(s, t);
+        unit_res.inner.number_of_gens_in_bidegree(s, t)
+    }
+
     pub fn set_unit_resolution(&mut self, unit_res : Weak<RwLock<Resolution<CC>>>) {
         if !self.chain_maps_to_unit_resolution.is_empty() {
             panic!("Cannot change unit resolution after you start computing products");
@@ -983,7 +1143,7 @@ impl<CC: UnitChainComplex> Resolution<CC> {
         let source_s = target_s - elt.s;
         let source_t = target_t - elt.t;
 
-        if source_t - (source_s as i32) < self.min_degree() {
+        if self.below_vanishing_line(source_s, source_t) {
             return;
         }
 
@@ -1005,8 +1165,7 @@ impl<CC: UnitChainComplex> Resolution<CC> {
                 let mut val = 0;
                 for i in 0 .. elt.class.len() {
                     if elt.class[i] != 0 {
-                        let idx = output_module.operation_generator_to_index(0, 0, elt.t, i);
-                        val += elt.class[i] * result.entry(idx);
+                        val += elt.class[i] * evaluate_dual_generator(&output_module, elt.t, i, result);
                     }
                 }
                 products[k].push(val % *self.prime());
@@ -1095,42 +1254,60 @@ impl<CC : UnitChainComplex> Resolution<CC> {
         }
     }
 
+    /// This function computes the products by the self map most recently added to self_maps with
+    /// the parts of Ext that have already been computed. This function should be called right
+    /// after `add_self_map`, unless `resolve_through_degree`/`resolve_through_bidegree` has never
+    /// been called.
+    pub fn catch_up_self_maps(&self) {
+        let f = self.self_maps.last().unwrap();
+        let next_s = *self.next_s.lock();
+        let next_t = *self.next_t.lock();
+        for t in self.min_degree() .. next_t {
+            for s in 0 .. next_s {
+                self.compute_self_map_step(f, s, t);
+            }
+        }
+    }
+
     /// We compute the products by self maps where the result has degree (s, t).
-    #[allow(clippy::needless_range_loop)]
     fn compute_self_maps(&self, target_s : u32, target_t : i32) {
         for f in &self.self_maps {
-            if target_s < f.s {
-                return;
-            }
-            let source_s = target_s - f.s;
-            let source_t = target_t - f.t;
+            self.compute_self_map_step(f, target_s, target_t);
+        }
+    }
 
-            if source_t - (source_s as i32) < self.min_degree() {
-                continue;
-            }
-            if source_s == 0 && source_t == self.min_degree() {
-                f.map.extend_step(target_s, target_t, Some(&f.map_data));
-            }
-            f.map.extend(target_s, target_t);
+    #[allow(clippy::needless_range_loop)]
+    fn compute_self_map_step(&self, f : &SelfMap<CC>, target_s : u32, target_t : i32) {
+        if target_s < f.s {
+            return;
+        }
+        let source_s = target_s - f.s;
+        let source_t = target_t - f.t;
 
-            let source = self.module(source_s);
-            let target = self.module(target_s);
+        if self.below_vanishing_line(source_s, source_t) {
+            return;
+        }
+        if source_s == 0 && source_t == self.min_degree() {
+            f.map.extend_step(target_s, target_t, Some(&f.map_data));
+        }
+        f.map.extend(target_s, target_t);
 
-            let source_dim = source.number_of_gens_in_degree(source_t);
-            let target_dim = target.number_of_gens_in_degree(target_t);
+        let source = self.module(source_s);
+        let target = self.module(target_s);
 
-            let mut products = vec![Vec::with_capacity(target_dim); source_dim];
+        let source_dim = source.number_of_gens_in_degree(source_t);
+        let target_dim = target.number_of_gens_in_degree(target_t);
 
-            for j in 0 .. target_dim {
-                let result = f.map.get_map(source_s).output(target_t, j);
+        let mut products = vec![Vec::with_capacity(target_dim); source_dim];
 
-                for k in 0 .. source_dim {
-                    let vector_idx = source.operation_generator_to_index(0, 0, source_t, k);
-                    products[k].push(result.entry(vector_idx));
-                }
+        for j in 0 .. target_dim {
+            let result = f.map.get_map(source_s).output(target_t, j);
+
+            for k in 0 .. source_dim {
+                products[k].push(evaluate_dual_generator(&source, source_t, k, result));
             }
-            self.add_structline(&f.name, source_s, source_t, target_s, target_t, false, products);
         }
+        self.add_structline(&f.name, source_s, source_t, target_s, target_t, false, products);
     }
 }
 
@@ -1152,11 +1329,91 @@ impl<CC : UnitChainComplex> Resolution<CC>
         self.inner.complex().min_degree()
     }
 
+    /// Whether Ext vanishes in bidegree (s, t) for degree reasons. Over a connected algebra, this
+    /// is the case when t - s is less than the min degree. There is no such vanishing line if the
+    /// algebra is concentrated in degree 0.
+    fn below_vanishing_line(&self, s : u32, t : i32) -> bool {
+        self.inner.is_connected() && t - (s as i32) < self.min_degree()
+    }
+
     pub fn differential(&self, s : u32) -> Arc<FreeModuleHomomorphism<FreeModule<<CC::Module as Module>::Algebra>>> {
         self.inner.differential(s)
     }
 }
 
+impl<CC : UnitChainComplex> Resolution<CC>
+where <CC::Module as Module>::Algebra : SteenrodAlgebraT
+{
+    /// Writes the generators in homological degree `s` and internal degree at most `max_t`,
+    /// together with their differentials, in the layout of the `Diff.s` files of Bruner's `ext`
+    /// programs. This only makes sense at the prime 2. The output starts with a line `s={s}
+    /// n={n}`, where `n` is the number of generators. Then for each generator there is a line
+    /// `{g} : {t} {k}`, where `g` is the index of the generator, `t` its internal degree and `k`
+    /// the number of terms in its differential, followed by one line `{j} {d} {m} {ops}` for each
+    /// term. Here `j` is the index of the generator in homological degree `s - 1` and `ops` is
+    /// its coefficient, which has degree `d` and consists of `m` Milnor basis elements.
+    ///
+    /// Generators are numbered in order of internal degree. In homological degree 0, the
+    /// differential is the augmentation, whose value is written in terms of the basis of the
+    /// module, numbered the same way.
+    pub fn to_bruner(&self, s : u32, max_t : i32) -> String {
+        let algebra = self.algebra();
+        let milnor = MilnorAlgebra::new(self.prime());
+        let source = self.module(s);
+        let min_degree = self.min_degree();
+
+        let mut blocks = Vec::new();
+        let mut num_gens = 0;
+        for t in min_degree ..= max_t {
+            for idx in 0 .. source.number_of_gens_in_degree(t) {
+                // (target index, op degree, op)
+                let mut terms = Vec::new();
+                if s == 0 {
+                    let target = self.inner.target().module(0);
+                    let chain_map = self.inner.chain_map(0);
+                    let output = chain_map.output(t, idx);
+                    let offset : usize = (target.min_degree() .. t).map(|i| target.dimension(i)).sum();
+                    for (j, v) in output.iter().enumerate() {
+                        if v != 0 {
+                            terms.push((offset + j, 0, "i(0)".to_string(), 1));
+                        }
+                    }
+                } else {
+                    let target = self.module(s - 1);
+                    let d = self.differential(s);
+                    let output = d.output(t, idx);
+                    let mut gen_number = 0;
+                    for gen_deg in min_degree ..= t {
+                        let num_target_gens = target.number_of_gens_in_degree(gen_deg);
+                        for gen_idx in 0 .. num_target_gens {
+                            let op_deg = t - gen_deg;
+                            let dim = algebra.dimension(op_deg, gen_deg);
+                            let offset = target.generator_offset(t, gen_deg, gen_idx);
+                            let mut op = FpVector::new(self.prime(), dim);
+                            for i in 0 .. dim {
+                                op.set_entry(i, output.entry(offset + i));
+                            }
+                            if !op.is_zero() {
+                                let ops = element_to_bruner(&*algebra, &milnor, op_deg, &op);
+                                let count = ops.split(' ').count();
+                                terms.push((gen_number + gen_idx, op_deg, ops, count));
+                            }
+                        }
+                        gen_number += num_target_gens;
+                    }
+                }
+                let mut block = format!("{} : {} {}\n", num_gens, t, terms.len());
+                for (j, d, ops, count) in terms {
+                    block.push_str(&format!("{} {} {} {}\n", j, d, count, ops));
+                }
+                blocks.push(block);
+                num_gens += 1;
+            }
+        }
+        format!("s={} n={}\n\n{}", s, num_gens, blocks.join("\n"))
+    }
+}
+
 use std::io;
 use std::io::{Read, Write};
 use saveload::{Save, Load};
@@ -177,6 +177,8 @@ where M: Module<Algebra = SteenrodAlgebra>,
             FiniteModule::FDModule(m) => m.max_degree(),
             FiniteModule::FPModule(m) => m.generators.get_max_generator_degree(),
             FiniteModule::RealProjectiveSpace(_) => panic!("Real Projective Space not supported"),
+            FiniteModule::ProjectiveSpace(_) => panic!("Projective Space not supported"),
+            FiniteModule::LensSpace(_) => panic!("Lens Space not supported"),
         };
 
         let hom = Self::new(name, Arc::downgrade(&source), Arc::downgrade(&target), 0, degree_shift);
@@ -1,8 +1,9 @@
 //! This file contains code used by main.rs
 
 use serde_json::value::Value;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
@@ -14,8 +15,12 @@ use ext::chain_complex::{ChainComplex, TensorChainComplex};
 use ext::module::homomorphism::{
     FiniteModuleHomomorphism, FreeModuleHomomorphism, IdentityHomomorphism, ModuleHomomorphism,
 };
+use ext::algebra::SteenrodAlgebra;
+use ext::bruner::fd_module_to_bruner;
+use ext::steenrod_evaluator::SteenrodCalculator;
+use ext::margolis::{free_basis, margolis_homology, MargolisOperator};
 use ext::module::{BoundedModule, FiniteModule, Module};
-use ext::resolution::Resolution;
+use ext::resolution::{minimal_presentation, Resolution};
 use ext::resolution_homomorphism::ResolutionHomomorphism;
 use ext::utils::{construct, construct_from_json, Config};
 use ext::yoneda::yoneda_representative_element;
@@ -500,6 +505,230 @@ pub fn steenrod() -> error::Result<String> {
     }
 }
 
+/// Evaluates expressions in the Steenrod algebra and prints them in both the Adem and the Milnor
+/// basis. If `module_config` is specified, the expressions are elements of the module instead,
+/// such as `Sq2 * x0 + x2`, and the basis of the module is computed up to
+/// `module_config.max_degree`. If `expression` is `None`, this reads expressions from standard
+/// input until it is closed.
+pub fn calc(p: u32, module_config: Option<&Config>, expression: Option<&str>) -> error::Result<String> {
+    let mut module = None;
+    let p = match module_config {
+        Some(config) => {
+            let bundle = construct(config)?;
+            let module_p = *bundle.module.prime();
+            if let SteenrodAlgebra::MilnorAlgebra(_) = &*bundle.module.algebra() {
+                return Err(error::GenericError::new(
+                    "The calculator requires a module that supports the Adem basis".to_string(),
+                )
+                .into());
+            }
+            bundle.module.compute_basis(config.max_degree);
+            let mut lookup = HashMap::new();
+            for degree in bundle.module.min_degree()..=config.max_degree {
+                for idx in 0..bundle.module.dimension(degree) {
+                    lookup.insert(bundle.module.basis_element_to_string(degree, idx), (degree, idx));
+                }
+            }
+            module = Some((bundle.module, lookup));
+            module_p
+        }
+        None => p,
+    };
+    let p = ValidPrime::try_new(p)
+        .ok_or_else(|| error::GenericError::new(format!("Invalid prime: {}", p)))?;
+    let calculator = SteenrodCalculator::new(p);
+
+    let evaluate = |input: &str| -> error::Result<String> {
+        match &module {
+            Some((module, lookup)) => {
+                let (degree, result) = calculator.evaluate_module(&**module, lookup, input)?;
+                Ok(format!(
+                    "Degree {}: {}",
+                    degree,
+                    module.element_to_string(degree, &result)
+                ))
+            }
+            None => {
+                let (degree, _) = calculator.evaluate_adem(input)?;
+                Ok(format!(
+                    "Degree {}\nAdem:   {}\nMilnor: {}",
+                    degree,
+                    calculator.evaluate_adem_to_string(input)?,
+                    calculator.evaluate_milnor_to_string(input)?
+                ))
+            }
+        }
+    };
+
+    if let Some(expression) = expression {
+        return evaluate(expression);
+    }
+
+    println!("Enter an expression to evaluate, or 'quit' to exit");
+    let stdin = std::io::stdin();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.read_line(&mut line)? == 0 {
+            break;
+        }
+        match line.trim() {
+            "" => (),
+            "quit" | "exit" => break,
+            input => match evaluate(input) {
+                Ok(result) => println!("{}", result),
+                Err(err) => println!("{}", err.inner()),
+            },
+        }
+    }
+    Ok(String::new())
+}
+
+/// Serves JSON-RPC requests for the resolution of the module, over a websocket listening on
+/// `websocket` if given and over stdio otherwise. Resolutions can only be saved inside
+/// `save_dir`. See `ext::server` for the protocol.
+pub fn serve(
+    config: &Config,
+    websocket: Option<&str>,
+    save_dir: Option<&str>,
+) -> error::Result<()> {
+    let bundle = construct(config)?;
+    let save_dir = save_dir.map(PathBuf::from);
+    match websocket {
+        Some(address) => ext::server::serve_websocket(bundle, save_dir, address),
+        None => {
+            let stdin = std::io::stdin();
+            ext::server::serve_lines(bundle, save_dir, stdin.lock(), std::io::stdout())?;
+            Ok(())
+        }
+    }
+}
+
+/// Prints the non-trivial Margolis homologies of a bounded module, with respect to the operators
+/// $Q_i$ and $P^s_t$ that lie in the algebra.
+pub fn margolis(config: &Config) -> error::Result<String> {
+    let bundle = construct(config)?;
+    let module = &bundle.module;
+    if !module.is_bounded() {
+        return Err(error::GenericError::new(
+            "Margolis homology can only be computed for bounded modules".to_string(),
+        )
+        .into());
+    }
+    let module = module.to_fd_module();
+    let max_degree = module.max_degree();
+    let algebra = module.algebra();
+    // Operators of higher degree act trivially, so their homology is the whole module.
+    let range = max_degree - module.min_degree();
+
+    let mut result = Vec::new();
+    for op in MargolisOperator::operators(module.prime(), range) {
+        // Skip operators that are not in the algebra, e.g. if the algebra has a profile.
+        let (degree, elt) = match op.to_element(&*algebra) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+        let homology = margolis_homology(&module, degree, &elt)?;
+        for (t, basis) in homology.iter_enum() {
+            if basis.is_empty() {
+                continue;
+            }
+            let elements: Vec<String> = basis
+                .iter()
+                .map(|v| module.element_to_string(t, v))
+                .collect();
+            result.push(format!("H({}) in degree {}: {}", op, t, elements.join(", ")));
+        }
+    }
+    if result.is_empty() {
+        result.push(format!(
+            "All Margolis homologies of operators of degree at most {} vanish",
+            range
+        ));
+    }
+
+    // Over a finite algebra such as A(n), this tells us whether the resolution is trivial.
+    if let SteenrodAlgebra::MilnorAlgebra(a) = &*algebra {
+        if a.top_degree().is_some() {
+            match free_basis(&module, a)? {
+                Some(basis) => {
+                    let generators: Vec<String> = basis
+                        .iter_enum()
+                        .flat_map(|(t, vs)| vs.iter().map(move |v| (t, v)))
+                        .map(|(t, v)| module.element_to_string(t, v))
+                        .collect();
+                    result.push(format!(
+                        "The module is free on generators {}",
+                        generators.join(", ")
+                    ));
+                }
+                None => result.push("The module is not free".to_string()),
+            }
+        }
+    }
+    Ok(result.join("\n"))
+}
+
+/// Returns the module as a finite dimensional module JSON. This flattens composite module specs
+/// such as tensor products into a single module file.
+pub fn export(config: &Config) -> error::Result<String> {
+    let bundle = construct(config)?;
+    let module = &bundle.module;
+    if !module.is_bounded() {
+        return Err(error::GenericError::new(
+            "Only bounded modules can be exported as finite dimensional modules".to_string(),
+        )
+        .into());
+    }
+    let mut json = Value::Object(serde_json::Map::new());
+    module.to_fd_module().to_json(&mut json);
+    module.algebra().to_json(&mut json);
+    Ok(serde_json::to_string_pretty(&json)?)
+}
+
+/// Returns a minimal presentation of the module as a finitely presented module JSON. Only the
+/// relations in degree at most `config.max_degree` are included.
+pub fn presentation(config: &Config) -> error::Result<String> {
+    let bundle = construct(config)?;
+    let module = &bundle.module;
+    let presentation = minimal_presentation(Arc::clone(module), config.max_degree);
+    let mut json = Value::Object(serde_json::Map::new());
+    presentation.to_json(&mut json);
+    module.algebra().to_json(&mut json);
+    Ok(serde_json::to_string_pretty(&json)?)
+}
+
+/// Returns the module, or the generators and differentials of its minimal resolution through
+/// `config.max_degree`, in the format of Bruner's ext programs. The differentials in each
+/// homological degree are separated by a blank line.
+pub fn bruner(config: &Config, resolution: bool) -> error::Result<String> {
+    let bundle = construct(config)?;
+    if !resolution {
+        let module = &bundle.module;
+        if !module.is_bounded() {
+            return Err(error::GenericError::new(
+                "Only bounded modules can be written in Bruner's format".to_string(),
+            )
+            .into());
+        }
+        return fd_module_to_bruner(&module.to_fd_module());
+    }
+
+    if *bundle.module.prime() != 2 {
+        return Err(error::GenericError::new(
+            "Bruner's format is only supported at the prime 2".to_string(),
+        )
+        .into());
+    }
+    let res = bundle.resolution.read();
+    res.resolve_through_degree(config.max_degree);
+    let diffs: Vec<String> = (0..=config.max_degree as u32)
+        .map(|s| res.to_bruner(s, config.max_degree))
+        .collect();
+    Ok(diffs.join("\n"))
+}
+
 pub fn test(_config: &Config) -> error::Result<String> {
     Ok(String::new())
 }
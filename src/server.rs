@@ -0,0 +1,455 @@
+//! A JSON-RPC 2.0 server for driving a resolution interactively, e.g. from a chart frontend
+//! running in the browser. Requests are served either over stdio, with one message per line, or
+//! over a local websocket, with one message per text frame.
+//!
+//! The methods are
+//!  * `resolve` with parameters `{"max_degree": t}` and optionally `"max_s"`, which resolves
+//!    through the given bidegree. The homological degree defaults to `t - min_degree`.
+//!  * `add_product` with parameters `{"hom_deg", "int_deg", "class", "name"}`, as in the
+//!    `products` field of a module specification. The length of `class` must be the dimension of
+//!    Ext of the unit in this bidegree.
+//!  * `add_self_map` with parameters `{"hom_deg", "int_deg", "name", "map_data"}`, as in the
+//!    `self_maps` field of a module specification. `map_data` must have a row for each generator
+//!    of Ext in bidegree `(hom_deg, int_deg + min_degree)` and a column for each basis element of
+//!    the module in degree `min_degree`.
+//!  * `cocycle` with parameters `{"s", "t", "idx"}`, which returns the cocycle representing the
+//!    class as a string.
+//!  * `save` with parameters `{"path"}`, which saves the resolution to a file. The path must be
+//!    relative to the save directory given to the server, and must not contain `..`. Saving is
+//!    disabled if there is no save directory.
+//!
+//! While a request is being processed, the `add_class` and `add_structline` callbacks of the
+//! resolution are streamed to the client as the notifications
+//! `{"method": "add_class", "params": {"s", "t", "num_gens"}}` and
+//! `{"method": "add_structline", "params": {"name", "source", "target", "left", "products"}}`,
+//! where `source` and `target` are `[s, t]` pairs.
+//!
+//! A panic while handling a request is reported as an error with code -32603, and the server
+//! carries on with the next request.
+//!
+//! Since any web page can open a websocket to localhost, the websocket server only accepts
+//! connections whose `Origin` header is `http(s)://localhost`, `http(s)://127.0.0.1` or
+//! `http(s)://[::1]`, with an optional port. Other clients have to send such a header too.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+use saveload::Save;
+use serde_json::{json, Value};
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::{Message, WebSocket};
+
+use crate::chain_complex::ChainComplex;
+use crate::module::{Module, ModuleSpecError};
+use crate::utils::{AlgebraicObjectsBundle, ProductSpec, SelfMapSpec};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+/// The error code for requests that are well-formed but cannot be carried out.
+const SERVER_ERROR: i64 = -32000;
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: String) -> Self {
+        Self { code, message }
+    }
+}
+
+impl From<ModuleSpecError> for RpcError {
+    fn from(err: ModuleSpecError) -> Self {
+        Self::new(INVALID_PARAMS, err.to_string())
+    }
+}
+
+/// Handles JSON-RPC messages for a single resolution. Responses are returned by `handle`, while
+/// notifications are passed to the `send` function given to `Server::new`.
+/// The `save` method writes files inside `save_dir`, and is disabled if it is `None`.
+pub struct Server {
+    bundle: AlgebraicObjectsBundle,
+    save_dir: Option<PathBuf>,
+}
+
+impl Server {
+    pub fn new(
+        bundle: AlgebraicObjectsBundle,
+        save_dir: Option<PathBuf>,
+        send: Rc<dyn Fn(Value)>,
+    ) -> Self {
+        {
+            let mut resolution = bundle.resolution.write();
+            let send_ = Rc::clone(&send);
+            resolution.add_class = Some(Box::new(move |s: u32, t: i32, num_gens: usize| {
+                send_(notification(
+                    "add_class",
+                    json!({"s": s, "t": t, "num_gens": num_gens}),
+                ))
+            }));
+            resolution.add_structline = Some(Box::new(
+                move |name: &str,
+                      source_s: u32,
+                      source_t: i32,
+                      target_s: u32,
+                      target_t: i32,
+                      left: bool,
+                      products: Vec<Vec<u32>>| {
+                    send(notification(
+                        "add_structline",
+                        json!({
+                            "name": name,
+                            "source": [source_s, source_t],
+                            "target": [target_s, target_t],
+                            "left": left,
+                            "products": products,
+                        }),
+                    ))
+                },
+            ));
+        }
+        Self { bundle, save_dir }
+    }
+
+    /// Handles a single JSON-RPC message and returns the response, or `None` if the message is a
+    /// notification.
+    pub fn handle(&self, message: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(err) => {
+                return Some(response(
+                    Value::Null,
+                    Err(RpcError::new(PARSE_ERROR, err.to_string())),
+                ))
+            }
+        };
+        let id = request.get("id").cloned();
+        let result = match request["method"].as_str() {
+            Some(method) => {
+                catch_unwind(AssertUnwindSafe(|| self.call(method, &request["params"])))
+                    .unwrap_or_else(|payload| {
+                        let message = if let Some(s) = payload.downcast_ref::<&str>() {
+                            (*s).to_string()
+                        } else if let Some(s) = payload.downcast_ref::<String>() {
+                            s.clone()
+                        } else {
+                            "Unknown panic".to_string()
+                        };
+                        Err(RpcError::new(INTERNAL_ERROR, message))
+                    })
+            }
+            None => Err(RpcError::new(
+                INVALID_REQUEST,
+                "The request has no method".to_string(),
+            )),
+        };
+        id.map(|id| response(id, result))
+    }
+
+    fn call(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        let resolution = &self.bundle.resolution;
+        match method {
+            "resolve" => {
+                let max_t =
+                    ModuleSpecError::expect_i64(&params["max_degree"], "max_degree")? as i32;
+                let min_degree = resolution.read().min_degree();
+                let max_s = match &params["max_s"] {
+                    Value::Null => std::cmp::max(max_t - min_degree, 0) as u32,
+                    max_s => ModuleSpecError::expect_u64(max_s, "max_s")? as u32,
+                };
+                resolution.read().resolve_through_bidegree(max_s, max_t);
+                Ok(Value::Null)
+            }
+            "add_product" => {
+                let product = ProductSpec::from_json(params, "params")?;
+                resolution.write().construct_unit_resolution();
+                let (dimension, p) = {
+                    let resolution = resolution.read();
+                    (
+                        resolution.unit_ext_dimension(product.hom_deg, product.int_deg),
+                        *resolution.prime(),
+                    )
+                };
+                if product.class.len() != dimension {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        format!(
+                            "The class has length {}, but Ext^({}, {}) of the unit has dimension {}",
+                            product.class.len(),
+                            product.hom_deg,
+                            product.int_deg,
+                            dimension
+                        ),
+                    ));
+                }
+                if product.class.iter().any(|&x| x >= p) {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        format!("The coordinates of the class must be less than {}", p),
+                    ));
+                }
+                let added = resolution.write().add_product(
+                    product.hom_deg,
+                    product.int_deg,
+                    product.class,
+                    &product.name,
+                );
+                if added {
+                    resolution.read().catch_up_products();
+                }
+                Ok(Value::Bool(added))
+            }
+            "add_self_map" => {
+                let prime = resolution.read().prime();
+                let self_map = SelfMapSpec::from_json(params, "params", prime)?;
+                let (rows, columns) = {
+                    let resolution = resolution.read();
+                    let (s, min_degree) = (self_map.hom_deg, resolution.min_degree());
+                    let t = self_map.int_deg + min_degree;
+                    if self_map.int_deg < 0 {
+                        (0, 0)
+                    } else {
+                        resolution.resolve_through_bidegree(s, t);
+                        let inner = &resolution.inner;
+                        (
+                            inner.number_of_gens_in_bidegree(s, t),
+                            inner.complex().module(0).dimension(min_degree),
+                        )
+                    }
+                };
+                let map_data = &self_map.map_data;
+                if map_data.rows() != rows || (rows > 0 && map_data.columns() != columns) {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        format!(
+                            "map_data is a {} x {} matrix, but should be {} x {}",
+                            map_data.rows(),
+                            map_data.columns(),
+                            rows,
+                            columns
+                        ),
+                    ));
+                }
+                let added = resolution.write().add_self_map(
+                    self_map.hom_deg,
+                    self_map.int_deg,
+                    &self_map.name,
+                    self_map.map_data,
+                );
+                if added {
+                    resolution.read().catch_up_self_maps();
+                }
+                Ok(Value::Bool(added))
+            }
+            "cocycle" => {
+                let s = ModuleSpecError::expect_u64(&params["s"], "s")? as u32;
+                let t = ModuleSpecError::expect_i64(&params["t"], "t")? as i32;
+                let idx = ModuleSpecError::expect_u64(&params["idx"], "idx")? as usize;
+
+                let resolution = resolution.read();
+                if t < resolution.min_degree() || !resolution.inner.has_computed_bidegree(s, t) {
+                    return Err(RpcError::new(
+                        SERVER_ERROR,
+                        format!("Bidegree ({}, {}) has not been computed", s, t),
+                    ));
+                }
+                let num_gens = resolution.inner.number_of_gens_in_bidegree(s, t);
+                if idx >= num_gens {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        format!("Ext^({}, {}) only has {} generators", s, t, num_gens),
+                    ));
+                }
+                Ok(Value::String(resolution.inner.cocycle_string(s, t, idx)))
+            }
+            "save" => {
+                let path = ModuleSpecError::expect_str(&params["path"], "path")?;
+                let save_dir = self.save_dir.as_ref().ok_or_else(|| {
+                    RpcError::new(
+                        SERVER_ERROR,
+                        "Saving is disabled since the server has no save directory".to_string(),
+                    )
+                })?;
+                let path = Path::new(path);
+                if path.as_os_str().is_empty()
+                    || !path
+                        .components()
+                        .all(|component| matches!(component, Component::Normal(_)))
+                {
+                    return Err(ModuleSpecError::InvalidField {
+                        path: "path".to_string(),
+                        expected: "a relative path without ..",
+                    }
+                    .into());
+                }
+                File::create(save_dir.join(path))
+                    .and_then(|file| resolution.read().save(&mut BufWriter::new(file)))
+                    .map_err(|err| RpcError::new(SERVER_ERROR, err.to_string()))?;
+                Ok(Value::Null)
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method {}", method),
+            )),
+        }
+    }
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "method": method, "params": params})
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(RpcError { code, message }) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {"code": code, "message": message},
+        }),
+    }
+}
+
+/// Serves requests read line by line from `input`, and writes the responses and notifications to
+/// `output`, one per line.
+pub fn serve_lines(
+    bundle: AlgebraicObjectsBundle,
+    save_dir: Option<PathBuf>,
+    input: impl BufRead,
+    output: impl Write + 'static,
+) -> io::Result<()> {
+    let output = Rc::new(RefCell::new(output));
+    let output_ = Rc::clone(&output);
+    // The callbacks cannot return errors. If writing fails, so will writing the response.
+    let server = Server::new(
+        bundle,
+        save_dir,
+        Rc::new(move |message| {
+            let mut output = output_.borrow_mut();
+            writeln!(output, "{}", message)
+                .and_then(|()| output.flush())
+                .ok();
+        }),
+    );
+
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle(&line) {
+            let mut output = output.borrow_mut();
+            writeln!(output, "{}", response)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `origin` is the origin of a page served by this machine, i.e. of the form
+/// `http(s)://host[:port]` where `host` is a loopback address.
+fn is_local_origin(origin: &str) -> bool {
+    let rest = match origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+    {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let split = if rest.starts_with('[') {
+        rest.find(']').map(|i| i + 1)
+    } else {
+        Some(rest.find(':').unwrap_or(rest.len()))
+    };
+    let (host, port) = match split {
+        Some(i) => rest.split_at(i),
+        None => return false,
+    };
+    let valid_port = match port.strip_prefix(':') {
+        Some(port) => !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()),
+        None => port.is_empty(),
+    };
+    valid_port && matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// Rejects websocket handshakes that do not come from a local page. See `is_local_origin`. The
+/// signature is the one tungstenite expects of the handshake callback.
+#[allow(clippy::result_large_err)]
+fn check_origin(request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+    let origin = request
+        .headers()
+        .get("Origin")
+        .and_then(|origin| origin.to_str().ok());
+    if matches!(origin, Some(origin) if is_local_origin(origin)) {
+        return Ok(response);
+    }
+    let mut error = ErrorResponse::new(Some(format!("Origin {:?} is not allowed", origin)));
+    *error.status_mut() = StatusCode::FORBIDDEN;
+    Err(error)
+}
+
+/// Listens for websocket connections on `address` and serves requests from them, one
+/// connection at a time. The resolution is kept between connections.
+pub fn serve_websocket(
+    bundle: AlgebraicObjectsBundle,
+    save_dir: Option<PathBuf>,
+    address: &str,
+) -> error::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    eprintln!("Listening on ws://{}", listener.local_addr()?);
+
+    let socket: Rc<RefCell<Option<WebSocket<TcpStream>>>> = Rc::new(RefCell::new(None));
+    let socket_ = Rc::clone(&socket);
+    let server = Server::new(
+        bundle,
+        save_dir,
+        Rc::new(move |message| {
+            if let Some(socket) = socket_.borrow_mut().as_mut() {
+                socket.send(Message::Text(message.to_string())).ok();
+            }
+        }),
+    );
+
+    for stream in listener.incoming() {
+        let websocket = match tungstenite::accept_hdr(stream?, check_origin) {
+            Ok(websocket) => websocket,
+            Err(err) => {
+                eprintln!("Websocket handshake failed: {}", err);
+                continue;
+            }
+        };
+        *socket.borrow_mut() = Some(websocket);
+
+        loop {
+            let message = socket.borrow_mut().as_mut().unwrap().read();
+            let text = match message {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) | Err(_) => break,
+                Ok(_) => continue,
+            };
+            if let Some(response) = server.handle(&text) {
+                let mut socket = socket.borrow_mut();
+                if socket
+                    .as_mut()
+                    .unwrap()
+                    .send(Message::Text(response.to_string()))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        }
+        *socket.borrow_mut() = None;
+    }
+    Ok(())
+}
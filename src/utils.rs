@@ -4,10 +4,12 @@ use parking_lot::RwLock;
 use serde_json::value::Value;
 
 use std::path::PathBuf;
-use algebra::{Algebra, SteenrodAlgebra};
-use algebra::module::{FiniteModule, Module, BoundedModule};
+use algebra::{Algebra, MilnorAlgebra, SteenrodAlgebra};
+use algebra::bruner::fd_module_from_bruner;
+use algebra::module::{FiniteModule, Module, BoundedModule, ModuleSpecError};
 use algebra::module::homomorphism::FreeModuleHomomorphism;
 use fp::matrix::Matrix;
+use fp::prime::ValidPrime;
 use fp::vector::FpVectorT;
 use crate::chain_complex::{FiniteChainComplex, ChainMap};
 use crate::resolution::Resolution;
@@ -30,7 +32,8 @@ pub struct AlgebraicObjectsBundle {
 
 pub fn construct(config : &Config) -> error::Result<AlgebraicObjectsBundle> {
     let contents = load_module_from_file(config)?;
-    let json = serde_json::from_str(&contents)?;
+    let mut json = serde_json::from_str(&contents)?;
+    load_module_references(&mut json, &config.module_paths)?;
 
     construct_from_json(json, config.algebra_name.clone())
 }
@@ -43,14 +46,23 @@ pub fn construct_from_json(mut json : Value, algebra_name : String) -> error::Re
 
     let cofiber = &json["cofiber"];
     if !cofiber.is_null() {
-        let s = cofiber["s"].as_u64().unwrap() as u32;
-        let t = cofiber["t"].as_i64().unwrap() as i32;
-        let idx = cofiber["idx"].as_u64().unwrap() as usize;
+        let s = ModuleSpecError::expect_u64(&cofiber["s"], "cofiber.s")? as u32;
+        let t = ModuleSpecError::expect_i64(&cofiber["t"], "cofiber.t")? as i32;
+        let idx = ModuleSpecError::expect_u64(&cofiber["idx"], "cofiber.idx")? as usize;
 
         resolution.resolve_through_bidegree(s, t + module.max_degree());
 
+        let num_gens = resolution.module(s).number_of_gens_in_degree(t);
+        if idx >= num_gens {
+            return Err(ModuleSpecError::Invalid {
+                path : "cofiber.idx".to_string(),
+                input : idx.to_string(),
+                info : format!("Ext^({}, {}) only has {} generators", s, t, num_gens)
+            }.into());
+        }
+
         let map = FreeModuleHomomorphism::new(resolution.module(s), Arc::clone(&module), t);
-        let mut new_output = Matrix::new(module.prime(), resolution.module(s).number_of_gens_in_degree(t), 1);
+        let mut new_output = Matrix::new(module.prime(), num_gens, 1);
         new_output[idx].set_entry(0, 1);
 
         let lock = map.lock();
@@ -70,41 +82,19 @@ pub fn construct_from_json(mut json : Value, algebra_name : String) -> error::Re
         resolution = Resolution::new(Arc::clone(&chain_complex), None, None);
     }
 
-    let products_value = &mut json["products"];
-    if !products_value.is_null() {
-        let products = products_value.as_array_mut().unwrap();
-        for prod in products {
-            let hom_deg = prod["hom_deg"].as_u64().unwrap() as u32;
-            let int_deg = prod["int_deg"].as_i64().unwrap() as i32;
-            let class : Vec<u32> = serde_json::from_value(prod["class"].take()).unwrap();
-            let name = prod["name"].as_str().unwrap();
-
-            resolution.add_product(hom_deg, int_deg, class, &name.to_string());
+    let products = &json["products"];
+    if !products.is_null() {
+        for (i, prod) in ModuleSpecError::expect_array(products, "products")?.iter().enumerate() {
+            let product = ProductSpec::from_json(prod, &format!("products[{}]", i))?;
+            resolution.add_product(product.hom_deg, product.int_deg, product.class, &product.name);
         }
     }
 
     let self_maps = &json["self_maps"];
     if !self_maps.is_null() {
-        for self_map in self_maps.as_array().unwrap() {
-            let s = self_map["hom_deg"].as_u64().unwrap() as u32;
-            let t = self_map["int_deg"].as_i64().unwrap() as i32;
-            let name = self_map["name"].as_str().unwrap();
-
-            let json_map_data = self_map["map_data"].as_array().unwrap();
-            let json_map_data : Vec<&Vec<Value>> = json_map_data
-                .iter()
-                .map(|x| x.as_array().unwrap())
-                .collect();
-
-            let rows = json_map_data.len();
-            let cols = json_map_data[0].len();
-            let mut map_data = Matrix::new(algebra.prime(), rows, cols);
-            for r in 0..rows {
-                for c in 0..cols {
-                    map_data[r].set_entry(c, json_map_data[r][c].as_u64().unwrap() as u32);
-                }
-            }
-            resolution.add_self_map(s, t, &name.to_string(), map_data);
+        for (i, self_map) in ModuleSpecError::expect_array(self_maps, "self_maps")?.iter().enumerate() {
+            let self_map = SelfMapSpec::from_json(self_map, &format!("self_maps[{}]", i), algebra.prime())?;
+            resolution.add_self_map(self_map.hom_deg, self_map.int_deg, &self_map.name, self_map.map_data);
         }
     }
     Ok(AlgebraicObjectsBundle {
@@ -114,22 +104,159 @@ pub fn construct_from_json(mut json : Value, algebra_name : String) -> error::Re
     })
 }
 
+/// A product as specified by an entry of the `products` field of a module specification.
+pub struct ProductSpec {
+    pub hom_deg : u32,
+    pub int_deg : i32,
+    pub class : Vec<u32>,
+    pub name : String
+}
+
+impl ProductSpec {
+    /// Parses an object with fields `hom_deg`, `int_deg`, `class` and `name`, where `path` is
+    /// the path of `json` used in error messages.
+    pub fn from_json(json : &Value, path : &str) -> Result<Self, ModuleSpecError> {
+        let path = |key : &str| format!("{}.{}", path, key);
+        let hom_deg = ModuleSpecError::expect_u64(&json["hom_deg"], &path("hom_deg"))? as u32;
+        let int_deg = ModuleSpecError::expect_i64(&json["int_deg"], &path("int_deg"))? as i32;
+        let class = ModuleSpecError::expect_array(&json["class"], &path("class"))?
+            .iter()
+            .enumerate()
+            .map(|(j, c)| ModuleSpecError::expect_u64(c, &format!("{}[{}]", path("class"), j)).map(|c| c as u32))
+            .collect::<Result<Vec<u32>, _>>()?;
+        let name = ModuleSpecError::expect_str(&json["name"], &path("name"))?.to_string();
+
+        Ok(Self { hom_deg, int_deg, class, name })
+    }
+}
+
+/// A self map as specified by an entry of the `self_maps` field of a module specification.
+pub struct SelfMapSpec {
+    pub hom_deg : u32,
+    pub int_deg : i32,
+    pub name : String,
+    pub map_data : Matrix
+}
+
+impl SelfMapSpec {
+    /// Parses an object with fields `hom_deg`, `int_deg`, `name` and `map_data`, where `path` is
+    /// the path of `json` used in error messages.
+    pub fn from_json(json : &Value, path : &str, p : ValidPrime) -> Result<Self, ModuleSpecError> {
+        let path = |key : &str| format!("{}.{}", path, key);
+        let hom_deg = ModuleSpecError::expect_u64(&json["hom_deg"], &path("hom_deg"))? as u32;
+        let int_deg = ModuleSpecError::expect_i64(&json["int_deg"], &path("int_deg"))? as i32;
+        let name = ModuleSpecError::expect_str(&json["name"], &path("name"))?.to_string();
+
+        let json_map_data = ModuleSpecError::expect_array(&json["map_data"], &path("map_data"))?;
+        let json_map_data = json_map_data
+            .iter()
+            .enumerate()
+            .map(|(r, x)| ModuleSpecError::expect_array(x, &format!("{}[{}]", path("map_data"), r)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let rows = json_map_data.len();
+        let cols = json_map_data.first().map_or(0, |row| row.len());
+        let mut map_data = Matrix::new(p, rows, cols);
+        for (r, row) in json_map_data.iter().enumerate() {
+            if row.len() != cols {
+                return Err(ModuleSpecError::Invalid {
+                    path : format!("{}[{}]", path("map_data"), r),
+                    input : Value::Array(row.to_vec()).to_string(),
+                    info : format!("Expected a row of length {}", cols)
+                });
+            }
+            for (c, entry) in row.iter().enumerate() {
+                let entry_path = format!("{}[{}][{}]", path("map_data"), r, c);
+                map_data[r].set_entry(c, ModuleSpecError::expect_u64(entry, &entry_path)? as u32);
+            }
+        }
+        Ok(Self { hom_deg, int_deg, name, map_data })
+    }
+}
+
 pub fn load_module_from_file(config : &Config) -> error::Result<String> {
+    find_module_file(&config.module_file_name, &config.module_paths)
+}
+
+fn find_module_file(name : &str, module_paths : &[PathBuf]) -> error::Result<String> {
     let mut result = None;
-    for path in &config.module_paths {
+    for path in module_paths {
         let mut path = path.clone();
-        path.push(&config.module_file_name);
+        path.push(name);
         path.set_extension("json");
         result = std::fs::read_to_string(path).ok();
         if result.is_some() {
             break;
         }
     }
-    result.ok_or_else(|| ModuleFileNotFoundError {
-        name : config.module_file_name.clone()
+    if let Some(result) = result {
+        return Ok(result);
+    }
+
+    // Fall back to module files in Bruner's format, which are referred to by their full name.
+    for path in module_paths {
+        let mut path = path.clone();
+        path.push(name);
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            return bruner_module_to_json(name, &contents);
+        }
+    }
+    Err(ModuleFileNotFoundError {
+        name : name.to_string()
     }.into())
 }
 
+/// Converts a module file in Bruner's format into a JSON module specification.
+fn bruner_module_to_json(name : &str, contents : &str) -> error::Result<String> {
+    let algebra = Arc::new(SteenrodAlgebra::from(MilnorAlgebra::new(ValidPrime::new(2))));
+    let module = fd_module_from_bruner(Arc::clone(&algebra), name.to_string(), contents)?;
+    let mut json = Value::Object(serde_json::Map::new());
+    module.to_json(&mut json);
+    algebra.to_json(&mut json);
+    Ok(json.to_string())
+}
+
+/// Module specs such as `{"type": "dual", "module": "Joker"}` are built out of other modules,
+/// which may be referred to by file name. This replaces these references by the contents of the
+/// corresponding files, searched for in `module_paths`. If `json` does not specify the prime or
+/// the algebra, these are copied from the (first) referenced module.
+pub fn load_module_references(json : &mut Value, module_paths : &[PathBuf]) -> error::Result<()> {
+    let first = match json["type"].as_str() {
+        Some("dual") | Some("shift") => {
+            load_module_reference(&mut json["module"], module_paths)?;
+            json["module"].clone()
+        }
+        Some("tensor") => {
+            load_module_reference(&mut json["left"], module_paths)?;
+            load_module_reference(&mut json["right"], module_paths)?;
+            json["left"].clone()
+        }
+        Some("sum") => {
+            if let Some(specs) = json["modules"].as_array_mut() {
+                for spec in specs {
+                    load_module_reference(spec, module_paths)?;
+                }
+            }
+            json["modules"][0].clone()
+        }
+        _ => return Ok(())
+    };
+
+    for key in &["p", "generic", "algebra", "profile"] {
+        if json[*key].is_null() && !first[*key].is_null() {
+            json[*key] = first[*key].clone();
+        }
+    }
+    Ok(())
+}
+
+fn load_module_reference(spec : &mut Value, module_paths : &[PathBuf]) -> error::Result<()> {
+    if let Some(name) = spec.as_str() {
+        *spec = serde_json::from_str(&find_module_file(name, module_paths)?)?;
+    }
+    load_module_references(spec, module_paths)
+}
+
 #[derive(Debug)]
 struct ModuleFileNotFoundError {
     name : String
@@ -0,0 +1,104 @@
+//! JavaScript bindings, enabled by the `wasm` feature. This exposes the Steenrod calculator, module
+//! validation and small resolutions. Errors are returned as strings, which are thrown as
+//! exceptions on the JavaScript side, and module specifications are passed as JSON strings.
+
+use wasm_bindgen::prelude::*;
+
+use crate::module::Module;
+use crate::steenrod_evaluator::SteenrodCalculator;
+use crate::utils::construct_from_json;
+use fp::prime::ValidPrime;
+
+use serde_json::{json, Value};
+
+fn to_js_error(err: error::Error) -> String {
+    err.inner().to_string()
+}
+
+fn parse_spec(json: &str) -> Result<Value, String> {
+    serde_json::from_str(json).map_err(|err| err.to_string())
+}
+
+/// A calculator for the Steenrod algebra at a fixed prime. This evaluates expressions such as
+/// `Sq2 * Sq2` or `χ(P(0, 1))`; see `steenrod_parser::parse_algebra` for the syntax.
+#[wasm_bindgen]
+pub struct Calculator {
+    inner: SteenrodCalculator,
+}
+
+#[wasm_bindgen]
+impl Calculator {
+    #[wasm_bindgen(constructor)]
+    pub fn new(p: u32) -> Result<Calculator, String> {
+        let p = ValidPrime::try_new(p).ok_or_else(|| format!("Invalid prime: {}", p))?;
+        Ok(Self {
+            inner: SteenrodCalculator::new(p),
+        })
+    }
+
+    pub fn prime(&self) -> u32 {
+        *self.inner.prime()
+    }
+
+    /// Evaluates `input` and returns the result in the Adem basis.
+    #[wasm_bindgen(js_name = evaluateAdem)]
+    pub fn evaluate_adem(&self, input: &str) -> Result<String, String> {
+        self.inner
+            .evaluate_adem_to_string(input)
+            .map_err(to_js_error)
+    }
+
+    /// Evaluates `input` and returns the result in the Milnor basis.
+    #[wasm_bindgen(js_name = evaluateMilnor)]
+    pub fn evaluate_milnor(&self, input: &str) -> Result<String, String> {
+        self.inner
+            .evaluate_milnor_to_string(input)
+            .map_err(to_js_error)
+    }
+}
+
+/// Checks that `json` is a valid module specification over the algebra `algebra`, which is
+/// either `adem` or `milnor`. This throws an exception describing the problem otherwise.
+#[wasm_bindgen(js_name = validateModule)]
+pub fn validate_module(json: &str, algebra: &str) -> Result<(), String> {
+    construct_from_json(parse_spec(json)?, algebra.to_string()).map_err(to_js_error)?;
+    Ok(())
+}
+
+/// Resolves the module `json` through internal degree `max_degree`, and returns the dimensions
+/// of Ext as a JSON string of the form
+/// `{"prime": 2, "min_degree": 0, "max_degree": 10, "dimensions": [[1, 1, ...], ...]}`, where
+/// `dimensions[s][t - min_degree]` is the dimension of $\mathrm{Ext}^{s, t}$. This is meant for
+/// small resolutions, since it blocks until the computation is done.
+#[wasm_bindgen]
+pub fn resolve(json: &str, algebra: &str, max_degree: i32) -> Result<String, String> {
+    let bundle =
+        construct_from_json(parse_spec(json)?, algebra.to_string()).map_err(to_js_error)?;
+    let resolution = bundle.resolution.read();
+    let min_degree = resolution.min_degree();
+    // Ext^{s, t} vanishes for t - s < min_degree.
+    let max_s = std::cmp::max(max_degree - min_degree, 0) as u32;
+    resolution.resolve_through_bidegree(max_s, max_degree);
+
+    let dimensions: Vec<Vec<usize>> = (0..=max_s)
+        .map(|s| {
+            let module = resolution.module(s);
+            (min_degree..=max_degree)
+                .map(|t| {
+                    if t < module.min_degree() {
+                        0
+                    } else {
+                        module.number_of_gens_in_degree(t)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    Ok(json!({
+        "prime": *bundle.module.prime(),
+        "min_degree": min_degree,
+        "max_degree": max_degree,
+        "dimensions": dimensions,
+    })
+    .to_string())
+}
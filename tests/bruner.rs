@@ -0,0 +1,57 @@
+mod common;
+
+use common::{ext_dimensions, module_paths};
+use ext::bruner::fd_module_to_bruner;
+use ext::module::BoundedModule;
+use ext::utils::{construct, Config};
+use std::path::PathBuf;
+
+fn config(name: &str, paths: Vec<PathBuf>, algebra: &str) -> Config {
+    Config {
+        module_paths: paths,
+        module_file_name: name.to_string(),
+        algebra_name: algebra.to_string(),
+        max_degree: 20,
+    }
+}
+
+fn resolve(config: &Config) -> Vec<Vec<usize>> {
+    ext_dimensions(&construct(config).unwrap(), config.max_degree)
+}
+
+#[test]
+fn bruner_module_round_trip() {
+    let modules = module_paths();
+    // A directory of our own, so that concurrent test runs do not overwrite each other's files.
+    let dir = std::env::temp_dir().join(format!("ext-bruner-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for &algebra in &["adem", "milnor"] {
+        for &name in &["Joker", "C2_sm_Ceta", "DA1", "RP4"] {
+            let bundle = construct(&config(name, modules.clone(), algebra)).unwrap();
+            let output = fd_module_to_bruner(&bundle.module.to_fd_module()).unwrap();
+
+            let file_name = format!("{}_{}.def", name, algebra);
+            std::fs::write(dir.join(&file_name), output).unwrap();
+            assert_eq!(
+                resolve(&config(&file_name, vec![dir.clone()], algebra)),
+                resolve(&config(name, modules.clone(), algebra))
+            );
+        }
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn bruner_resolution() {
+    for &algebra in &["adem", "milnor"] {
+        let bundle = construct(&config("S_2", module_paths(), algebra)).unwrap();
+        let resolution = bundle.resolution.read();
+        resolution.resolve_through_degree(4);
+
+        assert_eq!(resolution.to_bruner(0, 4), "s=0 n=1\n\n0 : 0 1\n0 0 1 i(0)\n");
+        assert_eq!(
+            resolution.to_bruner(1, 4),
+            "s=1 n=3\n\n0 : 1 1\n0 1 1 i(1)\n\n1 : 2 1\n0 2 1 i(2)\n\n2 : 4 1\n0 4 1 i(4)\n"
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Tests for the C API in `src/capi.rs`. This compiles `tests/capi/test.c` against the shared
+//! library and runs it. That `include/ext.h` is up to date is checked by `bin/test.sh`.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// The directory containing the shared library. `cargo test` builds it next to the test binary,
+/// in `target/<profile>/deps`, but only copies it to `target/<profile>` on `cargo build`.
+fn library_dir() -> PathBuf {
+    let mut dir = env::current_exe().unwrap();
+    dir.pop();
+    dir
+}
+
+#[test]
+fn c_program() {
+    let out_dir = env::temp_dir().join(format!("ext-capi-{}", std::process::id()));
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let program = out_dir.join("test");
+    let library_dir = library_dir();
+
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .arg(root().join("tests/capi/test.c"))
+        .arg("-o")
+        .arg(&program)
+        .arg("-I")
+        .arg(root().join("include"))
+        .arg("-L")
+        .arg(&library_dir)
+        .arg("-lext")
+        .status()
+        .expect("Failed to run the C compiler");
+    assert!(status.success(), "Failed to compile tests/capi/test.c");
+
+    // cargo sets the library path to include `target/<profile>`, which may contain an outdated
+    // copy of the library, so we override it.
+    let status = Command::new(&program)
+        .arg(out_dir.join("resolution.save"))
+        .env("LD_LIBRARY_PATH", &library_dir)
+        .env("DYLD_LIBRARY_PATH", &library_dir)
+        .status()
+        .unwrap();
+    std::fs::remove_dir_all(&out_dir).unwrap();
+    assert!(status.success(), "tests/capi/test.c failed");
+}
@@ -0,0 +1,96 @@
+/* Exercises the C API. This is compiled and run by tests/capi.rs, which passes the path of a
+ * scratch file for the save file as the first argument. */
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "ext.h"
+
+#define CHECK(call)                                                                           \
+    do {                                                                                      \
+        ExtError err = (call);                                                                \
+        if (err != EXT_ERROR_OK) {                                                            \
+            fprintf(stderr, "%s:%d: %s returned %d: %s\n", __FILE__, __LINE__, #call, err,    \
+                    ext_last_error());                                                        \
+            return 1;                                                                         \
+        }                                                                                     \
+    } while (0)
+
+#define EXPECT(cond)                                                                          \
+    do {                                                                                      \
+        if (!(cond)) {                                                                        \
+            fprintf(stderr, "%s:%d: expected %s\n", __FILE__, __LINE__, #cond);               \
+            return 1;                                                                         \
+        }                                                                                     \
+    } while (0)
+
+static const char *SPHERE =
+    "{\"type\": \"finite dimensional module\", \"p\": 2, \"gens\": {\"x0\": 0}, \"actions\": []}";
+
+int main(int argc, char **argv) {
+    ExtResolution *resolution = NULL;
+    ExtResolution *loaded = NULL;
+    size_t n;
+    uint32_t product[1];
+    uint32_t h1[] = {1};
+    uint32_t bad[] = {1, 1, 2};
+
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s <save file>\n", argv[0]);
+        return 1;
+    }
+
+    EXPECT(ext_resolution_new("{", "adem", &resolution) == EXT_ERROR_INVALID_MODULE);
+    EXPECT(strlen(ext_last_error()) > 0);
+    EXPECT(ext_resolution_new(SPHERE, "adem", NULL) == EXT_ERROR_NULL_POINTER);
+
+    CHECK(ext_resolution_new(SPHERE, "milnor", &resolution));
+    CHECK(ext_resolve_through_bidegree(resolution, 3, 8));
+
+    /* h_0, h_1, h_2 and h_3 */
+    CHECK(ext_number_of_gens_in_bidegree(resolution, 1, 1, &n));
+    EXPECT(n == 1);
+    CHECK(ext_number_of_gens_in_bidegree(resolution, 1, 8, &n));
+    EXPECT(n == 1);
+    CHECK(ext_number_of_gens_in_bidegree(resolution, 1, 3, &n));
+    EXPECT(n == 0);
+    EXPECT(ext_number_of_gens_in_bidegree(resolution, 4, 8, &n) == EXT_ERROR_NOT_COMPUTED);
+
+    /* h_0 * h_0 = h_0^2 and h_0 * h_1 = 0 */
+    CHECK(ext_product(resolution, "h_0", 1, 1, 0, product, 1));
+    EXPECT(product[0] == 1);
+    CHECK(ext_product(resolution, "h_0", 1, 2, 0, product, 0));
+    EXPECT(ext_product(resolution, "h_0", 1, 1, 1, product, 1) == EXT_ERROR_INVALID_ARGUMENT);
+    EXPECT(ext_product(resolution, "h_0", 3, 3, 0, product, 1) == EXT_ERROR_NOT_COMPUTED);
+
+    /* The class must lie in Ext^{1, 2} of the unit, which is one dimensional. */
+    EXPECT(ext_add_product(resolution, 1, 2, bad, 2, "h1") == EXT_ERROR_INVALID_ARGUMENT);
+    EXPECT(ext_add_product(resolution, 1, 2, h1, 0, "h1") == EXT_ERROR_INVALID_ARGUMENT);
+    EXPECT(ext_add_product(resolution, 1, 2, bad + 2, 1, "h1") == EXT_ERROR_INVALID_ARGUMENT);
+    EXPECT(ext_add_product(resolution, 1, 3, h1, 1, "zero") == EXT_ERROR_INVALID_ARGUMENT);
+
+    /* h_1^3 = h_0^2 h_2 */
+    CHECK(ext_add_product(resolution, 1, 2, h1, 1, "h1"));
+    EXPECT(ext_add_product(resolution, 1, 2, h1, 1, "h1") == EXT_ERROR_INVALID_ARGUMENT);
+    CHECK(ext_product(resolution, "h1", 2, 4, 0, product, 1));
+    EXPECT(product[0] == 1);
+
+    CHECK(ext_resolution_save(resolution, argv[1]));
+    CHECK(ext_resolution_load(SPHERE, "milnor", argv[1], &loaded));
+    CHECK(ext_number_of_gens_in_bidegree(loaded, 3, 6, &n));
+    EXPECT(n == 1);
+
+    /* Products can be added to a loaded resolution and are computed as it is extended. */
+    CHECK(ext_add_product(loaded, 1, 2, h1, 1, "h1"));
+    CHECK(ext_resolve_through_bidegree(loaded, 4, 12));
+    CHECK(ext_number_of_gens_in_bidegree(loaded, 4, 11, &n));
+    EXPECT(n == 1);
+    /* h_1 h_3 is nonzero */
+    CHECK(ext_product(loaded, "h1", 1, 8, 0, product, 1));
+    EXPECT(product[0] == 1);
+
+    ext_resolution_free(resolution);
+    ext_resolution_free(loaded);
+    ext_resolution_free(NULL);
+    return 0;
+}
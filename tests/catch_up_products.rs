@@ -0,0 +1,60 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use ext::chain_complex::ChainComplex;
+use ext::utils::construct_from_json;
+use serde_json::json;
+
+type Structline = (u32, i32, Vec<Vec<u32>>);
+
+/// Computes the products by h_1 on Ext of C2 through (4, 10), adding the product before or after
+/// resolving.
+fn h1_products(add_first: bool) -> Vec<Structline> {
+    let json = json!({
+        "type": "finite dimensional module",
+        "p": 2,
+        "gens": {"x0": 0, "x1": 1},
+        "actions": ["Sq1 x0 = x1"]
+    });
+    let bundle = construct_from_json(json, "adem".to_string()).unwrap();
+    let products = Rc::new(RefCell::new(Vec::new()));
+    let products_ = Rc::clone(&products);
+
+    let mut resolution = bundle.resolution.write();
+    resolution.add_structline = Some(Box::new(
+        move |name: &str,
+              source_s: u32,
+              source_t: i32,
+              _target_s: u32,
+              _target_t: i32,
+              _left: bool,
+              result: Vec<Vec<u32>>| {
+            if name == "h1" {
+                products_.borrow_mut().push((source_s, source_t, result));
+            }
+        },
+    ));
+    if add_first {
+        resolution.add_product(1, 2, vec![1], "h1");
+        resolution.resolve_through_bidegree(4, 10);
+    } else {
+        resolution.resolve_through_bidegree(4, 10);
+        resolution.add_product(1, 2, vec![1], "h1");
+        resolution.catch_up_products();
+    }
+    assert!(resolution.inner.has_computed_bidegree(4, 10));
+    drop(resolution);
+
+    let mut products = products.borrow().clone();
+    products.sort();
+    products
+}
+
+#[test]
+fn catch_up_products() {
+    // The unit resolution is only constructed when the product is added, so catching up has to
+    // resolve it as far as the existing resolution.
+    let products = h1_products(false);
+    assert!(!products.is_empty());
+    assert_eq!(products, h1_products(true));
+}
@@ -0,0 +1,32 @@
+//! Helpers shared by the integration tests. Each test crate only uses some of them.
+#![allow(dead_code)]
+
+use ext::utils::{construct_from_json, load_module_references, AlgebraicObjectsBundle};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// The directory containing the module specifications shipped with the crate.
+pub fn module_paths() -> Vec<PathBuf> {
+    vec![PathBuf::from("steenrod_modules")]
+}
+
+/// Reads the module specification `steenrod_modules/{name}.json`.
+pub fn load(name: &str) -> Value {
+    let contents = std::fs::read_to_string(format!("steenrod_modules/{}.json", name)).unwrap();
+    serde_json::from_str(&contents).unwrap()
+}
+
+/// Resolves `bundle` through `max_degree` and returns the dimensions of Ext, indexed by s and t.
+pub fn ext_dimensions(bundle: &AlgebraicObjectsBundle, max_degree: i32) -> Vec<Vec<usize>> {
+    let resolution = bundle.resolution.read();
+    resolution.resolve_through_degree(max_degree);
+    resolution.graded_dimension_vec()
+}
+
+/// Constructs the module specified by `json`, which may refer to the modules in
+/// `steenrod_modules` by name, and returns the dimensions of Ext through `max_degree`.
+pub fn resolve(mut json: Value, algebra: &str, max_degree: i32) -> Vec<Vec<usize>> {
+    load_module_references(&mut json, &module_paths()).unwrap();
+    let bundle = construct_from_json(json, algebra.to_string()).unwrap();
+    ext_dimensions(&bundle, max_degree)
+}
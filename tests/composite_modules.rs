@@ -0,0 +1,56 @@
+mod common;
+
+use common::{load, resolve};
+use ext::utils::{construct_from_json, load_module_references};
+use serde_json::json;
+
+#[test]
+fn tensor_and_sum() {
+    for &algebra in &["adem", "milnor"] {
+        assert_eq!(
+            resolve(json!({"type": "tensor", "left": "C2", "right": "Ceta"}), algebra, 20),
+            resolve(load("C2_sm_Ceta"), algebra, 20)
+        );
+
+        // Ext of a direct sum is the direct sum of Ext groups.
+        let sum = resolve(json!({"type": "sum", "modules": ["C2", "Joker", "C2"]}), algebra, 20);
+        let c2 = resolve(load("C2"), algebra, 20);
+        let joker = resolve(load("Joker"), algebra, 20);
+        for s in 0..sum.len() {
+            for t in 0..sum[s].len() {
+                assert_eq!(sum[s][t], 2 * c2[s][t] + joker[s][t]);
+            }
+        }
+    }
+}
+
+#[test]
+fn tensor_odd_prime() {
+    // C(3) ∧ C(3) splits as C(3) ∨ ΣC(3) at odd primes.
+    let c3 = json!({"type": "finite dimensional module", "p": 3, "gens": {"x0": 0, "x1": 1}, "actions": ["b x0 = x1"]});
+    for &algebra in &["adem", "milnor"] {
+        assert_eq!(
+            resolve(json!({"type": "tensor", "left": c3, "right": c3}), algebra, 30),
+            resolve(json!({"type": "sum", "modules": [c3, {"type": "shift", "shift": 1, "module": c3}]}), algebra, 30)
+        );
+    }
+}
+
+#[test]
+fn tensor_isomorphism() {
+    let path = std::path::PathBuf::from("steenrod_modules");
+    let module = |mut json: serde_json::Value| {
+        load_module_references(&mut json, std::slice::from_ref(&path)).unwrap();
+        construct_from_json(json, "milnor".to_string()).unwrap().module
+    };
+    let tensor = module(json!({"type": "tensor", "left": "C2", "right": "Ceta"}));
+    assert!(tensor.is_isomorphic(&module(load("C2_sm_Ceta"))).unwrap());
+    assert!(!tensor.is_isomorphic(&module(json!({"type": "tensor", "left": "Ceta", "right": "Ceta"}))).unwrap());
+
+    // The Joker is not self-dual as an A-module, since Sq4 acts non-trivially on its dual.
+    let joker = module(load("Joker"));
+    let dual = module(json!({"type": "shift", "shift": 4, "module": {"type": "dual", "module": "Joker"}}));
+    let double_dual = module(json!({"type": "dual", "module": {"type": "dual", "module": "Joker"}}));
+    assert!(!joker.is_isomorphic(&dual).unwrap());
+    assert!(joker.is_isomorphic(&double_dual).unwrap());
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::{load, resolve};
+use ext::utils::{construct_from_json, load_module_references};
+use serde_json::json;
+
+#[test]
+fn dual_shift() {
+    for &algebra in &["adem", "milnor"] {
+        // C2 is self-dual up to a shift.
+        assert_eq!(
+            resolve(json!({"type": "shift", "shift": 1, "module": {"type": "dual", "module": "C2"}}), algebra, 20),
+            resolve(load("C2"), algebra, 20)
+        );
+        // The dual of C(η) is Σ^{-2} C(η).
+        assert_eq!(
+            resolve(json!({"type": "dual", "module": "Ceta"}), algebra, 20),
+            resolve(json!({"type": "shift", "shift": -2, "module": "Ceta"}), algebra, 20)
+        );
+    }
+}
+
+#[test]
+fn dual_joker() {
+    let path = std::path::PathBuf::from("steenrod_modules");
+    let module = |mut json: serde_json::Value| {
+        load_module_references(&mut json, std::slice::from_ref(&path)).unwrap();
+        construct_from_json(json, "milnor".to_string()).unwrap().module
+    };
+    // The dual of the Joker, written out by hand. Sq4 acts on the dual through
+    // χ(Sq4) = Sq4 + Sq3 Sq1, so it is non-trivial there, unlike on the Joker.
+    let expected = module(json!({
+        "type": "finite dimensional module",
+        "p": 2,
+        "gens": {"x0": 0, "x1": 1, "x2": 2, "x3": 3, "x4": 4},
+        "actions": ["Sq1 x0 = x1", "Sq2 x0 = x2", "Sq2 x1 = x3", "Sq2 x2 = x4", "Sq1 x3 = x4", "Sq4 x0 = x4"]
+    }));
+    let dual = module(json!({"type": "shift", "shift": 4, "module": {"type": "dual", "module": "Joker"}}));
+    assert!(dual.is_isomorphic(&expected).unwrap());
+    assert!(!dual.is_isomorphic(&module(load("Joker"))).unwrap());
+}
@@ -0,0 +1,49 @@
+use ext::module::Module;
+use ext::utils::{construct, construct_from_json, Config};
+use serde_json::Value;
+use std::path::PathBuf;
+
+fn config(name: &str, algebra: &str) -> Config {
+    Config {
+        module_paths: vec![PathBuf::from("steenrod_modules")],
+        module_file_name: name.to_string(),
+        algebra_name: algebra.to_string(),
+        max_degree: 20,
+    }
+}
+
+#[test]
+fn convert_finite_fp_module() {
+    let bundle = construct(&config("A1-mod-Sq1", "milnor")).unwrap();
+    let module = &bundle.module;
+    assert!(module.is_fd_module());
+    assert_eq!(
+        (0..=6).map(|t| module.dimension(t)).collect::<Vec<_>>(),
+        vec![1, 0, 1, 1, 0, 1, 0]
+    );
+    assert_eq!(module.basis_element_to_string(2, 0), "P(2) x0");
+
+    let json = std::fs::read_to_string("steenrod_modules/A1-mod-Sq1.json").unwrap();
+    let mut json: Value = serde_json::from_str(&json).unwrap();
+    json["to_fd_module"] = Value::Bool(false);
+    let fp_bundle = construct_from_json(json, "milnor".to_string()).unwrap();
+    assert!(fp_bundle.module.is_fp_module());
+
+    let resolution = bundle.resolution.read();
+    let fp_resolution = fp_bundle.resolution.read();
+    resolution.resolve_through_degree(20);
+    fp_resolution.resolve_through_degree(20);
+    assert_eq!(
+        resolution.graded_dimension_vec(),
+        fp_resolution.graded_dimension_vec()
+    );
+}
+
+#[test]
+fn infinite_fp_module() {
+    let json = std::fs::read_to_string("steenrod_modules/A-mod-Sq1-Sq2.json").unwrap();
+    let mut json: Value = serde_json::from_str(&json).unwrap();
+    json["to_fd_module"] = Value::Bool(true);
+    let bundle = construct_from_json(json, "adem".to_string()).unwrap();
+    assert!(bundle.module.is_fp_module());
+}
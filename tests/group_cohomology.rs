@@ -0,0 +1,105 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use ext::algebra::GroupAlgebra;
+use ext::chain_complex::UnitChainComplex;
+use ext::resolution::Resolution;
+use ext::FDChainComplex;
+use fp::prime::ValidPrime;
+
+fn resolve(algebra: GroupAlgebra, max_s: u32) -> Resolution<FDChainComplex<GroupAlgebra>> {
+    let cc = Arc::new(FDChainComplex::unit_chain_complex(Arc::new(algebra)));
+    let resolution = Resolution::new(cc, None, None);
+    resolution.resolve_through_bidegree(max_s, 0);
+    resolution
+}
+
+fn cohomology(algebra: GroupAlgebra, max_s: u32) -> Vec<usize> {
+    let resolution = resolve(algebra, max_s);
+    (0..=max_s)
+        .map(|s| resolution.module(s).number_of_gens_in_degree(0))
+        .collect()
+}
+
+#[test]
+fn group_cohomology_dimensions() {
+    let two = ValidPrime::new(2);
+    let three = ValidPrime::new(3);
+
+    assert_eq!(cohomology(GroupAlgebra::cyclic(two, 2), 6), vec![1; 7]);
+    assert_eq!(cohomology(GroupAlgebra::cyclic(two, 4), 6), vec![1; 7]);
+    assert_eq!(cohomology(GroupAlgebra::cyclic(three, 9), 6), vec![1; 7]);
+    assert_eq!(
+        cohomology(GroupAlgebra::elementary_abelian(two, 2), 6),
+        vec![1, 2, 3, 4, 5, 6, 7]
+    );
+    assert_eq!(
+        cohomology(GroupAlgebra::elementary_abelian(three, 2), 4),
+        vec![1, 2, 3, 4, 5]
+    );
+    assert_eq!(
+        cohomology(GroupAlgebra::dihedral(two, 4), 6),
+        vec![1, 2, 3, 4, 5, 6, 7]
+    );
+
+    // The dihedral group of order 8 as the symmetries of a square.
+    let d8 = GroupAlgebra::from_permutations(
+        two,
+        "D8".to_string(),
+        vec![vec![1, 2, 3, 0], vec![0, 3, 2, 1]],
+    )
+    .unwrap();
+    assert_eq!(cohomology(d8, 6), vec![1, 2, 3, 4, 5, 6, 7]);
+}
+
+/// Returns the products by the degree one class, indexed by the homological degree of the
+/// multiplicand.
+fn products_by_degree_one_class(algebra: GroupAlgebra, max_s: u32) -> Vec<Vec<Vec<u32>>> {
+    let products = Rc::new(RefCell::new(vec![Vec::new(); max_s as usize]));
+    let products_ = Rc::clone(&products);
+
+    let cc = Arc::new(FDChainComplex::unit_chain_complex(Arc::new(algebra)));
+    let mut resolution = Resolution::new(
+        cc,
+        None,
+        Some(Box::new(
+            move |name: &str,
+                  source_s: u32,
+                  _source_t: i32,
+                  _target_s: u32,
+                  _target_t: i32,
+                  _left: bool,
+                  result: Vec<Vec<u32>>| {
+                assert_eq!(name, "x");
+                products_.borrow_mut()[source_s as usize] = result;
+            },
+        )),
+    );
+    resolution.add_product(1, 0, vec![1], "x");
+    resolution.resolve_through_bidegree(max_s, 0);
+    drop(resolution);
+
+    Rc::try_unwrap(products).unwrap().into_inner()
+}
+
+#[test]
+fn group_cohomology_products() {
+    // H^*(C_2; F_2) = F_2[x]
+    let products = products_by_degree_one_class(GroupAlgebra::cyclic(ValidPrime::new(2), 2), 6);
+    for product in products {
+        assert_eq!(product, vec![vec![1]]);
+    }
+
+    // H^*(C_4; F_2) = F_2[y] ⊗ E[x] and H^*(C_3; F_3) = F_3[y] ⊗ E[x] with |x| = 1, |y| = 2.
+    for &(p, n) in &[(2, 4), (3, 3)] {
+        let products = products_by_degree_one_class(GroupAlgebra::cyclic(ValidPrime::new(p), n), 6);
+        for (s, product) in products.into_iter().enumerate() {
+            if s % 2 == 0 {
+                assert_ne!(product, vec![vec![0]]);
+            } else {
+                assert_eq!(product, vec![vec![0]]);
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::resolve;
+use ext::module::{BoundedModule, Module};
+use ext::utils::construct_from_json;
+use serde_json::json;
+
+#[test]
+fn lens_space_validity() {
+    for &p in &[3, 5] {
+        for &(min, max) in &[(0, 20), (1, 15), (-7, 12)] {
+            for &algebra in &["adem", "milnor"] {
+                let json = json!({"type": "lens space", "p": p, "min": min, "max": max});
+                let bundle = construct_from_json(json, algebra.to_string()).unwrap();
+                let module = bundle.module.to_fd_module();
+                for input_degree in module.min_degree()..=module.max_degree() {
+                    for output_degree in input_degree + 1..=module.max_degree() {
+                        module.check_validity(input_degree, output_degree).unwrap();
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn lens_space_actions() {
+    let modules = [
+        (
+            json!({"type": "lens space", "p": 3, "min": 1, "max": 6}),
+            json!({
+                "type": "finite dimensional module",
+                "p": 3,
+                "gens": {"x1": 1, "x2": 2, "x3": 3, "x4": 4, "x5": 5, "x6": 6},
+                "actions": ["b x1 = x2", "b x3 = x4", "b x5 = x6", "P1 x2 = x6"]
+            }),
+        ),
+        (
+            json!({"type": "lens space", "p": 3, "min": -3, "max": 5}),
+            json!({
+                "type": "finite dimensional module",
+                "p": 3,
+                "gens": {
+                    "x-3": -3, "x-2": -2, "x-1": -1, "x0": 0,
+                    "x1": 1, "x2": 2, "x3": 3, "x4": 4, "x5": 5
+                },
+                "actions": [
+                    "b x-3 = x-2", "b x-1 = x0", "b x1 = x2", "b x3 = x4",
+                    "P1 x-3 = x1", "P1 x-2 = 2 x2", "P1 x-1 = 2 x3"
+                ]
+            }),
+        ),
+    ];
+    for (space, fd_module) in &modules {
+        for &algebra in &["adem", "milnor"] {
+            assert_eq!(
+                resolve(space.clone(), algebra, 20),
+                resolve(fd_module.clone(), algebra, 20),
+                "{}",
+                space
+            );
+        }
+    }
+}
+
+#[test]
+fn lens_space_at_two() {
+    let json = json!({"type": "lens space", "p": 2, "min": 1});
+    assert!(construct_from_json(json, "adem".to_string()).is_err());
+}
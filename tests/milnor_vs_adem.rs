@@ -14,6 +14,12 @@ fn milnor_vs_adem() {
     compare("Csigma", 30);
     compare("S_3", 30);
     compare("Calpha", 30);
+    compare("CP_inf", 30);
+    compare("CP_-2_inf", 30);
+    compare("HP_inf", 30);
+    compare("CP_inf_3", 30);
+    compare("L_inf_3", 30);
+    compare("L_-3_inf_5", 30);
 }
 
 fn compare(module_name : &str, max_degree : i32) {
@@ -1,4 +1,7 @@
+use ext::module::ModuleSpecError;
 use ext::utils::construct_from_json;
+use serde_json::json;
+use std::collections::HashMap;
 
 #[test]
 fn module_construct_error() {
@@ -10,3 +13,179 @@ fn test(json: &str) {
     matches!(construct_from_json(serde_json::from_str(json).unwrap(), "adem".to_string()), Err(_));
     matches!(construct_from_json(serde_json::from_str(json).unwrap(), "milnor".to_string()), Err(_));
 }
+
+fn error(json: serde_json::Value) -> String {
+    construct_from_json(json, "adem".to_string())
+        .err()
+        .expect("module spec should fail to load")
+        .to_string()
+}
+
+fn fd_module(actions: &[&str]) -> serde_json::Value {
+    json!({
+        "type": "finite dimensional module",
+        "p": 2,
+        "gens": {"x0": 0, "x1": 1, "x2": 2},
+        "actions": actions
+    })
+}
+
+#[test]
+fn module_spec_error_paths() {
+    let cases = [
+        (
+            fd_module(&["Sq1 x0 = x1", "Sq1 x1 = x3"]),
+            "actions[1]: invalid generator x3",
+        ),
+        (
+            fd_module(&["Sq2 x0 = x1"]),
+            "actions[0]: x1 is in degree 1 but the action has degree 2",
+        ),
+        (
+            fd_module(&["Sq1 x0 = x1", "Sq1 x0"]),
+            "actions[1]: failed to parse at position 6",
+        ),
+        (
+            json!({"type": "finite dimensional module", "p": 2, "gens": {"x0": "0"}}),
+            "gens.x0: expected an integer",
+        ),
+        (
+            json!({"type": "finite dimensional module", "p": 2, "gens": {"x0": 0}}),
+            "actions: expected an array of actions",
+        ),
+    ];
+    for (json, message) in &cases {
+        let error = error(json.clone());
+        assert!(error.contains(message), "{}", error);
+    }
+
+    let mut json = fd_module(&[]);
+    json["cofiber"] = json!({"s": 0, "idx": 0});
+    assert!(error(json).starts_with("cofiber.t: expected an integer\n"));
+
+    let mut json = fd_module(&[]);
+    json["cofiber"] = json!({"s": 0, "t": 0, "idx": 2});
+    assert!(error(json).starts_with("cofiber.idx: "));
+
+    let mut json = fd_module(&[]);
+    json["products"] = json!([{"hom_deg": 1, "int_deg": 1, "class": [1, "a"], "name": "h0"}]);
+    assert!(error(json).starts_with("products[0].class[1]: expected a non-negative integer\n"));
+
+    let mut json = fd_module(&[]);
+    json["self_maps"] =
+        json!([{"hom_deg": 1, "int_deg": 2, "name": "v", "map_data": [[1, 0], [1]]}]);
+    let message = error(json);
+    assert!(
+        message.starts_with("self_maps[0].map_data[1]: "),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn module_spec_error_position() {
+    let bundle = construct_from_json(fd_module(&["Sq1 x0 = x1"]), "adem".to_string()).unwrap();
+    let mut module = bundle.module.as_fd_module().unwrap().clone();
+    let gen_to_idx: HashMap<String, (i32, usize)> =
+        (0..3).map(|i| (format!("x{}", i), (i, 0))).collect();
+
+    match module.parse_action(&gen_to_idx, "Sq1 x1 = x2 + ", false) {
+        Err(ModuleSpecError::Parse {
+            path,
+            input,
+            position,
+            ..
+        }) => {
+            assert_eq!(path, "");
+            assert_eq!(input, "Sq1 x1 = x2 + ");
+            assert_eq!(position, 14);
+        }
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(module
+        .parse_action(&gen_to_idx, "Sq1 x1 = x2", false)
+        .is_ok());
+}
+
+#[test]
+fn module_spec_error_out_of_range() {
+    let json = json!({
+        "type": "finite dimensional module",
+        "p": 2,
+        "gens": {"x0": 0},
+        "actions": ["Sq1 x0 = 0", "Sq1 x0 = x1"]
+    });
+    let message = error(json);
+    assert!(
+        message.starts_with("actions[1]: The module is zero in degree 1"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn module_spec_error_family_parameters() {
+    for (json, expected) in &[
+        (
+            json!({"type": "brown gitler module", "p": 2, "k": -1}),
+            "k: expected a non-negative integer",
+        ),
+        (
+            json!({"type": "brown gitler module", "p": 2, "k": 1u64 << 32}),
+            "k: The parameter is too large",
+        ),
+        (
+            json!({"type": "brown gitler module", "p": 2, "k": 1u64 << 30}),
+            "k: The parameter is too large",
+        ),
+        (
+            json!({"type": "A mod A(n)", "p": 2, "n": 1u64 << 32}),
+            "n: The parameter is too large",
+        ),
+    ] {
+        let message = error(json.clone());
+        assert!(message.starts_with(expected), "{}", message);
+    }
+    let message = error(json!({"type": "A mod A(n)", "p": 3, "n": 40}));
+    assert!(
+        message.contains("The generators of A(40) are too large"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn module_spec_error_adem_actions() {
+    let module = |action: serde_json::Value| {
+        json!({
+            "type": "finite dimensional module",
+            "p": 2,
+            "gens": {"x0": 0, "x1": 1},
+            "adem_actions": [
+                {"op": [1], "input": "x0", "output": [{"gen": "x1", "coeff": 1}]},
+                action
+            ]
+        })
+    };
+    for (action, expected) in &[
+        (
+            json!({"op": [1], "input": "x1", "output": [{"gen": "x2", "coeff": 1}]}),
+            "adem_actions[1].output[0].gen: invalid generator x2",
+        ),
+        (
+            json!({"op": [2], "input": "x0", "output": [{"gen": "x1", "coeff": 1}]}),
+            "adem_actions[1].output[0].gen: x1 is in degree 1 but the action has degree 2",
+        ),
+        (
+            json!({"op": "Sq1", "input": "x0", "output": []}),
+            "adem_actions[1].op: ",
+        ),
+    ] {
+        let message = error(module(action.clone()));
+        assert!(message.starts_with(expected), "{}", message);
+    }
+
+    // Actions with no output are allowed even if they land above the top degree.
+    let json = module(json!({"op": [2], "input": "x1", "output": []}));
+    assert!(construct_from_json(json, "adem".to_string()).is_ok());
+}
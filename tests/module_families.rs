@@ -0,0 +1,142 @@
+mod common;
+
+use common::{load, resolve};
+use ext::module::{BoundedModule, Module};
+use ext::utils::construct_from_json;
+use serde_json::json;
+
+const MAX_DEGREE: i32 = 20;
+
+#[test]
+fn brown_gitler_dimensions() {
+    // H_* B(k) is spanned by the monomials of weight at most k, where at p = 2 the weight of
+    // xi_i is 2^{i - 1}.
+    let expected: [(u32, u32, Vec<usize>); 4] = [
+        (2, 1, vec![1, 1]),
+        (2, 2, vec![1, 1, 1, 1]),
+        (2, 4, vec![1, 1, 1, 2, 2, 1, 1, 1]),
+        (3, 3, vec![1, 1, 0, 0, 1, 1]),
+    ];
+    for (p, k, dimensions) in &expected {
+        for &algebra in &["adem", "milnor"] {
+            let json = json!({"type": "brown gitler module", "p": p, "k": k});
+            let bundle = construct_from_json(json, algebra.to_string()).unwrap();
+            let module = bundle.module.as_fd_module().unwrap();
+            assert_eq!(module.min_degree(), 0);
+            assert_eq!(module.max_degree(), dimensions.len() as i32 - 1);
+            for (t, &dim) in dimensions.iter().enumerate() {
+                assert_eq!(module.dimension(t as i32), dim, "B({}) at p = {}", k, p);
+            }
+            for input_degree in 0..=module.max_degree() {
+                for output_degree in input_degree + 1..=module.max_degree() {
+                    module.check_validity(input_degree, output_degree).unwrap();
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn brown_gitler_odd_prime_weights() {
+    // At p = 3, tau_i and xi_i both have weight 3^i. The monomials of small weight are
+    //  weight 1: tau_0 (degree 1)
+    //  weight 3: xi_1 (4), tau_1 (5)
+    //  weight 4: tau_0 xi_1 (5), tau_0 tau_1 (6)
+    //  weight 6: xi_1^2 (8), xi_1 tau_1 (9)
+    //  weight 7: tau_0 xi_1^2 (9), tau_0 xi_1 tau_1 (10)
+    let expected: [(u32, Vec<usize>); 5] = [
+        (1, vec![1, 1]),
+        (2, vec![1, 1]),
+        (4, vec![1, 1, 0, 0, 1, 2, 1]),
+        (6, vec![1, 1, 0, 0, 1, 2, 1, 0, 1, 1]),
+        (7, vec![1, 1, 0, 0, 1, 2, 1, 0, 1, 2, 1]),
+    ];
+    for (k, dimensions) in &expected {
+        let json = json!({"type": "brown gitler module", "p": 3, "k": k});
+        let bundle = construct_from_json(json, "milnor".to_string()).unwrap();
+        let module = bundle.module.as_fd_module().unwrap();
+        let actual: Vec<usize> = (0..=module.max_degree())
+            .map(|t| module.dimension(t))
+            .collect();
+        assert_eq!(&actual, dimensions, "B({}) at p = 3", k);
+    }
+}
+
+#[test]
+fn brown_gitler_resolution() {
+    let modules = [
+        (
+            json!({"type": "brown gitler module", "p": 2, "k": 2}),
+            json!({
+                "type": "finite dimensional module",
+                "p": 2,
+                "gens": {"x0": 0, "x1": 1, "x2": 2, "x3": 3},
+                "actions": ["Sq1 x0 = x1", "Sq2 x0 = x2", "Sq1 x2 = x3"]
+            }),
+        ),
+        (
+            json!({"type": "brown gitler module", "p": 3, "k": 3}),
+            json!({
+                "type": "finite dimensional module",
+                "p": 3,
+                "gens": {"x0": 0, "x1": 1, "x4": 4, "x5": 5},
+                "actions": ["b x0 = x1", "P1 x0 = x4", "b x4 = x5"]
+            }),
+        ),
+    ];
+    for (family, fd_module) in &modules {
+        for &algebra in &["adem", "milnor"] {
+            assert_eq!(
+                resolve(family.clone(), algebra, MAX_DEGREE),
+                resolve(fd_module.clone(), algebra, MAX_DEGREE),
+                "{}",
+                family
+            );
+        }
+    }
+}
+
+#[test]
+fn a_mod_a_n() {
+    // Ext of A//A(0) is F_2[h_0].
+    for &algebra in &["adem", "milnor"] {
+        let json = json!({"type": "A mod A(n)", "p": 2, "n": 0});
+        for row in resolve(json, algebra, MAX_DEGREE) {
+            assert_eq!(row[0], 1);
+            assert!(row[1..].iter().all(|&x| x == 0));
+        }
+    }
+
+    // These files only specify the relations in the Adem basis.
+    for &(n, name) in &[(1, "A-mod-Sq1-Sq2"), (2, "A-mod-Sq1-Sq2-Sq4")] {
+        let json = json!({"type": "A mod A(n)", "p": 2, "n": n});
+        assert_eq!(
+            resolve(json, "adem", MAX_DEGREE),
+            resolve(load(name), "adem", MAX_DEGREE)
+        );
+    }
+
+    // Over A(1), A//A(1) is F_2 in degree 0.
+    let json = json!({
+        "type": "A mod A(n)",
+        "p": 2,
+        "n": 1,
+        "algebra": ["milnor"],
+        "profile": {"truncated": true, "p_part": [2, 1]}
+    });
+    let bundle = construct_from_json(json, "milnor".to_string()).unwrap();
+    bundle.module.compute_basis(10);
+    assert_eq!(bundle.module.dimension(0), 1);
+    assert!((1..=10).all(|t| bundle.module.dimension(t) == 0));
+
+    // At p = 3, the dual of A//A(1) is F_3[xi_1^3, xi_2, ...] ⊗ E[tau_2, ...].
+    let json = json!({"type": "A mod A(n)", "p": 3, "n": 1});
+    let bundle = construct_from_json(json, "milnor".to_string()).unwrap();
+    bundle.module.compute_basis(12);
+    assert_eq!(
+        (0..=12)
+            .map(|t| bundle.module.dimension(t))
+            .collect::<Vec<_>>(),
+        vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]
+    );
+}
@@ -0,0 +1,68 @@
+use ext::module::Module;
+use ext::resolution::minimal_presentation;
+use ext::utils::{construct, construct_from_json, Config};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const MAX_DEGREE: i32 = 20;
+
+fn config(name: &str, algebra: &str) -> Config {
+    Config {
+        module_paths: vec![PathBuf::from("steenrod_modules")],
+        module_file_name: name.to_string(),
+        algebra_name: algebra.to_string(),
+        max_degree: MAX_DEGREE,
+    }
+}
+
+#[test]
+fn presentation_resolution() {
+    for &algebra in &["adem", "milnor"] {
+        for &name in &["Joker", "C2", "RP4", "tmf2", "C3", "A-mod-Sq1-Sq2"] {
+            // This file only specifies the relations in the Adem basis.
+            if name == "A-mod-Sq1-Sq2" && algebra == "milnor" {
+                continue;
+            }
+            let bundle = construct(&config(name, algebra)).unwrap();
+            let presentation = minimal_presentation(Arc::clone(&bundle.module), MAX_DEGREE);
+
+            let mut json = Value::Object(serde_json::Map::new());
+            presentation.to_json(&mut json);
+            bundle.module.algebra().to_json(&mut json);
+            let presented = construct_from_json(json, algebra.to_string()).unwrap();
+
+            let resolution = bundle.resolution.read();
+            let presented_resolution = presented.resolution.read();
+            resolution.resolve_through_degree(MAX_DEGREE);
+            presented_resolution.resolve_through_degree(MAX_DEGREE);
+            assert_eq!(
+                resolution.graded_dimension_vec(),
+                presented_resolution.graded_dimension_vec(),
+                "{} over {}",
+                name,
+                algebra
+            );
+        }
+    }
+}
+
+#[test]
+fn presentation_generators() {
+    let bundle = construct(&config("Joker", "adem")).unwrap();
+    let presentation = minimal_presentation(Arc::clone(&bundle.module), 12);
+
+    let generators = &presentation.generators;
+    assert_eq!(generators.gen_names[0], vec!["x0".to_string()]);
+    assert!((1..=12).all(|t| generators.number_of_gens_in_degree(t) == 0));
+
+    // The relations are Sq3, Sq4, Sq4 Sq2, Sq4 Sq2 Sq1 and Sq8.
+    let relations = &presentation.relations;
+    let mut relation_degrees = Vec::new();
+    for t in 0..=12 {
+        for _ in 0..relations.number_of_gens_in_degree(t) {
+            relation_degrees.push(t);
+        }
+    }
+    assert_eq!(relation_degrees, vec![3, 4, 6, 7, 8]);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::resolve;
+use ext::module::{BoundedModule, Module};
+use ext::utils::construct_from_json;
+use serde_json::json;
+
+#[test]
+fn projective_space_validity() {
+    for &p in &[2, 3, 5] {
+        for &module_type in &["complex projective space", "quaternionic projective space"] {
+            for &(min, max) in &[(0, 8), (1, 6), (-5, 4)] {
+                for &algebra in &["adem", "milnor"] {
+                    let json = json!({"type": module_type, "p": p, "min": min, "max": max});
+                    let bundle = construct_from_json(json, algebra.to_string()).unwrap();
+                    let module = bundle.module.to_fd_module();
+                    for input_degree in module.min_degree()..=module.max_degree() {
+                        for output_degree in input_degree + 1..=module.max_degree() {
+                            module.check_validity(input_degree, output_degree).unwrap();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn projective_space_actions() {
+    let modules = [
+        (
+            json!({"type": "complex projective space", "p": 2, "min": 1, "max": 3}),
+            json!({
+                "type": "finite dimensional module",
+                "p": 2,
+                "gens": {"y1": 2, "y2": 4, "y3": 6},
+                "actions": ["Sq2 y1 = y2"]
+            }),
+        ),
+        (
+            json!({"type": "quaternionic projective space", "p": 2, "min": 1, "max": 2}),
+            json!({
+                "type": "finite dimensional module",
+                "p": 2,
+                "gens": {"z1": 4, "z2": 8},
+                "actions": ["Sq4 z1 = z2"]
+            }),
+        ),
+        (
+            json!({"type": "complex projective space", "p": 3, "min": 1, "max": 4}),
+            json!({
+                "type": "finite dimensional module",
+                "p": 3,
+                "gens": {"y1": 2, "y2": 4, "y3": 6, "y4": 8},
+                "actions": ["P1 y1 = y3", "P1 y2 = 2 y4"]
+            }),
+        ),
+        (
+            json!({"type": "complex projective space", "p": 3, "min": -1, "max": 1}),
+            json!({
+                "type": "finite dimensional module",
+                "p": 3,
+                "gens": {"y-1": -2, "y0": 0, "y1": 2},
+                "actions": ["P1 y-1 = 2 y1"]
+            }),
+        ),
+    ];
+    for (space, fd_module) in &modules {
+        for &algebra in &["adem", "milnor"] {
+            assert_eq!(
+                resolve(space.clone(), algebra, 20),
+                resolve(fd_module.clone(), algebra, 20),
+                "{}",
+                space
+            );
+        }
+    }
+}
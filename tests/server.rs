@@ -0,0 +1,317 @@
+use std::cell::RefCell;
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use ext::server::{serve_lines, Server};
+use ext::utils::{construct_from_json, AlgebraicObjectsBundle};
+use serde_json::{json, Value};
+use tungstenite::client::IntoClientRequest;
+
+fn sphere() -> AlgebraicObjectsBundle {
+    let json =
+        json!({"type": "finite dimensional module", "p": 2, "gens": {"x0": 0}, "actions": []});
+    construct_from_json(json, "adem".to_string()).unwrap()
+}
+
+fn server() -> (Server, Rc<RefCell<Vec<Value>>>) {
+    server_saving_to(None)
+}
+
+fn server_saving_to(save_dir: Option<PathBuf>) -> (Server, Rc<RefCell<Vec<Value>>>) {
+    let notifications = Rc::new(RefCell::new(Vec::new()));
+    let notifications_ = Rc::clone(&notifications);
+    let server = Server::new(
+        sphere(),
+        save_dir,
+        Rc::new(move |message| notifications_.borrow_mut().push(message)),
+    );
+    (server, notifications)
+}
+
+fn request(server: &Server, method: &str, params: Value) -> Value {
+    let request = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+    let response = server.handle(&request.to_string()).unwrap();
+    assert_eq!(response["id"], 1);
+    response
+}
+
+/// Returns the products named `name` in the `add_structline` notifications, as
+/// `(source, target, products)`.
+fn structlines(notifications: &[Value], name: &str) -> Vec<(Value, Value, Value)> {
+    notifications
+        .iter()
+        .filter(|n| n["method"] == "add_structline" && n["params"]["name"] == name)
+        .map(|n| {
+            let params = &n["params"];
+            (
+                params["source"].clone(),
+                params["target"].clone(),
+                params["products"].clone(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn resolve_and_products() {
+    let (server, notifications) = server();
+    let response = request(&server, "resolve", json!({"max_degree": 4}));
+    assert_eq!(response["result"], Value::Null);
+
+    let classes: Vec<_> = notifications
+        .borrow()
+        .iter()
+        .filter(|n| n["method"] == "add_class" && n["params"]["num_gens"] != 0)
+        .map(|n| (n["params"]["s"].clone(), n["params"]["t"].clone()))
+        .collect();
+    assert!(classes.contains(&(json!(1), json!(2))));
+    assert!(classes.contains(&(json!(3), json!(3))));
+    assert!(structlines(&notifications.borrow(), "h_0").contains(&(
+        json!([1, 1]),
+        json!([2, 2]),
+        json!([[1]])
+    )));
+
+    // Products added after resolving are computed immediately.
+    let h1 = json!({"hom_deg": 1, "int_deg": 2, "class": [1], "name": "h1"});
+    notifications.borrow_mut().clear();
+    assert_eq!(request(&server, "add_product", h1.clone())["result"], true);
+    assert!(structlines(&notifications.borrow(), "h1").contains(&(
+        json!([1, 2]),
+        json!([2, 4]),
+        json!([[1]])
+    )));
+    assert_eq!(request(&server, "add_product", h1)["result"], false);
+
+    let h0 = json!({"hom_deg": 1, "int_deg": 1, "name": "h0", "map_data": [[1]]});
+    notifications.borrow_mut().clear();
+    assert_eq!(request(&server, "add_self_map", h0)["result"], true);
+    assert!(structlines(&notifications.borrow(), "h0").contains(&(
+        json!([1, 1]),
+        json!([2, 2]),
+        json!([[1]])
+    )));
+
+    // And are computed as the resolution is extended.
+    notifications.borrow_mut().clear();
+    request(&server, "resolve", json!({"max_degree": 8}));
+    // h_1^3 = h_0^2 h_2
+    assert!(structlines(&notifications.borrow(), "h1").contains(&(
+        json!([2, 4]),
+        json!([3, 6]),
+        json!([[1]])
+    )));
+    assert!(structlines(&notifications.borrow(), "h0").contains(&(
+        json!([2, 5]),
+        json!([3, 6]),
+        json!([[1]])
+    )));
+}
+
+#[test]
+fn cocycles_and_errors() {
+    let (server, _) = server();
+    request(&server, "resolve", json!({"max_degree": 4}));
+
+    let response = request(&server, "cocycle", json!({"s": 1, "t": 2, "idx": 0}));
+    assert_eq!(response["result"], "P2 x_{0,0}");
+
+    let response = request(&server, "cocycle", json!({"s": 1, "t": 2, "idx": 1}));
+    assert_eq!(response["error"]["code"], -32602);
+    let response = request(&server, "cocycle", json!({"s": 1, "t": 10, "idx": 0}));
+    assert_eq!(response["error"]["code"], -32000);
+    let response = request(&server, "add_product", json!({"hom_deg": 1}));
+    assert_eq!(response["error"]["code"], -32602);
+    assert!(response["error"]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("params.int_deg"));
+    // The class and map data must match the dimensions of Ext.
+    let response = request(
+        &server,
+        "add_product",
+        json!({"hom_deg": 1, "int_deg": 2, "class": [1, 1], "name": "h1"}),
+    );
+    assert_eq!(response["error"]["code"], -32602);
+    let response = request(
+        &server,
+        "add_product",
+        json!({"hom_deg": 1, "int_deg": 2, "class": [2], "name": "h1"}),
+    );
+    assert_eq!(response["error"]["code"], -32602);
+    let response = request(
+        &server,
+        "add_self_map",
+        json!({"hom_deg": 1, "int_deg": 1, "name": "h0", "map_data": [[1], [1]]}),
+    );
+    assert_eq!(response["error"]["code"], -32602);
+    let response = request(
+        &server,
+        "add_self_map",
+        json!({"hom_deg": 1, "int_deg": 3, "name": "h0", "map_data": [[1]]}),
+    );
+    assert_eq!(response["error"]["code"], -32602);
+    let response = request(
+        &server,
+        "add_product",
+        json!({"hom_deg": 1, "int_deg": 2, "class": [1], "name": "h1"}),
+    );
+    assert_eq!(response["result"], true);
+
+    let response = request(&server, "multiply", Value::Null);
+    assert_eq!(response["error"]["code"], -32601);
+
+    let response = server.handle("{").unwrap();
+    assert_eq!(response["error"]["code"], -32700);
+    // Notifications have no response.
+    assert_eq!(
+        server.handle(r#"{"jsonrpc": "2.0", "method": "resolve", "params": {"max_degree": 5}}"#),
+        None
+    );
+}
+
+#[test]
+fn save() {
+    let dir = std::env::temp_dir().join(format!("ext-server-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (server, _) = server_saving_to(Some(dir.clone()));
+    request(&server, "resolve", json!({"max_degree": 4}));
+    let response = request(&server, "save", json!({"path": "S_2.save"}));
+    assert_eq!(response["result"], Value::Null);
+    assert!(std::fs::metadata(dir.join("S_2.save")).unwrap().len() > 0);
+
+    let absolute = dir.join("absolute.save");
+    for path in &[
+        "../S_2.save",
+        "a/../../S_2.save",
+        absolute.to_str().unwrap(),
+        "",
+    ] {
+        let response = request(&server, "save", json!({ "path": path }));
+        assert_eq!(response["error"]["code"], -32602, "{}", path);
+    }
+    assert!(!absolute.exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn save_disabled() {
+    let (server, _) = server();
+    let response = request(&server, "save", json!({"path": "S_2.save"}));
+    assert_eq!(response["error"]["code"], -32000);
+}
+
+#[derive(Clone, Default)]
+struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn stdio() {
+    let input = r#"{"jsonrpc": "2.0", "id": "a", "method": "resolve", "params": {"max_degree": 2}}
+
+{"jsonrpc": "2.0", "id": "b", "method": "cocycle", "params": {"s": 1, "t": 1, "idx": 0}}
+"#;
+    let output = Buffer::default();
+    serve_lines(sphere(), None, Cursor::new(input), output.clone()).unwrap();
+
+    let output = String::from_utf8(output.0.borrow().clone()).unwrap();
+    let messages: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let responses: Vec<&Value> = messages.iter().filter(|m| m.get("id").is_some()).collect();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["id"], "a");
+    assert_eq!(responses[1]["result"], "P1 x_{0,0}");
+    // The notifications are sent before the response to the request that caused them.
+    assert_eq!(messages[0]["method"], "add_class");
+    assert_eq!(messages.last().unwrap()["id"], "b");
+}
+
+#[test]
+fn websocket() {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let address = format!("127.0.0.1:{}", port);
+    let address_ = address.clone();
+    // The server runs until the process exits.
+    std::thread::spawn(move || ext::server::serve_websocket(sphere(), None, &address_).unwrap());
+
+    let url = format!("ws://{}", address);
+    // Returns the status code of the response if the handshake is rejected.
+    let connect = |origin: Option<&str>| {
+        let mut request = url.as_str().into_client_request().unwrap();
+        if let Some(origin) = origin {
+            request
+                .headers_mut()
+                .insert("Origin", origin.parse().unwrap());
+        }
+        tungstenite::connect(request)
+            .map(|(socket, _)| socket)
+            .map_err(|err| match err {
+                tungstenite::Error::Http(response) => Some(response.status().as_u16()),
+                _ => None,
+            })
+    };
+    let mut socket = (0..100)
+        .find_map(|_| {
+            let result = connect(Some("http://localhost:8000")).ok();
+            if result.is_none() {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+            result
+        })
+        .expect("Failed to connect to the server");
+
+    let request =
+        json!({"jsonrpc": "2.0", "id": 7, "method": "resolve", "params": {"max_degree": 3}});
+    socket
+        .send(tungstenite::Message::Text(request.to_string()))
+        .unwrap();
+    let mut num_classes = 0;
+    loop {
+        let message: Value =
+            serde_json::from_str(socket.read().unwrap().to_text().unwrap()).unwrap();
+        if message["method"] == "add_class" {
+            num_classes += 1;
+        } else if message.get("method").is_none() {
+            assert_eq!(message["id"], 7);
+            assert_eq!(message["result"], Value::Null);
+            break;
+        }
+    }
+    assert!(num_classes > 0);
+
+    // The server only serves one connection at a time, so close this one first.
+    socket.close(None).unwrap();
+    while socket.read().is_ok() {}
+
+    for origin in &[
+        None,
+        Some("null"),
+        Some("http://example.com"),
+        Some("http://localhost.example.com"),
+        Some("http://127.0.0.1.example.com:8000"),
+        Some("file://localhost"),
+    ] {
+        assert_eq!(connect(*origin).err(), Some(Some(403)), "{:?}", origin);
+    }
+    for origin in &["http://127.0.0.1", "https://[::1]:8080"] {
+        let mut socket = connect(Some(origin)).unwrap();
+        socket.close(None).unwrap();
+        while socket.read().is_ok() {}
+    }
+}
@@ -0,0 +1,58 @@
+use ext::module::Module;
+use ext::steenrod_evaluator::SteenrodCalculator;
+use ext::utils::{construct, Config};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn calculator_module_action() {
+    let bundle = construct(&Config {
+        module_paths: vec![PathBuf::from("steenrod_modules")],
+        module_file_name: "Joker".to_string(),
+        algebra_name: "adem".to_string(),
+        max_degree: 4,
+    })
+    .unwrap();
+    let module = &*bundle.module;
+    let mut lookup = HashMap::new();
+    for degree in 0..=4 {
+        for idx in 0..module.dimension(degree) {
+            lookup.insert(module.basis_element_to_string(degree, idx), (degree, idx));
+        }
+    }
+
+    let calculator = SteenrodCalculator::new(module.prime());
+    for &(input, degree, output) in &[
+        ("Sq2 * x0 + Sq1 * x1", 2, "x2"),
+        ("Sq2 Sq1 * x0 + Sq2 * x1", 3, "0"),
+        ("χ(Sq2 Sq1) * x1", 4, "x4"),
+        ("Sq2^2 * x0", 4, "x4"),
+    ] {
+        let (d, result) = calculator.evaluate_module(module, &lookup, input).unwrap();
+        assert_eq!(d, degree, "{}", input);
+        assert_eq!(module.element_to_string(d, &result), output, "{}", input);
+    }
+    assert!(calculator
+        .evaluate_module(module, &lookup, "Sq1 * x5")
+        .is_err());
+}
+
+#[test]
+fn calculator_command_line_errors() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ext"))
+        .args(["calc", "Sq2 * "])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("Parse error"), "{}", stderr);
+    assert!(!stderr.contains("backtrace"), "{}", stderr);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ext"))
+        .args(["calc", "-p", "3", "-m", "Joker", "Sq2 * x0"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
@@ -0,0 +1,162 @@
+use ext::utils::construct_from_json;
+use serde_json::{json, Value};
+
+fn error(json: Value, algebra: &str) -> Option<String> {
+    construct_from_json(json, algebra.to_string())
+        .err()
+        .map(|e| e.to_string())
+}
+
+fn rp3(products: Vec<&str>) -> Value {
+    json!({
+        "type": "finite dimensional module",
+        "p": 2,
+        "unstable": true,
+        "gens": {"x1": 1, "x2": 2, "x3": 3},
+        "actions": ["Sq1 x1 = x2"],
+        "cup_products": products
+    })
+}
+
+#[test]
+fn unstable_modules() {
+    for &algebra in &["adem", "milnor"] {
+        assert_eq!(
+            error(
+                rp3(vec!["x1 * x1 = x2", "x1 * x2 = x3", "x2 * x1 = x3"]),
+                algebra
+            ),
+            None
+        );
+
+        let lens_space = json!({
+            "type": "finite dimensional module",
+            "p": 3,
+            "unstable": true,
+            "gens": {"x": 1, "y": 2, "xy": 3, "y2": 4},
+            "actions": ["b x = y", "b xy = y2"],
+            "cup_products": ["x * y = xy", "y * x = xy", "y * y = y2"]
+        });
+        assert_eq!(error(lens_space, algebra), None);
+
+        // Products only need to be listed in one order.
+        assert_eq!(
+            error(rp3(vec!["x1 * x1 = x2", "x1 * x2 = x3"]), algebra),
+            None
+        );
+        assert_eq!(error(lens_space_6(true), algebra), None);
+    }
+}
+
+/// The cohomology of the 6-skeleton of the lens space L^\infty(3), where P1 y = y^3 if `power`
+/// is true.
+fn lens_space_6(power: bool) -> Value {
+    let mut actions = vec!["b x = y", "b xy = y2", "b xy2 = y3"];
+    if power {
+        actions.push("P1 y = y3");
+    }
+    json!({
+        "type": "finite dimensional module",
+        "p": 3,
+        "unstable": true,
+        "gens": {"x": 1, "y": 2, "xy": 3, "y2": 4, "xy2": 5, "y3": 6},
+        "actions": actions,
+        "cup_products": [
+            "x * y = xy",
+            "x * y2 = xy2",
+            "y * y = y2",
+            "y * xy = xy2",
+            "y * y2 = y3",
+        ]
+    })
+}
+
+#[test]
+fn instability_failure() {
+    for &algebra in &["adem", "milnor"] {
+        let c_eta = json!({
+            "type": "finite dimensional module",
+            "p": 2,
+            "unstable": true,
+            "gens": {"x0": 0, "x2": 2},
+            "actions": ["Sq2 x0 = x2"]
+        });
+        let message = error(c_eta, algebra).unwrap();
+        assert!(message.contains("not unstable"), "{}", message);
+        assert!(message.contains("x0 = x2"), "{}", message);
+
+        let negative = json!({
+            "type": "finite dimensional module",
+            "p": 2,
+            "unstable": true,
+            "gens": {"x-1": -1}
+        });
+        assert!(error(negative, algebra).is_some());
+
+        let moore = json!({
+            "type": "finite dimensional module",
+            "p": 3,
+            "unstable": true,
+            "gens": {"x0": 0, "x1": 1},
+            "actions": ["b x0 = x1"]
+        });
+        let message = error(moore, algebra).unwrap();
+        assert!(message.contains("x0 = x1"), "{}", message);
+    }
+}
+
+#[test]
+fn cartan_failure() {
+    for &algebra in &["adem", "milnor"] {
+        // RP^4 without Sq1 x3 = x4. Then Sq1(x1 * x2) = 0 but the Cartan formula gives
+        // x2 * x2 + x1 * 0 = x4.
+        let rp4 = json!({
+            "type": "finite dimensional module",
+            "p": 2,
+            "unstable": true,
+            "gens": {"x1": 1, "x2": 2, "x3": 3, "x4": 4},
+            "actions": ["Sq1 x1 = x2", "Sq2 x2 = x4"],
+            "cup_products": ["x1 * x1 = x2", "x1 * x2 = x3", "x1 * x3 = x4", "x2 * x2 = x4"]
+        });
+        let message = error(rp4, algebra).unwrap();
+        assert!(message.contains("Cartan formula failed"), "{}", message);
+        assert!(message.contains("(x1 * x2) = 0"), "{}", message);
+        assert!(message.contains("gives x4"), "{}", message);
+    }
+
+    // x1 * x2 and x2 * x1 have to agree.
+    let message = error(
+        rp3(vec!["x1 * x1 = x2", "x1 * x2 = x3", "x2 * x1 = 0"]),
+        "adem",
+    )
+    .unwrap();
+    assert!(
+        message.contains("cup_products[2]: The product is not graded commutative"),
+        "{}",
+        message
+    );
+
+    let message = error(rp3(vec!["x1 * x1 = x2", "x1 * x4 = x3"]), "adem").unwrap();
+    assert!(
+        message.contains("cup_products[1]: invalid generator x4"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn power_failure() {
+    for &algebra in &["adem", "milnor"] {
+        // Sq1 x1 = x2 but x1 * x1 = 0.
+        let message = error(rp3(vec!["x1 * x2 = x3"]), algebra).unwrap();
+        assert!(message.contains("Unstable condition failed"), "{}", message);
+        assert!(message.contains("x1 = x2"), "{}", message);
+        assert!(message.contains("power of x1 is 0"), "{}", message);
+
+        // P1 y = 0 but y^3 = y3.
+        let message = error(lens_space_6(false), algebra).unwrap();
+        assert!(message.contains("Unstable condition failed"), "{}", message);
+        assert!(message.contains("y = 0"), "{}", message);
+        assert!(message.contains("power of y is y3"), "{}", message);
+    }
+}
@@ -0,0 +1,51 @@
+//! Tests for the JavaScript bindings. These run natively with `cargo test --features wasm`, and in
+//! a headless wasm runtime with `wasm-pack test --node -- --features wasm`.
+#![cfg(feature = "wasm")]
+
+use ext::wasm::{resolve, validate_module, Calculator};
+use serde_json::{json, Value};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn calculator() {
+    let calculator = Calculator::new(2).unwrap();
+    assert_eq!(calculator.prime(), 2);
+    assert_eq!(calculator.evaluate_adem("Sq2 * Sq2").unwrap(), "P3 P1");
+    assert_eq!(calculator.evaluate_milnor("Sq2 * Sq2").unwrap(), "P(1, 1)");
+
+    let calculator = Calculator::new(3).unwrap();
+    assert_eq!(calculator.evaluate_adem("P1 * P1").unwrap(), "2 * P2");
+}
+
+#[test]
+fn small_resolution() {
+    let c2 = json!({
+        "type": "finite dimensional module",
+        "p": 2,
+        "gens": {"x0": 0, "x1": 1},
+        "actions": ["Sq1 x0 = x1"]
+    })
+    .to_string();
+    validate_module(&c2, "milnor").unwrap();
+
+    let result: Value = serde_json::from_str(&resolve(&c2, "adem", 4).unwrap()).unwrap();
+    assert_eq!(result["prime"], 2);
+    assert_eq!(result["min_degree"], 0);
+    assert_eq!(
+        result["dimensions"],
+        json!([
+            [1, 0, 0, 0, 0],
+            [0, 0, 1, 1, 1],
+            [0, 0, 0, 0, 1],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0]
+        ])
+    );
+
+    let invalid = json!({"type": "finite dimensional module", "p": 2, "gens": {"x0": 0}, "actions": ["Sq1 x0 = x1"]});
+    let message = validate_module(&invalid.to_string(), "adem").unwrap_err();
+    assert!(message.contains("zero in degree 1"), "{}", message);
+    assert!(Calculator::new(4).is_err());
+}